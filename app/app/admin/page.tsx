@@ -5,7 +5,12 @@ import { useConnection, useWallet } from '@solana/wallet-adapter-react';
 import { PublicKey, SystemProgram } from '@solana/web3.js';
 import toast from 'react-hot-toast';
 import WalletGate from '@/components/WalletGate';
-import { getProgram, confirmTransaction, formatProgramError } from '@/lib/anchorClient';
+import {
+    getProgram,
+    confirmTransaction,
+    formatProgramError,
+    ALL_ADMIN_PERMISSIONS,
+} from '@/lib/anchorClient';
 import { getConfigPda, getAdminPda, getTrainerPda } from '@/lib/pdas';
 
 /**
@@ -93,7 +98,7 @@ export default function AdminPage() {
             toast.loading('Adding admin...', { id: 'add-admin' });
 
             const tx = await program.methods
-                .addAdmin()
+                .addAdmin(ALL_ADMIN_PERMISSIONS)
                 .accounts({
                     authority: wallet.publicKey,
                     config: configPda,
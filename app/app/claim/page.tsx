@@ -2,12 +2,40 @@
 
 import { useState, useEffect } from 'react';
 import { useConnection, useWallet } from '@solana/wallet-adapter-react';
-import { PublicKey, SystemProgram } from '@solana/web3.js';
+import {
+    ComputeBudgetProgram,
+    Keypair,
+    PublicKey,
+    SystemProgram,
+    SYSVAR_RENT_PUBKEY,
+} from '@solana/web3.js';
+import {
+    ASSOCIATED_TOKEN_PROGRAM_ID,
+    TOKEN_2022_PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+    getAssociatedTokenAddressSync,
+} from '@solana/spl-token';
 import toast from 'react-hot-toast';
 import { useSearchParams } from 'next/navigation';
 import WalletButton from '@/components/WalletButton';
-import { getProgram, confirmTransaction, formatProgramError } from '@/lib/anchorClient';
-import { getTrainerPda, getAttendancePda, getMonthlyCounterPda } from '@/lib/pdas';
+import {
+    getProgram,
+    confirmTransaction,
+    formatProgramError,
+    TOKEN_METADATA_PROGRAM_ID,
+    CLAIM_COMPUTE_UNITS,
+} from '@/lib/anchorClient';
+import {
+    getConfigPda,
+    getTrainerPda,
+    getAttendancePda,
+    getBookingPda,
+    getMonthlyCounterPda,
+    getNftAuthorityPda,
+    getMetadataPda,
+    getMasterEditionPda,
+    getCollectionAuthorityRecordPda,
+} from '@/lib/pdas';
 import { base64ToUint8Array } from '@/lib/hash';
 import { yyyymmddToYyyymm } from '@/lib/time';
 
@@ -51,37 +79,96 @@ export default function ClaimPage() {
 
                 toast.loading('Claiming your workout attendance...', { id: 'claim' });
 
-                // Fetch instance data
+                // Fetch instance and config data
                 const program = getProgram(connection, wallet as any);
                 const instanceAccount = await program.account.workoutInstance.fetch(instancePubkey);
+                const [configPda] = getConfigPda();
+                const config = await program.account.config.fetch(configPda);
 
                 const trainerPubkey = instanceAccount.trainer as PublicKey;
                 const yyyymmdd = instanceAccount.yyyymmdd as number;
                 const yyyymm = yyyymmddToYyyymm(yyyymmdd);
 
+                // Classic mode mints a Metaplex NFT with the SPL Token program,
+                // soulbound mode a non-transferable Token-2022 NFT
+                const soulbound = 'soulbound' in (config.mintMode as object);
+                const tokenProgram = soulbound ? TOKEN_2022_PROGRAM_ID : TOKEN_PROGRAM_ID;
+
                 // Derive PDAs
                 const [trainerPda] = getTrainerPda(trainerPubkey);
                 const [attendancePda] = getAttendancePda(instancePubkey, wallet.publicKey);
+                // Passed even without a booking; the program consumes it if it exists
+                const [bookingPda] = getBookingPda(instancePubkey, wallet.publicKey);
                 const [monthlyCounterPda] = getMonthlyCounterPda(wallet.publicKey, yyyymm);
+                const [nftAuthorityPda] = getNftAuthorityPda();
+
+                // Fresh mint for the attendance NFT (signs the transaction)
+                const nftMint = Keypair.generate();
+                const nftTokenAccount = getAssociatedTokenAddressSync(
+                    nftMint.publicKey,
+                    wallet.publicKey,
+                    false,
+                    tokenProgram
+                );
+
+                // Collection accounts, required when Config.collection_mint is set
+                const collectionMint = config.collectionMint as PublicKey | null;
+                const collectionAccounts =
+                    collectionMint && !soulbound
+                        ? {
+                              collectionMint,
+                              collectionMetadata: getMetadataPda(collectionMint)[0],
+                              collectionMasterEdition: getMasterEditionPda(collectionMint)[0],
+                              collectionAuthorityRecord: getCollectionAuthorityRecordPda(
+                                  collectionMint,
+                                  nftAuthorityPda
+                              )[0],
+                          }
+                        : {
+                              collectionMint: null,
+                              collectionMetadata: null,
+                              collectionMasterEdition: null,
+                              collectionAuthorityRecord: null,
+                          };
 
-                // Call claim_nft instruction (simplified - just records attendance)
+                // Call claim_nft with the QR secret as proof
                 const tx = await program.methods
-                    .claimNft(Array.from(revealSecret))
+                    .claimNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
                     .accounts({
                         user: wallet.publicKey,
+                        config: configPda,
                         instance: instancePubkey,
                         trainer: trainerPda,
                         attendance: attendancePda,
                         monthlyCounter: monthlyCounterPda,
+                        nftMint: nftMint.publicKey,
+                        nftTokenAccount,
+                        nftMetadata: soulbound ? null : getMetadataPda(nftMint.publicKey)[0],
+                        nftMasterEdition: soulbound
+                            ? null
+                            : getMasterEditionPda(nftMint.publicKey)[0],
+                        nftAuthority: nftAuthorityPda,
+                        ...collectionAccounts,
+                        tokenProgram,
+                        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                        tokenMetadataProgram: soulbound ? null : TOKEN_METADATA_PROGRAM_ID,
+                        booking: bookingPda,
+                        secretChain: null,
+                        instructionsSysvar: null,
                         systemProgram: SystemProgram.programId,
+                        rent: SYSVAR_RENT_PUBKEY,
                     })
+                    .preInstructions([
+                        ComputeBudgetProgram.setComputeUnitLimit({ units: CLAIM_COMPUTE_UNITS }),
+                    ])
+                    .signers([nftMint])
                     .rpc();
 
                 await confirmTransaction(connection, tx);
 
                 toast.success('Attendance claimed successfully!', { id: 'claim' });
                 setClaimed(true);
-                setClaimedMint(attendancePda.toBase58());
+                setClaimedMint(nftMint.publicKey.toBase58());
             } catch (error: any) {
                 console.error('Claim error:', error);
                 const errorMsg = formatProgramError(error);
@@ -191,7 +278,7 @@ export default function ClaimPage() {
                     </p>
 
                     <div className="bg-white dark:bg-gray-800 rounded-lg p-4 mb-4">
-                        <div className="text-sm text-gray-600 dark:text-gray-400 mb-2">Attendance Record:</div>
+                        <div className="text-sm text-gray-600 dark:text-gray-400 mb-2">Attendance NFT:</div>
                         <div className="font-mono text-xs break-all text-gray-900 dark:text-gray-100">
                             {claimedMint}
                         </div>
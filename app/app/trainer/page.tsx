@@ -3,11 +3,12 @@
 import { useState, useEffect } from 'react';
 import { useConnection, useWallet } from '@solana/wallet-adapter-react';
 import { PublicKey, SystemProgram } from '@solana/web3.js';
+import { BN } from '@coral-xyz/anchor';
 import toast from 'react-hot-toast';
 import WalletGate from '@/components/WalletGate';
 import QrDisplay from '@/components/QrDisplay';
 import { getProgram, confirmTransaction, formatProgramError } from '@/lib/anchorClient';
-import { getTrainerPda, getWorkoutInstancePda, getAdminPda } from '@/lib/pdas';
+import { getConfigPda, getTrainerPda, getWorkoutInstancePda, getAdminPda } from '@/lib/pdas';
 import { generateSecret, sha256, uint8ArrayToBase64 } from '@/lib/hash';
 import { dateToYyyymmdd, getCurrentTimestamp, formatYyyymmdd, formatTime } from '@/lib/time';
 
@@ -109,6 +110,7 @@ export default function TrainerPage() {
 
             // Derive PDAs
             const program = getProgram(connection, wallet as any);
+            const [configPda] = getConfigPda();
             const [trainerPda] = getTrainerPda(wallet.publicKey);
             const [instancePda] = getWorkoutInstancePda(
                 wallet.publicKey,
@@ -135,16 +137,23 @@ export default function TrainerPage() {
                     yyyymmdd,
                     hourNum,
                     minuteNum,
-                    { toNumber: () => windowStartTs } as any,
-                    { toNumber: () => windowEndTs } as any,
+                    new BN(windowStartTs),
+                    new BN(windowEndTs),
                     Array.from(secretHash),
-                    null
+                    null, // No metadata URI override
+                    { revealSecret: {} },
+                    null, // No allowlist
+                    null, // No capacity limit
+                    false, // No booking required
+                    null // No check-in phase
                 )
                 .accounts({
                     authority: wallet.publicKey,
                     admin: adminAccount,
+                    config: configPda,
                     trainer: trainerPda,
                     instance: instancePda,
+                    secretChain: null, // Only needed for hash-chain sessions
                     systemProgram: SystemProgram.programId,
                 })
                 .rpc();
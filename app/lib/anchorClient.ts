@@ -23,6 +23,20 @@ export const SYSVAR_INSTRUCTIONS_PUBKEY = new PublicKey(
     'Sysvar1nstructions1111111111111111111111111'
 );
 
+// Compute units requested for claim_nft (mint, metadata and master edition CPIs)
+export const CLAIM_COMPUTE_UNITS = 400_000;
+
+// Admin permission bits (see Admin in programs/workout_poap/src/state/admin.rs)
+export const ADMIN_PERMISSIONS = {
+    REGISTER_TRAINERS: 1 << 0,
+    MANAGE_TRAINERS: 1 << 1,
+    MANAGE_SCHEDULES: 1 << 2,
+    MANAGE_INSTANCES: 1 << 3,
+    GRANT_ATTENDANCE: 1 << 4,
+    REVOKE_ATTENDANCE: 1 << 5,
+};
+export const ALL_ADMIN_PERMISSIONS = Object.values(ADMIN_PERMISSIONS).reduce((a, b) => a | b, 0);
+
 /**
  * Get an Anchor program instance for the Workout POAP program
  * 
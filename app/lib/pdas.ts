@@ -95,6 +95,24 @@ export function getAttendancePda(
     );
 }
 
+/**
+ * Derive a Booking PDA
+ * Seeds: ["booking", instance_pubkey, user_pubkey]
+ */
+export function getBookingPda(
+    instancePubkey: PublicKey,
+    userPubkey: PublicKey
+): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [
+            Buffer.from('booking'),
+            instancePubkey.toBuffer(),
+            userPubkey.toBuffer(),
+        ],
+        PROGRAM_ID
+    );
+}
+
 /**
  * Derive a MonthlyCounter PDA
  * Seeds: ["monthly", user_pubkey, yyyymm (u32 LE)]
@@ -141,3 +159,32 @@ export function getMasterEditionPda(mint: PublicKey): [PublicKey, number] {
     );
 }
 
+/**
+ * Derive the program's NFT authority PDA (mint, freeze and update authority of attendance NFTs)
+ * Seeds: ["nft_authority"]
+ */
+export function getNftAuthorityPda(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from('nft_authority')],
+        PROGRAM_ID
+    );
+}
+
+/**
+ * Derive the Metaplex collection authority record delegating a collection to `authority`
+ */
+export function getCollectionAuthorityRecordPda(
+    collectionMint: PublicKey,
+    authority: PublicKey
+): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [
+            Buffer.from('metadata'),
+            TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+            collectionMint.toBuffer(),
+            Buffer.from('collection_authority'),
+            authority.toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM_ID
+    );
+}
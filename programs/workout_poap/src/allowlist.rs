@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+/// Domain separator for leaves, so an inner node can never pass as a leaf.
+const LEAF_PREFIX: &[u8] = &[0];
+
+/// Domain separator for inner nodes.
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Leaf of the allowlist tree for `user`: SHA-256(0x00 || user).
+pub fn allowlist_leaf(user: &Pubkey) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, user.as_ref()]).to_bytes()
+}
+
+/// Parent of two nodes: SHA-256(0x01 || min(a, b) || max(a, b)).
+///
+/// Children are sorted before hashing, so proofs don't need left/right flags.
+pub fn allowlist_parent(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, lo, hi]).to_bytes()
+}
+
+/// Check that `user` is included in the allowlist tree with root `root`.
+pub fn verify_allowlist_proof(root: &[u8; 32], user: &Pubkey, proof: &[[u8; 32]]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(allowlist_leaf(user), |node, sibling| allowlist_parent(&node, sibling));
+    computed == *root
+}
+
+/// Off-chain builder for allowlist trees and inclusion proofs, using the same
+/// leaf and node format the program verifies.
+///
+/// A level with an odd number of nodes carries its last node up unchanged.
+///
+/// # Example
+/// ```
+/// use anchor_lang::prelude::Pubkey;
+/// use workout_poap::allowlist::{verify_allowlist_proof, AllowlistTree};
+///
+/// let members: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+/// let tree = AllowlistTree::new(&members);
+/// let proof = tree.proof(&members[3]).unwrap();
+/// assert!(verify_allowlist_proof(&tree.root(), &members[3], &proof));
+/// assert!(tree.proof(&Pubkey::new_unique()).is_none());
+/// ```
+#[cfg(not(target_os = "solana"))]
+pub struct AllowlistTree {
+    /// levels[0] holds the leaves, the last level holds the root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl AllowlistTree {
+    /// Build the tree for `users`. Duplicates are removed.
+    pub fn new(users: &[Pubkey]) -> Self {
+        let mut leaves: Vec<[u8; 32]> = users.iter().map(allowlist_leaf).collect();
+        leaves.sort_unstable();
+        leaves.dedup();
+
+        let mut levels = vec![leaves];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => allowlist_parent(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// Root to pass to open_workout_instance (all zeros for an empty list).
+    pub fn root(&self) -> [u8; 32] {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Inclusion proof for `user`, or None if they are not on the list.
+    pub fn proof(&self, user: &Pubkey) -> Option<Vec<[u8; 32]>> {
+        let leaf = allowlist_leaf(user);
+        let mut index = self.levels[0].iter().position(|l| *l == leaf)?;
+        let mut proof = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
@@ -0,0 +1,115 @@
+/// Smallest allowed UTC offset in minutes (UTC-12:00).
+pub const MIN_UTC_OFFSET_MINS: i16 = -12 * 60;
+
+/// Largest allowed UTC offset in minutes (UTC+14:00).
+pub const MAX_UTC_OFFSET_MINS: i16 = 14 * 60;
+
+/// Number of days from 1970-01-01 to the given proleptic Gregorian date.
+///
+/// Uses Howard Hinnant's `days_from_civil` algorithm; `month` is 1-12 and
+/// `day` is 1-31. The date is not validated.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The proleptic Gregorian date `(year, month, day)` that is `days` days after
+/// 1970-01-01.
+///
+/// Inverse of `days_from_civil` (Howard Hinnant's `civil_from_days`).
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// Unix timestamp of the end of month `yyyymm` (midnight UTC on the first day
+/// of the following month).
+pub fn month_end_ts(yyyymm: u32) -> i64 {
+    let (year, month) = (i64::from(yyyymm / 100), yyyymm % 100);
+    let (year, month) = if month >= 12 { (year + 1, 1) } else { (year, month + 1) };
+    days_from_civil(year, month, 1) * 86_400
+}
+
+/// Number of days in `month` (1-12) of `year`.
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// Whether `yyyymmdd` is a real calendar date between years 1970 and 9999.
+pub fn is_valid_date(yyyymmdd: u32) -> bool {
+    let (year, month, day) = (i64::from(yyyymmdd / 10_000), (yyyymmdd / 100) % 100, yyyymmdd % 100);
+    (1970..=9999).contains(&year) && day >= 1 && day <= days_in_month(year, month)
+}
+
+/// Whether `hour:minute` is a valid wall-clock time (00:00 to 23:59).
+pub fn is_valid_time(hour: u8, minute: u8) -> bool {
+    hour <= 23 && minute <= 59
+}
+
+/// Whether `utc_offset_mins` is a real-world UTC offset (-12:00 to +14:00).
+pub fn is_valid_utc_offset(utc_offset_mins: i16) -> bool {
+    (MIN_UTC_OFFSET_MINS..=MAX_UTC_OFFSET_MINS).contains(&utc_offset_mins)
+}
+
+/// Number of days from 1970-01-01 to the date `yyyymmdd`.
+pub fn days_from_yyyymmdd(yyyymmdd: u32) -> i64 {
+    days_from_civil(
+        i64::from(yyyymmdd / 10_000),
+        (yyyymmdd / 100) % 100,
+        yyyymmdd % 100,
+    )
+}
+
+/// Day of week of `yyyymmdd`: 0 = Sunday, 1 = Monday, ..., 6 = Saturday.
+pub fn day_of_week(yyyymmdd: u32) -> u8 {
+    // 1970-01-01 was a Thursday
+    (days_from_yyyymmdd(yyyymmdd) + 4).rem_euclid(7) as u8
+}
+
+/// Unix timestamp of `hour:minute` on `yyyymmdd`, taking the wall-clock time as UTC.
+pub fn wall_clock_ts(yyyymmdd: u32, hour: u8, minute: u8) -> i64 {
+    days_from_yyyymmdd(yyyymmdd) * 86_400 + i64::from(hour) * 3_600 + i64::from(minute) * 60
+}
+
+/// Unix timestamp of `hour:minute` on `yyyymmdd` in a time zone `utc_offset_mins`
+/// ahead of UTC.
+pub fn local_time_ts(yyyymmdd: u32, hour: u8, minute: u8, utc_offset_mins: i16) -> i64 {
+    wall_clock_ts(yyyymmdd, hour, minute) - i64::from(utc_offset_mins) * 60
+}
+
+/// Date (YYYYMMDD) of the Unix timestamp `ts` in a time zone `utc_offset_mins`
+/// ahead of UTC.
+pub fn local_yyyymmdd(ts: i64, utc_offset_mins: i16) -> u32 {
+    let local_ts = ts + i64::from(utc_offset_mins) * 60;
+    let (year, month, day) = civil_from_days(local_ts.div_euclid(86_400));
+    year as u32 * 10_000 + month * 100 + day
+}
+
+/// Month (YYYYMM) of the Unix timestamp `ts` in a time zone `utc_offset_mins`
+/// ahead of UTC.
+pub fn local_yyyymm(ts: i64, utc_offset_mins: i16) -> u32 {
+    let local_ts = ts + i64::from(utc_offset_mins) * 60;
+    let (year, month, _) = civil_from_days(local_ts.div_euclid(86_400));
+    year as u32 * 100 + month
+}
@@ -41,5 +41,199 @@ pub enum WorkoutError {
     
     #[msg("Metadata URI override too long (max 200 bytes)")]
     MetadataUriTooLong,
+    
+    #[msg("Collection accounts are missing or do not match Config.collection_mint")]
+    CollectionMismatch,
+    
+    #[msg("Collection authority has not been delegated to the program")]
+    CollectionAuthorityNotDelegated,
+    
+    #[msg("Compressed minting is not enabled: Config has no Merkle tree")]
+    CompressionNotEnabled,
+    
+    #[msg("Merkle tree does not match Config.merkle_tree")]
+    MerkleTreeMismatch,
+    
+    #[msg("Token program does not match Config.mint_mode")]
+    TokenProgramMismatch,
+    
+    #[msg("Metadata, master edition and token metadata program are required in classic mode")]
+    MissingMetadataAccounts,
+    
+    #[msg("Metadata URI template too long (max 200 bytes)")]
+    UriTemplateTooLong,
+    
+    #[msg("Claim proof does not match the instance's claim method")]
+    WrongClaimMethod,
+    
+    #[msg("Instructions sysvar is required to verify a trainer voucher")]
+    MissingInstructionsSysvar,
+    
+    #[msg("Trainer voucher must be signed by an Ed25519 instruction right before the claim")]
+    MissingVoucherSignature,
+    
+    #[msg("Invalid trainer voucher: malformed signature instruction or message")]
+    InvalidVoucher,
+    
+    #[msg("Trainer voucher was not signed by the instance's trainer")]
+    VoucherSignerMismatch,
+    
+    #[msg("Trainer voucher has expired")]
+    VoucherExpired,
+    
+    #[msg("Invalid rotation period: must be non-zero and give at most 240 periods per claim window")]
+    InvalidRotationPeriod,
+    
+    #[msg("The trainer's secret chain is required for hash-chain sessions")]
+    MissingSecretChain,
+    
+    #[msg("The trainer's secret chain is exhausted; commit a new one")]
+    SecretChainExhausted,
+    
+    #[msg("User is not on this workout's allowlist")]
+    NotOnAllowlist,
+    
+    #[msg("Workout is full: max_claims reached")]
+    CapacityReached,
+    
+    #[msg("Waitlist is full (max 20 users)")]
+    WaitlistFull,
+    
+    #[msg("Promoted booking must belong to the first user on the waitlist")]
+    WaitlistPromotionMismatch,
+    
+    #[msg("This workout requires a booking to claim")]
+    BookingRequired,
+    
+    #[msg("Booking is not confirmed (still on the waitlist)")]
+    BookingNotConfirmed,
+    
+    #[msg("This workout has no check-in phase")]
+    CheckInNotEnabled,
+    
+    #[msg("User has already checked in to this workout")]
+    AlreadyCheckedIn,
+    
+    #[msg("This workout requires checking in before claiming")]
+    NotCheckedIn,
+    
+    #[msg("Check-out too early: minimum workout duration not reached")]
+    CheckOutTooEarly,
+    
+    #[msg("Attendance for this workout has been revoked")]
+    AttendanceRevoked,
+    
+    #[msg("No NFT has been claimed for this attendance")]
+    AttendanceNotClaimed,
+    
+    #[msg("Missing the attendance NFT's mint, token account or token program")]
+    MissingNftAccounts,
+    
+    #[msg("NFT accounts do not match the attendance record")]
+    NftMismatch,
+    
+    #[msg("The workout's claim window has not ended yet")]
+    ClaimWindowNotEnded,
+    
+    #[msg("The workout instance must be reclaimed first")]
+    InstanceNotReclaimed,
+    
+    #[msg("The retention period has not passed yet")]
+    RetentionPeriodActive,
+    
+    #[msg("This workout instance has been cancelled")]
+    InstanceCancelled,
+    
+    #[msg("Cannot cancel a workout after attendance has been claimed")]
+    ClaimsAlreadyMade,
+    
+    #[msg("This schedule is not active")]
+    ScheduleInactive,
+    
+    #[msg("The requested date and time do not match any slot of the schedule")]
+    NotAScheduledSlot,
+    
+    #[msg("Invalid schedule slot: day of week, time or duration out of range")]
+    InvalidSlot,
+    
+    #[msg("Schedule contains the same slot twice")]
+    DuplicateSlot,
+    
+    #[msg("Invalid date: expected a real calendar date in YYYYMMDD format")]
+    InvalidDate,
+    
+    #[msg("Invalid date range: end date is before start date")]
+    InvalidDateRange,
+    
+    #[msg("Too many blackout dates (max 30)")]
+    TooManyBlackoutDates,
+    
+    #[msg("UTC offset must be between -12:00 and +14:00")]
+    InvalidUtcOffset,
+    
+    #[msg("The schedule does not apply on this date (outside its date range or a blackout date)")]
+    DateNotInSchedule,
+    
+    #[msg("Only the schedule's creator or their delegates can edit it")]
+    NotScheduleEditor,
+    
+    #[msg("Only the schedule's creator can do this")]
+    NotScheduleOwner,
+    
+    #[msg("Too many schedule delegates (max 5)")]
+    TooManyDelegates,
+    
+    #[msg("Invalid time: hour must be 0-23 and minute 0-59")]
+    InvalidTime,
+    
+    #[msg("Claim window must lie within a day of the stated workout time")]
+    ImplausibleClaimWindow,
+    
+    #[msg("Trainer is inactive")]
+    TrainerInactive,
+    
+    #[msg("Trainer must be deactivated before removal")]
+    TrainerStillActive,
+    
+    #[msg("Too many specialty tags (max 8)")]
+    TooManySpecialties,
+    
+    #[msg("Specialty tag too long (max 32 bytes)")]
+    SpecialtyTooLong,
+    
+    #[msg("Admin lacks the permission required for this action")]
+    MissingPermission,
+    
+    #[msg("Unknown admin permission bits")]
+    InvalidPermissions,
+    
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthorityTransfer,
+    
+    #[msg("Signer is not the proposed new authority")]
+    NotPendingAuthority,
+    
+    #[msg("Account already uses the current layout")]
+    AlreadyMigrated,
+    
+    #[msg("Invalid secret chain length: must be at least 1")]
+    InvalidChainLength,
+    
+    #[msg("Booking was already used to claim this workout")]
+    BookingConsumed,
+    
+    #[msg("Trainer profile or artwork URI too long (max 200 bytes)")]
+    ProfileUriTooLong,
+    
+    #[msg("Too many hash-chain sessions are waiting for their first claim (max 16)")]
+    TooManyUnrevealedLinks,
+    
+    #[msg("The trainer's secret chain was recommitted after this workout was opened")]
+    StaleChainLink,
+    
+    #[msg("This session's chain link was exposed by a later session's claim")]
+    ChainLinkExposed,
+    
+    #[msg("The claim window of a rotating-code instance cannot start at a different time")]
+    RotationStartFixed,
 }
-
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::state::{BookingStatus, CancelReason, GrantReason, MintMode, MonthSource, RevokeReason};
 
 /// Event emitted when a new workout instance is opened.
 /// 
@@ -38,7 +39,7 @@ pub struct Claimed {
     /// The user who claimed
     pub user: Pubkey,
     
-    /// The minted NFT address
+    /// The minted NFT address (the asset id for compressed NFTs)
     pub nft_mint: Pubkey,
     
     /// The month this claim counts towards (YYYYMM format)
@@ -46,6 +47,9 @@ pub struct Claimed {
     
     /// The user's new total for this month
     pub new_monthly_count: u32,
+    
+    /// The claimant's position among this workout's attendees (1 = first)
+    pub ordinal: u32,
 }
 
 /// Event emitted when a workout instance is closed.
@@ -70,11 +74,78 @@ pub struct TrainerRegistered {
     pub display_name: String,
 }
 
+/// Event emitted when a trainer's profile is updated.
+#[event]
+pub struct TrainerUpdated {
+    /// The trainer's public key
+    pub trainer: Pubkey,
+    
+    /// The trainer or admin who made the change
+    pub updated_by: Pubkey,
+    
+    /// The display name before the update
+    pub old_display_name: String,
+    
+    /// The display name after the update
+    pub new_display_name: String,
+    
+    /// The bio URI after the update
+    pub bio_uri: String,
+    
+    /// The avatar URI after the update
+    pub avatar_uri: String,
+    
+    /// The specialty tags after the update
+    pub specialties: Vec<String>,
+    
+    /// The default artwork URI after the update
+    pub default_artwork_uri: String,
+}
+
+/// Event emitted when an admin activates or deactivates a trainer.
+#[event]
+pub struct TrainerActiveChanged {
+    /// The trainer's public key
+    pub trainer: Pubkey,
+    
+    /// The admin who made the change
+    pub changed_by: Pubkey,
+    
+    /// Whether the trainer is now active
+    pub is_active: bool,
+}
+
+/// Event emitted when a trainer is removed.
+#[event]
+pub struct TrainerRemoved {
+    /// The trainer's public key
+    pub trainer: Pubkey,
+    
+    /// The admin who removed the trainer
+    pub removed_by: Pubkey,
+}
+
 /// Event emitted when a new admin is added.
 #[event]
 pub struct AdminAdded {
     /// The new admin's public key
     pub admin: Pubkey,
+    
+    /// The admin's permission bitmask
+    pub permissions: u16,
+}
+
+/// Event emitted when the super-admin changes an admin's permissions.
+#[event]
+pub struct AdminPermissionsUpdated {
+    /// The admin's public key
+    pub admin: Pubkey,
+    
+    /// The permission bitmask before the change
+    pub old_permissions: u16,
+    
+    /// The permission bitmask after the change
+    pub new_permissions: u16,
 }
 
 /// Event emitted when an admin is removed.
@@ -84,3 +155,286 @@ pub struct AdminRemoved {
     pub admin: Pubkey,
 }
 
+/// Event emitted when the program's Bubblegum Merkle tree is created.
+#[event]
+pub struct MerkleTreeCreated {
+    /// The Merkle tree account
+    pub merkle_tree: Pubkey,
+    
+    /// Maximum depth of the tree (capacity is 2^max_depth leaves)
+    pub max_depth: u32,
+    
+    /// Maximum number of concurrent changes per slot
+    pub max_buffer_size: u32,
+}
+
+/// Event emitted when the super-admin changes how claim_nft mints NFTs.
+#[event]
+pub struct MintModeChanged {
+    /// The previous mint mode
+    pub old_mode: MintMode,
+    
+    /// The new mint mode
+    pub new_mode: MintMode,
+}
+
+/// Event emitted when the super-admin changes the gym's time zone or month source.
+#[event]
+pub struct TimezoneChanged {
+    /// The previous UTC offset in minutes
+    pub old_utc_offset_mins: i16,
+    
+    /// The new UTC offset in minutes
+    pub new_utc_offset_mins: i16,
+    
+    /// The previous month source
+    pub old_month_source: MonthSource,
+    
+    /// The new month source
+    pub new_month_source: MonthSource,
+}
+
+/// Event emitted when the super-admin changes the default metadata URI template.
+#[event]
+pub struct MetadataUriTemplateChanged {
+    /// The new template
+    pub template: String,
+}
+
+/// Event emitted when a trainer commits to a new hash chain of QR secrets.
+#[event]
+pub struct SecretChainCommitted {
+    /// The trainer's public key
+    pub trainer: Pubkey,
+    
+    /// The committed chain tip h^length(seed)
+    pub tip: [u8; 32],
+    
+    /// Number of sessions the chain can serve
+    pub length: u32,
+}
+
+/// Event emitted when a user books a spot in a workout instance.
+#[event]
+pub struct SpotBooked {
+    /// The workout instance
+    pub instance: Pubkey,
+    
+    /// The user who booked
+    pub user: Pubkey,
+    
+    /// Confirmed, or Waitlisted if the instance was full
+    pub status: BookingStatus,
+}
+
+/// Event emitted when a user cancels their booking.
+#[event]
+pub struct BookingCancelled {
+    /// The workout instance
+    pub instance: Pubkey,
+    
+    /// The user who cancelled
+    pub user: Pubkey,
+}
+
+/// Event emitted when a waitlisted user is promoted to a confirmed spot.
+#[event]
+pub struct BookingPromoted {
+    /// The workout instance
+    pub instance: Pubkey,
+    
+    /// The promoted user
+    pub user: Pubkey,
+}
+
+/// Event emitted when a user checks in to a two-phase workout.
+#[event]
+pub struct CheckedIn {
+    /// The workout instance
+    pub instance: Pubkey,
+    
+    /// The user who checked in
+    pub user: Pubkey,
+    
+    /// Unix timestamp of the check-in
+    pub checked_in_at: i64,
+}
+
+/// Event emitted when an admin or trainer grants attendance to a user manually.
+#[event]
+pub struct AttendanceGranted {
+    /// The workout instance
+    pub instance: Pubkey,
+    
+    /// The user who received the attendance NFT
+    pub user: Pubkey,
+    
+    /// The admin or trainer who granted it
+    pub granted_by: Pubkey,
+    
+    /// Why attendance was granted manually
+    pub reason: GrantReason,
+    
+    /// The minted NFT
+    pub nft_mint: Pubkey,
+    
+    /// The attendee's ordinal within the instance
+    pub ordinal: u32,
+    
+    /// The user's new monthly attendance count
+    pub new_monthly_count: u32,
+}
+
+/// Event emitted when an admin revokes a user's attendance.
+#[event]
+pub struct AttendanceRevoked {
+    /// The workout instance
+    pub instance: Pubkey,
+    
+    /// The user whose attendance was revoked
+    pub user: Pubkey,
+    
+    /// The admin who revoked it
+    pub revoked_by: Pubkey,
+    
+    /// Why the attendance was revoked
+    pub reason: RevokeReason,
+    
+    /// The revoked NFT (or compressed asset id)
+    pub nft_mint: Pubkey,
+    
+    /// The user's monthly attendance count after the rollback
+    pub new_monthly_count: u32,
+}
+
+/// Event emitted when an ended workout instance is closed and its rent returned.
+#[event]
+pub struct WorkoutReclaimed {
+    /// The closed workout instance
+    pub instance: Pubkey,
+    
+    /// The trainer who led the workout
+    pub trainer: Pubkey,
+    
+    /// The account that received the rent
+    pub payer: Pubkey,
+}
+
+/// Event emitted when a workout instance is cancelled, so attendees with a
+/// booking can be notified.
+#[event]
+pub struct WorkoutCancelled {
+    /// The cancelled workout instance
+    pub instance: Pubkey,
+    
+    /// The trainer who was to lead the workout
+    pub trainer: Pubkey,
+    
+    /// The admin or trainer who cancelled it
+    pub cancelled_by: Pubkey,
+    
+    /// Why the workout was cancelled
+    pub reason: CancelReason,
+    
+    /// Number of confirmed bookings at the time of cancellation
+    pub booked_count: u32,
+}
+
+/// Event emitted when an open workout instance is updated.
+#[event]
+pub struct WorkoutUpdated {
+    /// The updated workout instance
+    pub instance: Pubkey,
+    
+    /// The admin or trainer who made the change
+    pub updated_by: Pubkey,
+    
+    /// Claim window start before the update
+    pub old_window_start_ts: i64,
+    
+    /// Claim window start after the update
+    pub new_window_start_ts: i64,
+    
+    /// Claim window end before the update
+    pub old_window_end_ts: i64,
+    
+    /// Claim window end after the update
+    pub new_window_end_ts: i64,
+    
+    /// Secret hash before the update
+    pub old_secret_hash: [u8; 32],
+    
+    /// Secret hash after the update
+    pub new_secret_hash: [u8; 32],
+    
+    /// Metadata URI override before the update (empty if none)
+    pub old_metadata_uri: String,
+    
+    /// Metadata URI override after the update (empty if none)
+    pub new_metadata_uri: String,
+}
+
+/// Event emitted when a new schedule is created.
+#[event]
+pub struct ScheduleCreated {
+    /// The schedule account
+    pub schedule: Pubkey,
+    
+    /// The admin who created (and owns) the schedule
+    pub created_by: Pubkey,
+    
+    /// Number of weekly slots
+    pub slot_count: u8,
+    
+    /// Whether the schedule is active
+    pub is_active: bool,
+}
+
+/// Event emitted when an existing schedule's slots, settings or delegates change.
+#[event]
+pub struct ScheduleUpdated {
+    /// The schedule account
+    pub schedule: Pubkey,
+    
+    /// The admin who made the change (the creator or a delegate)
+    pub updated_by: Pubkey,
+    
+    /// Number of weekly slots after the update
+    pub slot_count: u8,
+    
+    /// Whether the schedule is active after the update
+    pub is_active: bool,
+    
+    /// Admins allowed to edit besides the creator, after the update
+    pub delegates: Vec<Pubkey>,
+}
+
+/// Event emitted when the super-admin proposes a new super-admin.
+#[event]
+pub struct AuthorityTransferProposed {
+    /// The current super-admin
+    pub current_authority: Pubkey,
+    
+    /// The proposed new super-admin, who must accept
+    pub proposed_authority: Pubkey,
+}
+
+/// Event emitted when the proposed super-admin accepts the transfer.
+#[event]
+pub struct AuthorityTransferAccepted {
+    /// The previous super-admin
+    pub old_authority: Pubkey,
+    
+    /// The new super-admin
+    pub new_authority: Pubkey,
+}
+
+/// Event emitted when the super-admin cancels a pending transfer.
+#[event]
+pub struct AuthorityTransferCancelled {
+    /// The super-admin who cancelled (unchanged)
+    pub authority: Pubkey,
+    
+    /// The key that had been proposed
+    pub cancelled_authority: Pubkey,
+}
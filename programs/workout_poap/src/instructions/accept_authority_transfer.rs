@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use crate::state::Config;
+use crate::errors::WorkoutError;
+use crate::events::AuthorityTransferAccepted;
+
+/// Accept a pending super-admin transfer (step 2 of 2).
+/// 
+/// Must be signed by the key proposed in propose_authority_transfer, which then
+/// becomes Config.authority.
+pub fn accept_authority_transfer(ctx: Context<AcceptAuthorityTransfer>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let pending_authority = config
+        .pending_authority
+        .ok_or(WorkoutError::NoPendingAuthorityTransfer)?;
+    require_keys_eq!(
+        ctx.accounts.new_authority.key(),
+        pending_authority,
+        WorkoutError::NotPendingAuthority
+    );
+    
+    let old_authority = config.authority;
+    config.authority = pending_authority;
+    config.pending_authority = None;
+    
+    emit!(AuthorityTransferAccepted {
+        old_authority,
+        new_authority: pending_authority,
+    });
+    
+    msg!("Authority transferred: {} -> {}", old_authority, pending_authority);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthorityTransfer<'info> {
+    /// The proposed new super-admin
+    pub new_authority: Signer<'info>,
+    
+    /// The global config account
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
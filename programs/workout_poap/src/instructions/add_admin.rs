@@ -6,27 +6,36 @@ use crate::events::AdminAdded;
 /// Add a new admin to the program.
 /// 
 /// Only the super-admin (Config.authority) can add new admins.
-/// Admins can manage trainers, schedules, and workout instances.
+/// Admins can manage trainers, schedules, and workout instances, as far as
+/// their permissions allow.
 /// 
 /// # Arguments
 /// * `new_admin` - The public key of the account to grant admin privileges
-pub fn add_admin(ctx: Context<AddAdmin>) -> Result<()> {
+/// * `permissions` - Bitmask of Admin permission bits (e.g. Admin::MANAGE_INSTANCES
+///   for front-desk staff, Admin::ALL_PERMISSIONS for full admins)
+pub fn add_admin(ctx: Context<AddAdmin>, permissions: u16) -> Result<()> {
     // Verify the signer is the config authority (super-admin)
     require_keys_eq!(
         ctx.accounts.authority.key(),
         ctx.accounts.config.authority,
         WorkoutError::NotConfigAuthority
     );
+    require!(
+        permissions & !Admin::ALL_PERMISSIONS == 0,
+        WorkoutError::InvalidPermissions
+    );
     
     let admin = &mut ctx.accounts.admin;
     admin.authority = ctx.accounts.new_admin.key();
+    admin.permissions = permissions;
     admin.bump = ctx.bumps.admin;
     
     emit!(AdminAdded {
         admin: admin.authority,
+        permissions,
     });
     
-    msg!("Admin added: {}", admin.authority);
+    msg!("Admin added: {} (permissions {:#06b})", admin.authority, permissions);
     
     Ok(())
 }
@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use crate::state::{Attendance, Booking, BookingStatus, Waitlist, WorkoutInstance};
+use crate::errors::WorkoutError;
+use crate::events::SpotBooked;
+
+/// Reserve a spot in a workout instance.
+///
+/// Any user who has not claimed yet can book an open instance once. While
+/// claims plus confirmed bookings are below the instance's max_claims the
+/// booking is confirmed; after that the user is added to the instance's
+/// waitlist and promoted automatically when a confirmed booking is cancelled.
+/// Instances without max_claims always confirm.
+pub fn book_spot(ctx: Context<BookSpot>) -> Result<()> {
+    let instance = &mut ctx.accounts.instance;
+    instance.require_open()?;
+    require!(
+        !Attendance::is_claimed(&ctx.accounts.attendance)?,
+        WorkoutError::AlreadyClaimed
+    );
+
+    let instance_key = instance.key();
+    let user = ctx.accounts.user.key();
+
+    let waitlist = &mut ctx.accounts.waitlist;
+    if waitlist.instance == Pubkey::default() {
+        // First booking: the waitlist was just created
+        waitlist.instance = instance_key;
+        waitlist.payer = user;
+        waitlist.bump = ctx.bumps.waitlist;
+    }
+
+    let status = if instance.has_free_spot() {
+        instance.booked_count = instance
+            .booked_count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        BookingStatus::Confirmed
+    } else {
+        require!(
+            waitlist.users.len() < Waitlist::MAX_USERS,
+            WorkoutError::WaitlistFull
+        );
+        waitlist.users.push(user);
+        BookingStatus::Waitlisted
+    };
+
+    let booking = &mut ctx.accounts.booking;
+    booking.instance = instance_key;
+    booking.user = user;
+    booking.status = status;
+    booking.booked_at = Clock::get()?.unix_timestamp;
+    booking.bump = ctx.bumps.booking;
+
+    emit!(SpotBooked {
+        instance: instance_key,
+        user,
+        status,
+    });
+
+    msg!("Spot booked by {} for instance {}: {:?}", user, instance_key, status);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct BookSpot<'info> {
+    /// The user booking a spot
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The workout instance to book
+    #[account(
+        mut,
+        seeds = [
+            b"instance",
+            instance.trainer.as_ref(),
+            &instance.yyyymmdd.to_le_bytes(),
+            &[instance.hour],
+            &[instance.minute]
+        ],
+        bump = instance.bump
+    )]
+    pub instance: Account<'info, WorkoutInstance>,
+
+    /// The booking to create (PDA)
+    /// Seeds: ["booking", instance, user]
+    #[account(
+        init,
+        payer = user,
+        space = Booking::LEN,
+        seeds = [b"booking", instance.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub booking: Account<'info, Booking>,
+
+    /// The instance's waitlist (PDA), created by the first booking
+    /// Seeds: ["waitlist", instance]
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = Waitlist::LEN,
+        seeds = [b"waitlist", instance.key().as_ref()],
+        bump
+    )]
+    pub waitlist: Account<'info, Waitlist>,
+
+    /// CHECK: The user's attendance record (PDA), checked so users who already
+    /// claimed cannot hold a spot. May not exist yet
+    /// Seeds: ["attendance", instance, user]
+    #[account(
+        seeds = [b"attendance", instance.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub attendance: UncheckedAccount<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use crate::state::Config;
+use crate::errors::WorkoutError;
+use crate::events::AuthorityTransferCancelled;
+
+/// Cancel a pending super-admin transfer.
+/// 
+/// Only the current super-admin (Config.authority) can call this.
+pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        ctx.accounts.config.authority,
+        WorkoutError::NotConfigAuthority
+    );
+    
+    let config = &mut ctx.accounts.config;
+    let cancelled_authority = config
+        .pending_authority
+        .take()
+        .ok_or(WorkoutError::NoPendingAuthorityTransfer)?;
+    
+    emit!(AuthorityTransferCancelled {
+        authority: config.authority,
+        cancelled_authority,
+    });
+    
+    msg!("Authority transfer to {} cancelled", cancelled_authority);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelAuthorityTransfer<'info> {
+    /// The super-admin authority from Config
+    pub authority: Signer<'info>,
+    
+    /// The global config account
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
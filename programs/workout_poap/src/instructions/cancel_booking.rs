@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use crate::state::{Attendance, Booking, BookingStatus, Waitlist, WorkoutInstance};
+use crate::errors::WorkoutError;
+use crate::events::{BookingCancelled, BookingPromoted};
+
+/// Cancel a booking and refund its rent to the user.
+///
+/// Cancelling a waitlisted booking just removes the user from the waitlist.
+/// Cancelling a confirmed booking frees its spot, which goes to the first user
+/// on the waitlist: the client must then pass that user's booking as
+/// `promoted_booking` so it can be confirmed.
+///
+/// A booking used for a claim is consumed and cannot be cancelled. Neither can
+/// a confirmed booking of a user who claimed without passing it: the claim
+/// already took a spot, so the booking's spot must not be freed again.
+pub fn cancel_booking(ctx: Context<CancelBooking>) -> Result<()> {
+    let instance_key = ctx.accounts.instance.key();
+    let user = ctx.accounts.user.key();
+    let waitlist = &mut ctx.accounts.waitlist;
+
+    match ctx.accounts.booking.status {
+        BookingStatus::Waitlisted => {
+            waitlist.users.retain(|waitlisted| *waitlisted != user);
+        }
+        BookingStatus::Consumed => return err!(WorkoutError::BookingConsumed),
+        BookingStatus::Confirmed => {
+            require!(
+                !Attendance::is_claimed(&ctx.accounts.attendance)?,
+                WorkoutError::BookingConsumed
+            );
+            if waitlist.users.is_empty() {
+                let instance = &mut ctx.accounts.instance;
+                instance.booked_count = instance.booked_count.saturating_sub(1);
+            } else {
+                // Hand the freed spot to the front of the waitlist
+                let promoted_user = waitlist.users.remove(0);
+                let promoted_booking = ctx
+                    .accounts
+                    .promoted_booking
+                    .as_mut()
+                    .ok_or(WorkoutError::WaitlistPromotionMismatch)?;
+                require_keys_eq!(
+                    promoted_booking.user,
+                    promoted_user,
+                    WorkoutError::WaitlistPromotionMismatch
+                );
+                promoted_booking.status = BookingStatus::Confirmed;
+
+                emit!(BookingPromoted {
+                    instance: instance_key,
+                    user: promoted_user,
+                });
+            }
+        }
+    }
+
+    emit!(BookingCancelled {
+        instance: instance_key,
+        user,
+    });
+
+    msg!("Booking cancelled by {} for instance {}", user, instance_key);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelBooking<'info> {
+    /// The user cancelling their booking (receives the rent refund)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The workout instance the booking is for
+    #[account(
+        mut,
+        seeds = [
+            b"instance",
+            instance.trainer.as_ref(),
+            &instance.yyyymmdd.to_le_bytes(),
+            &[instance.hour],
+            &[instance.minute]
+        ],
+        bump = instance.bump
+    )]
+    pub instance: Account<'info, WorkoutInstance>,
+
+    /// The booking to cancel
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"booking", instance.key().as_ref(), user.key().as_ref()],
+        bump = booking.bump
+    )]
+    pub booking: Account<'info, Booking>,
+
+    /// The instance's waitlist
+    #[account(
+        mut,
+        seeds = [b"waitlist", instance.key().as_ref()],
+        bump = waitlist.bump
+    )]
+    pub waitlist: Account<'info, Waitlist>,
+
+    /// CHECK: The user's attendance record (PDA), checked so a confirmed
+    /// booking cannot be cancelled after the user claimed. May not exist
+    /// Seeds: ["attendance", instance, user]
+    #[account(
+        seeds = [b"attendance", instance.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub attendance: UncheckedAccount<'info>,
+
+    /// Optional: booking of the first waitlisted user, required when cancelling
+    /// a confirmed booking while the waitlist is not empty
+    #[account(
+        mut,
+        seeds = [b"booking", instance.key().as_ref(), promoted_booking.user.as_ref()],
+        bump = promoted_booking.bump
+    )]
+    pub promoted_booking: Option<Account<'info, Booking>>,
+}
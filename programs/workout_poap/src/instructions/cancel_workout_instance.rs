@@ -0,0 +1,109 @@
+use crate::errors::WorkoutError;
+use crate::events::WorkoutCancelled;
+use crate::state::{Admin, CancelReason, Trainer, WorkoutInstance};
+use anchor_lang::prelude::*;
+
+/// Cancel a workout instance that should never have been claimable.
+///
+/// Can be called by:
+/// - Any admin, OR
+/// - The assigned trainer
+///
+/// Unlike close_workout_instance, which ends a workout that took place, this
+/// records why the class did not happen. It is only allowed before the first
+/// claim (or grant). The instance is closed as well, so claims, check-ins and
+/// bookings fail with InstanceCancelled; existing bookings can still be cancelled
+/// for their rent.
+///
+/// A deactivated trainer can no longer cancel instances; admins still can, and
+/// may omit the Trainer account once the trainer has been removed.
+///
+/// # Arguments
+/// * `reason` - Why the workout is cancelled, stored on the instance
+pub fn cancel_workout_instance(
+    ctx: Context<CancelWorkoutInstance>,
+    reason: CancelReason,
+) -> Result<()> {
+    let instance = &mut ctx.accounts.instance;
+    instance.require_open()?;
+    require!(instance.claim_count == 0, WorkoutError::ClaimsAlreadyMade);
+
+    instance.cancel_reason = Some(reason);
+    instance.is_closed = true;
+
+    emit!(WorkoutCancelled {
+        instance: instance.key(),
+        trainer: instance.trainer,
+        cancelled_by: ctx.accounts.authority.key(),
+        reason,
+        booked_count: instance.booked_count,
+    });
+
+    msg!(
+        "Workout instance cancelled: {} ({:?})",
+        instance.key(),
+        reason
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelWorkoutInstance<'info> {
+    /// The caller (admin or trainer)
+    pub authority: Signer<'info>,
+
+    /// Optional: Admin account if called by an admin
+    #[account(
+        seeds = [b"admin", authority.key().as_ref()],
+        bump = admin.bump
+    )]
+    pub admin: Option<Account<'info, Admin>>,
+
+    /// The trainer for this workout; required when the trainer calls, optional
+    /// for admins so they can still manage instances of a removed trainer
+    #[account(
+        seeds = [b"trainer", instance.trainer.as_ref()],
+        bump = trainer.bump
+    )]
+    pub trainer: Option<Account<'info, Trainer>>,
+
+    /// The workout instance to cancel
+    #[account(
+        mut,
+        seeds = [
+            b"instance",
+            instance.trainer.as_ref(),
+            &instance.yyyymmdd.to_le_bytes(),
+            &[instance.hour],
+            &[instance.minute]
+        ],
+        bump = instance.bump
+    )]
+    pub instance: Account<'info, WorkoutInstance>,
+}
+
+impl<'info> CancelWorkoutInstance<'info> {
+    /// Validate that the caller is authorized (either admin or the trainer)
+    pub fn validate(&self) -> Result<()> {
+        // If admin account is present and holds the permission, they're authorized
+        if let Some(admin) = &self.admin {
+            require!(
+                admin.has_permission(Admin::MANAGE_INSTANCES),
+                WorkoutError::MissingPermission
+            );
+            return Ok(());
+        }
+
+        // Otherwise, the signer must be the trainer
+        let trainer = self.trainer.as_ref().ok_or(WorkoutError::NotAssignedTrainer)?;
+        require_keys_eq!(
+            self.authority.key(),
+            trainer.authority,
+            WorkoutError::NotAssignedTrainer
+        );
+        require!(trainer.is_active, WorkoutError::TrainerInactive);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::state::{Attendance, WorkoutInstance};
+use crate::errors::WorkoutError;
+use crate::events::CheckedIn;
+
+/// Check in to a two-phase workout instance.
+///
+/// The trainer shows the check-in QR near the start of the workout. Scanning it
+/// records the check-in time on the user's Attendance record; no NFT is minted
+/// and no counter is incremented yet. The user completes attendance by claiming
+/// with the regular (check-out) secret near the end, at least
+/// `min_duration_secs` after checking in.
+///
+/// # Arguments
+/// * `check_in_secret` - The secret shown in the check-in QR code
+pub fn check_in(ctx: Context<CheckIn>, check_in_secret: Vec<u8>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.instance.verify_check_in(now, &check_in_secret)?;
+
+    let attendance = &mut ctx.accounts.attendance;
+    require!(!attendance.revoked, WorkoutError::AttendanceRevoked);
+    require!(
+        attendance.checked_in_at.is_none(),
+        WorkoutError::AlreadyCheckedIn
+    );
+    attendance.instance = ctx.accounts.instance.key();
+    attendance.user = ctx.accounts.user.key();
+    attendance.checked_in_at = Some(now);
+    attendance.bump = ctx.bumps.attendance;
+
+    emit!(CheckedIn {
+        instance: attendance.instance,
+        user: attendance.user,
+        checked_in_at: now,
+    });
+
+    msg!(
+        "User {} checked in to instance {}",
+        attendance.user,
+        attendance.instance
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CheckIn<'info> {
+    /// The user checking in
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The two-phase workout instance
+    #[account(
+        seeds = [
+            b"instance",
+            instance.trainer.as_ref(),
+            &instance.yyyymmdd.to_le_bytes(),
+            &[instance.hour],
+            &[instance.minute]
+        ],
+        bump = instance.bump
+    )]
+    pub instance: Account<'info, WorkoutInstance>,
+
+    /// The attendance record (PDA), completed later by the claim
+    /// Seeds: ["attendance", instance, user]
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = Attendance::LEN,
+        seeds = [b"attendance", instance.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub attendance: Account<'info, Attendance>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
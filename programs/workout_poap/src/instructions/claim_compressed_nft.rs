@@ -0,0 +1,330 @@
+use crate::errors::WorkoutError;
+use crate::events::Claimed;
+use crate::nft::{
+    attendance_nft_uri, workout_nft_name, AttendanceCollection, MintCompressedAttendanceNft,
+    NFT_AUTHORITY_SEED,
+};
+use crate::state::{
+    Attendance, Booking, ClaimProof, Config, MonthlyCounter, SecretChain, Trainer,
+    WorkoutInstance,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use mpl_bubblegum::programs::{SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
+
+/// Claim a compressed NFT for attending a workout.
+///
+/// Same checks, name, URI and monthly counter update as `claim_nft`, but the NFT is
+/// minted as a Bubblegum compressed NFT into Config.merkle_tree instead of a full
+/// mint + metadata + master edition, which costs no rent per claim.
+///
+/// The Attendance record stores the Bubblegum asset id in `nft_mint` and the
+/// tree leaf index in `leaf_index`.
+///
+/// # Arguments
+/// * `proof` - The reveal_secret shown in the QR code (must hash to secret_hash), or
+///   the expiry of a trainer-signed voucher whose Ed25519 signature instruction
+///   directly precedes this one
+/// * `allowlist_proof` - Merkle inclusion proof for the user if the instance has an
+///   allowlist (see `allowlist::AllowlistTree`), otherwise empty
+pub fn claim_compressed_nft(
+    ctx: Context<ClaimCompressedNft>,
+    proof: ClaimProof,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    // 1. Verify compressed minting is enabled and this is the program's tree
+    let merkle_tree = ctx
+        .accounts
+        .config
+        .merkle_tree
+        .ok_or(WorkoutError::CompressionNotEnabled)?;
+    require_keys_eq!(
+        ctx.accounts.merkle_tree.key(),
+        merkle_tree,
+        WorkoutError::MerkleTreeMismatch
+    );
+
+    // 2. Verify the instance is open, the claim window and the secret or voucher
+    let instructions_sysvar = ctx
+        .accounts
+        .instructions_sysvar
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let instance_key = ctx.accounts.instance.key();
+    ctx.accounts.instance.verify_claim(
+        &instance_key,
+        ctx.accounts.user.key,
+        now,
+        &proof,
+        &allowlist_proof,
+        instructions_sysvar.as_ref(),
+        ctx.accounts.secret_chain.as_deref_mut().map(|c| &mut **c),
+    )?;
+
+    // 3. Verify this user hasn't already claimed or had attendance revoked
+    require!(
+        !ctx.accounts.attendance.revoked,
+        WorkoutError::AttendanceRevoked
+    );
+    require!(
+        !ctx.accounts.attendance.claimed,
+        WorkoutError::AlreadyClaimed
+    );
+    let booking_info = ctx.accounts.booking.to_account_info();
+    let mut booking = Booking::load(&booking_info)?;
+    ctx.accounts.instance.verify_booking(booking.as_ref())?;
+    ctx.accounts
+        .instance
+        .verify_check_out(now, ctx.accounts.attendance.checked_in_at)?;
+
+    // 4. Count the claim against the instance's capacity; the ordinal goes into the metadata
+    let ordinal = ctx.accounts.instance.record_claim(booking.as_mut())?;
+    if let Some(booking) = &booking {
+        booking.store(&booking_info)?;
+    }
+    let instance = &ctx.accounts.instance;
+
+    // 5. Mint the compressed attendance NFT into the program's tree
+    let name = workout_nft_name(
+        instance.yyyymmdd,
+        instance.hour,
+        instance.minute,
+        &ctx.accounts.trainer.display_name,
+        ordinal,
+        true,
+    );
+    let uri = attendance_nft_uri(
+        &ctx.accounts.config.metadata_uri_template,
+        &ctx.accounts.trainer.default_artwork_uri,
+        &instance.key(),
+        instance,
+        ctx.accounts.user.key,
+        ordinal,
+    );
+    let nft_authority = ctx.accounts.nft_authority.to_account_info();
+    let collection_mint = ctx.accounts.collection_mint.as_ref().map(|a| a.to_account_info());
+    let collection_master_edition = ctx
+        .accounts
+        .collection_master_edition
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let collection_authority_record = ctx
+        .accounts
+        .collection_authority_record
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let collection = AttendanceCollection::resolve(
+        ctx.accounts.config.collection_mint,
+        collection_mint.as_ref(),
+        ctx.accounts.collection_metadata.as_deref(),
+        collection_master_edition.as_ref(),
+        collection_authority_record.as_ref(),
+        nft_authority.key,
+    )?;
+    let (asset_id, leaf_index, leaf_data_hash) = MintCompressedAttendanceNft {
+        payer: &ctx.accounts.user.to_account_info(),
+        leaf_owner: &ctx.accounts.user.to_account_info(),
+        tree_config: &ctx.accounts.tree_config.to_account_info(),
+        merkle_tree: &ctx.accounts.merkle_tree.to_account_info(),
+        nft_authority: &nft_authority,
+        bubblegum_program: &ctx.accounts.bubblegum_program.to_account_info(),
+        bubblegum_signer: &ctx.accounts.bubblegum_signer.to_account_info(),
+        log_wrapper: &ctx.accounts.log_wrapper.to_account_info(),
+        compression_program: &ctx.accounts.compression_program.to_account_info(),
+        token_metadata_program: &ctx.accounts.token_metadata_program.to_account_info(),
+        system_program: &ctx.accounts.system_program.to_account_info(),
+        collection,
+    }
+    .mint(name, uri, ctx.bumps.nft_authority)?;
+
+    // Month the claim counts toward (see Config.month_source)
+    let yyyymm = ctx.accounts.config.counted_yyyymm(instance.yyyymmdd, now);
+
+    // 6. Mark attendance as claimed
+    let attendance = &mut ctx.accounts.attendance;
+    attendance.instance = ctx.accounts.instance.key();
+    attendance.user = ctx.accounts.user.key();
+    attendance.claimed = true;
+    attendance.nft_mint = asset_id;
+    attendance.leaf_index = Some(leaf_index);
+    attendance.leaf_data_hash = leaf_data_hash;
+    attendance.claimed_at = now;
+    attendance.counted_yyyymm = yyyymm;
+    attendance.bump = ctx.bumps.attendance;
+
+    // 7. Increment monthly counter
+    let new_monthly_count = ctx.accounts.monthly_counter.increment(
+        ctx.accounts.user.key(),
+        yyyymm,
+        ctx.bumps.monthly_counter,
+    )?;
+
+    emit!(Claimed {
+        instance: ctx.accounts.instance.key(),
+        user: ctx.accounts.user.key(),
+        nft_mint: asset_id,
+        yyyymm,
+        new_monthly_count,
+        ordinal,
+    });
+
+    msg!(
+        "Compressed attendance claimed by {} for instance {} (leaf {}, monthly count: {})",
+        ctx.accounts.user.key(),
+        ctx.accounts.instance.key(),
+        leaf_index,
+        new_monthly_count
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimCompressedNft<'info> {
+    /// The user claiming the NFT (becomes the leaf owner)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The global config account (holds the Merkle tree and optional collection mint)
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The workout instance being claimed (mutable so hash-chain instances can
+    /// bind their secret on the first claim)
+    #[account(
+        mut,
+        seeds = [
+            b"instance",
+            instance.trainer.as_ref(),
+            &instance.yyyymmdd.to_le_bytes(),
+            &[instance.hour],
+            &[instance.minute]
+        ],
+        bump = instance.bump
+    )]
+    pub instance: Box<Account<'info, WorkoutInstance>>,
+
+    /// The trainer who led this workout (needed for metadata)
+    #[account(
+        seeds = [b"trainer", instance.trainer.as_ref()],
+        bump = trainer.bump
+    )]
+    pub trainer: Box<Account<'info, Trainer>>,
+
+    /// The attendance record (PDA)
+    /// Seeds: ["attendance", instance, user]
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = Attendance::LEN,
+        seeds = [b"attendance", instance.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub attendance: Box<Account<'info, Attendance>>,
+
+    /// The monthly counter (PDA)
+    /// Seeds: ["monthly", user, yyyymm], yyyymm as chosen by Config.month_source
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = MonthlyCounter::LEN,
+        seeds = [
+            b"monthly",
+            user.key().as_ref(),
+            &config.counted_yyyymm(instance.yyyymmdd, Clock::get()?.unix_timestamp).to_le_bytes()
+        ],
+        bump
+    )]
+    pub monthly_counter: Box<Account<'info, MonthlyCounter>>,
+
+    /// CHECK: The program's Merkle tree, checked against Config.merkle_tree
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum tree config PDA
+    /// Seeds: [merkle_tree] (Bubblegum program)
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        seeds::program = bubblegum_program.key(),
+        bump
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA acting as tree creator and collection authority
+    /// Seeds: ["nft_authority"]
+    #[account(seeds = [NFT_AUTHORITY_SEED], bump)]
+    pub nft_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum's collection CPI signer PDA
+    /// Seeds: ["collection_cpi"] (Bubblegum program)
+    #[account(
+        seeds = [b"collection_cpi"],
+        seeds::program = bubblegum_program.key(),
+        bump
+    )]
+    pub bubblegum_signer: UncheckedAccount<'info>,
+
+    /// CHECK: Optional: the collection mint, required when Config.collection_mint is set
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// Optional: the collection's metadata (mutable so sized collections can count items)
+    #[account(mut)]
+    pub collection_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    /// CHECK: Optional: the collection's master edition, validated by the token metadata program
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional: collection authority record delegating to nft_authority,
+    /// validated against its derived address. Not needed if nft_authority is
+    /// the collection's update authority.
+    pub collection_authority_record: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex Bubblegum program
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Noop program (Bubblegum log wrapper)
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// Metaplex Token Metadata program (used for collection verification)
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// CHECK: The user's booking PDA, passed whether or not the user booked. A
+    /// booking, required if instance.booking_required, is marked consumed by
+    /// the claim and its spot becomes the claim's; without one the claim is a
+    /// walk-in
+    #[account(
+        mut,
+        seeds = [b"booking", instance.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub booking: UncheckedAccount<'info>,
+
+    /// Optional: the trainer's secret chain, required for hash-chain claims
+    #[account(
+        mut,
+        seeds = [b"secret_chain", instance.trainer.as_ref()],
+        bump = secret_chain.bump
+    )]
+    pub secret_chain: Option<Box<Account<'info, SecretChain>>>,
+
+    /// CHECK: Optional: instructions sysvar, required for trainer voucher claims
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
@@ -1,8 +1,18 @@
 use crate::errors::WorkoutError;
 use crate::events::Claimed;
-use crate::state::{Attendance, MonthlyCounter, Trainer, WorkoutInstance};
+use crate::nft::{
+    attendance_nft_uri, workout_nft_name, AttendanceCollection, MetaplexAccounts, MintAttendanceNft,
+    NFT_AUTHORITY_SEED,
+};
+use crate::state::{
+    Attendance, Booking, ClaimProof, Config, MintMode, MonthlyCounter, SecretChain, Trainer,
+    WorkoutInstance,
+};
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token_interface::TokenInterface;
 
 /// Claim an NFT for attending a workout.
 ///
@@ -13,109 +23,226 @@ use anchor_lang::solana_program::hash::hash;
 ///
 /// This instruction:
 /// 1. Verifies the current time is within the claim window
-/// 2. Verifies SHA-256(reveal_secret) matches the stored secret_hash
+/// 2. Verifies SHA-256(reveal_secret) matches the stored secret_hash, or, for
+///    instances using ClaimMethod::TrainerVoucher, that the trainer signed a
+///    voucher for this user (checked via the Ed25519 program and instructions sysvar)
 /// 3. Ensures the user hasn't already claimed for this workout
-/// 4. Mints a new NFT with metadata describing the workout
-/// 5. Increments the user's monthly attendance counter
+///    and, for two-phase instances, checked in at least min_duration_secs ago
+/// 4. Counts the claim against the instance's max_claims capacity
+/// 5. Mints a new NFT with metadata describing the workout and the attendee's ordinal
+/// 6. Increments the user's monthly attendance counter
+///
+/// In classic mode (the default) the NFT is a Metaplex master edition with a
+/// supply of 1. The program's `nft_authority` PDA is its update authority. Mint,
+/// token account, metadata and edition creation together need more than the
+/// default 200k compute units, so clients should prepend a compute budget
+/// instruction (~300k CU).
+///
+/// If `Config.collection_mint` is set, the NFT is also verified as a member of that
+/// collection. The `nft_authority` PDA must be the collection's update authority or
+/// have been approved as a collection authority beforehand.
+///
+/// With `Config.mint_mode` set to Soulbound, the NFT is instead a Token-2022 mint
+/// with the NonTransferable extension, so it can never leave the user's wallet.
+/// Its name, URI, trainer and instance are stored on the mint itself through the
+/// metadata-pointer and token-metadata extensions, and the mint authority is
+/// removed after minting. The Metaplex accounts are not needed in this mode and
+/// soulbound tokens are not verified into the collection.
 ///
 /// # Arguments
-/// * `reveal_secret` - The secret shown in the QR code (must hash to secret_hash)
-pub fn claim_nft(ctx: Context<ClaimNft>, reveal_secret: Vec<u8>) -> Result<()> {
-    let instance = &ctx.accounts.instance;
+/// * `proof` - The reveal_secret shown in the QR code (must hash to secret_hash), or
+///   the expiry of a trainer-signed voucher whose Ed25519 signature instruction
+///   directly precedes this one
+/// * `allowlist_proof` - Merkle inclusion proof for the user if the instance has an
+///   allowlist (see `allowlist::AllowlistTree`), otherwise empty
+pub fn claim_nft(
+    ctx: Context<ClaimNft>,
+    proof: ClaimProof,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Result<()> {
     let clock = Clock::get()?;
     let now = clock.unix_timestamp;
 
-    // 1. Verify the instance is not closed
-    require!(!instance.is_closed, WorkoutError::InstanceClosed);
+    // 1-3. Verify the instance is open, we're within the claim window,
+    // and the reveal_secret or trainer voucher is valid for this user
+    let instructions_sysvar = ctx
+        .accounts
+        .instructions_sysvar
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let instance_key = ctx.accounts.instance.key();
+    ctx.accounts.instance.verify_claim(
+        &instance_key,
+        ctx.accounts.user.key,
+        now,
+        &proof,
+        &allowlist_proof,
+        instructions_sysvar.as_ref(),
+        ctx.accounts.secret_chain.as_deref_mut().map(|c| &mut **c),
+    )?;
 
-    // 2. Verify we're within the claim window
+    // 4. Verify this user hasn't already claimed or had attendance revoked
     require!(
-        now >= instance.window_start_ts && now <= instance.window_end_ts,
-        WorkoutError::InvalidClaimWindow
+        !ctx.accounts.attendance.revoked,
+        WorkoutError::AttendanceRevoked
     );
-
-    // 3. Verify the reveal_secret matches the stored hash
-    let computed_hash = hash(&reveal_secret).to_bytes();
-    require!(
-        computed_hash == instance.secret_hash,
-        WorkoutError::HashMismatch
-    );
-
-    // 4. Verify this user hasn't already claimed
     require!(
         !ctx.accounts.attendance.claimed,
         WorkoutError::AlreadyClaimed
     );
+    let booking_info = ctx.accounts.booking.to_account_info();
+    let mut booking = Booking::load(&booking_info)?;
+    ctx.accounts.instance.verify_booking(booking.as_ref())?;
+    ctx.accounts
+        .instance
+        .verify_check_out(now, ctx.accounts.attendance.checked_in_at)?;
+
+    // 5. Count the claim against the instance's capacity; the ordinal goes into the metadata
+    let ordinal = ctx.accounts.instance.record_claim(booking.as_mut())?;
+    if let Some(booking) = &booking {
+        booking.store(&booking_info)?;
+    }
+    let instance = &ctx.accounts.instance;
+
+    // 6. Mint the attendance NFT in the configured mode
+    let mint_mode = ctx.accounts.config.mint_mode;
+    let name = workout_nft_name(
+        instance.yyyymmdd,
+        instance.hour,
+        instance.minute,
+        &ctx.accounts.trainer.display_name,
+        ordinal,
+        mint_mode == MintMode::Classic,
+    );
+    let uri = attendance_nft_uri(
+        &ctx.accounts.config.metadata_uri_template,
+        &ctx.accounts.trainer.default_artwork_uri,
+        &instance.key(),
+        instance,
+        ctx.accounts.user.key,
+        ordinal,
+    );
+    let additional_metadata = vec![
+        ("trainer".to_string(), ctx.accounts.trainer.display_name.clone()),
+        ("instance".to_string(), instance.key().to_string()),
+        ("ordinal".to_string(), ordinal.to_string()),
+    ];
+    let nft_authority = ctx.accounts.nft_authority.to_account_info();
+    let collection_mint = ctx.accounts.collection_mint.as_ref().map(|a| a.to_account_info());
+    let collection_master_edition = ctx
+        .accounts
+        .collection_master_edition
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let collection_authority_record = ctx
+        .accounts
+        .collection_authority_record
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let nft_metadata = ctx.accounts.nft_metadata.as_ref().map(|a| a.to_account_info());
+    let nft_master_edition = ctx
+        .accounts
+        .nft_master_edition
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let token_metadata_program = ctx
+        .accounts
+        .token_metadata_program
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let rent = ctx.accounts.rent.to_account_info();
+    let metaplex = match (mint_mode, &nft_metadata, &nft_master_edition, &token_metadata_program) {
+        (MintMode::Classic, Some(metadata), Some(master_edition), Some(token_metadata_program)) => {
+            Some(MetaplexAccounts {
+                metadata,
+                master_edition,
+                token_metadata_program,
+                rent: &rent,
+                collection: AttendanceCollection::resolve(
+                    ctx.accounts.config.collection_mint,
+                    collection_mint.as_ref(),
+                    ctx.accounts.collection_metadata.as_deref(),
+                    collection_master_edition.as_ref(),
+                    collection_authority_record.as_ref(),
+                    nft_authority.key,
+                )?,
+            })
+        }
+        (MintMode::Classic, ..) => return err!(WorkoutError::MissingMetadataAccounts),
+        (MintMode::Soulbound, ..) => None,
+    };
+    MintAttendanceNft {
+        payer: &ctx.accounts.user.to_account_info(),
+        owner: &ctx.accounts.user.to_account_info(),
+        mint: &ctx.accounts.nft_mint.to_account_info(),
+        token_account: &ctx.accounts.nft_token_account.to_account_info(),
+        nft_authority: &nft_authority,
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        associated_token_program: &ctx.accounts.associated_token_program.to_account_info(),
+        system_program: &ctx.accounts.system_program.to_account_info(),
+        metaplex,
+    }
+    .mint(mint_mode, name, uri, additional_metadata, ctx.bumps.nft_authority)?;
+    let nft_mint = ctx.accounts.nft_mint.key();
 
-    // 5. Mark attendance and mint info
-    // Note: Actual NFT minting would happen here via Metaplex CPI
-    // For this example, we're simplifying to avoid stack overflow issues
-    // In production, use anchor_spl::metadata::create_metadata_accounts_v3
+    // Month the claim counts toward (see Config.month_source)
+    let yyyymm = ctx.accounts.config.counted_yyyymm(instance.yyyymmdd, now);
 
-    // 6. Mark attendance as claimed
+    // 7. Mark attendance as claimed
     let attendance = &mut ctx.accounts.attendance;
     attendance.instance = ctx.accounts.instance.key();
     attendance.user = ctx.accounts.user.key();
     attendance.claimed = true;
-    attendance.nft_mint = Pubkey::default(); // Would be set to actual mint in production
+    attendance.nft_mint = nft_mint;
+    attendance.leaf_index = None;
+    attendance.leaf_data_hash = [0; 32];
+    attendance.claimed_at = now;
+    attendance.counted_yyyymm = yyyymm;
     attendance.bump = ctx.bumps.attendance;
 
-    // 7. Increment monthly counter
-    let monthly_counter = &mut ctx.accounts.monthly_counter;
-    let yyyymm = instance.yyyymmdd / 100; // Convert YYYYMMDD to YYYYMM
-
-    if monthly_counter.count == 0 {
-        // First claim for this month
-        monthly_counter.user = ctx.accounts.user.key();
-        monthly_counter.yyyymm = yyyymm;
-        monthly_counter.bump = ctx.bumps.monthly_counter;
-    }
-    monthly_counter.count = monthly_counter.count.checked_add(1).unwrap();
+    // 8. Increment monthly counter
+    let new_monthly_count = ctx.accounts.monthly_counter.increment(
+        ctx.accounts.user.key(),
+        yyyymm,
+        ctx.bumps.monthly_counter,
+    )?;
 
     emit!(Claimed {
         instance: ctx.accounts.instance.key(),
         user: ctx.accounts.user.key(),
-        nft_mint: Pubkey::default(), // Would be actual NFT mint in production
+        nft_mint,
         yyyymm,
-        new_monthly_count: monthly_counter.count,
+        new_monthly_count,
+        ordinal,
     });
 
     msg!(
         "Attendance claimed by {} for instance {} (monthly count: {})",
         ctx.accounts.user.key(),
         ctx.accounts.instance.key(),
-        monthly_counter.count
+        new_monthly_count
     );
 
     Ok(())
 }
 
-// Note: For a production implementation, you would use anchor_spl::metadata
-// to create the NFT metadata via CPI here. Due to stack size constraints
-// in the demo, we're keeping this simplified. The full implementation would:
-//
-// 1. Use anchor_spl::metadata::create_metadata_accounts_v3
-// 2. Create the mint and mint tokens to the user
-// 3. Set the metadata with workout details (date, time, trainer)
-//
-// Example structure:
-// use anchor_spl::metadata::{create_metadata_accounts_v3, CreateMetadataAccountsV3};
-// use mpl_token_metadata::types::{DataV2, Creator};
-//
-// The metadata would include:
-// - Name: "Workout • 2025-10-28 07:00 • Coach Alex"
-// - Symbol: "WRKOUT"
-// - URI: pointing to off-chain JSON with workout details
-
 #[derive(Accounts)]
-#[instruction(reveal_secret: Vec<u8>)]
 pub struct ClaimNft<'info> {
     /// The user claiming the NFT
     #[account(mut)]
     pub user: Signer<'info>,
 
-    /// The workout instance being claimed
+    /// The global config account (holds the optional collection mint)
     #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The workout instance being claimed (mutable so hash-chain instances can
+    /// bind their secret on the first claim)
+    #[account(
+        mut,
         seeds = [
             b"instance",
             instance.trainer.as_ref(),
@@ -125,14 +252,14 @@ pub struct ClaimNft<'info> {
         ],
         bump = instance.bump
     )]
-    pub instance: Account<'info, WorkoutInstance>,
+    pub instance: Box<Account<'info, WorkoutInstance>>,
 
     /// The trainer who led this workout (needed for metadata)
     #[account(
         seeds = [b"trainer", instance.trainer.as_ref()],
         bump = trainer.bump
     )]
-    pub trainer: Account<'info, Trainer>,
+    pub trainer: Box<Account<'info, Trainer>>,
 
     /// The attendance record (PDA)
     /// Seeds: ["attendance", instance, user]
@@ -143,19 +270,98 @@ pub struct ClaimNft<'info> {
         seeds = [b"attendance", instance.key().as_ref(), user.key().as_ref()],
         bump
     )]
-    pub attendance: Account<'info, Attendance>,
+    pub attendance: Box<Account<'info, Attendance>>,
 
     /// The monthly counter (PDA)
-    /// Seeds: ["monthly", user, yyyymm]
+    /// Seeds: ["monthly", user, yyyymm], yyyymm as chosen by Config.month_source
     #[account(
         init_if_needed,
         payer = user,
         space = MonthlyCounter::LEN,
-        seeds = [b"monthly", user.key().as_ref(), &(instance.yyyymmdd / 100).to_le_bytes()],
+        seeds = [
+            b"monthly",
+            user.key().as_ref(),
+            &config.counted_yyyymm(instance.yyyymmdd, Clock::get()?.unix_timestamp).to_le_bytes()
+        ],
         bump
     )]
-    pub monthly_counter: Account<'info, MonthlyCounter>,
+    pub monthly_counter: Box<Account<'info, MonthlyCounter>>,
+
+    /// The new NFT mint (fresh keypair, signs the transaction)
+    /// Created by the program as an SPL Token or Token-2022 mint depending on Config.mint_mode
+    #[account(mut)]
+    pub nft_mint: Signer<'info>,
+
+    /// CHECK: The user's associated token account for the NFT, created by the
+    /// associated token program (which validates its address)
+    #[account(mut)]
+    pub nft_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Classic mode only: Metaplex metadata PDA, created and validated by
+    /// the token metadata program
+    #[account(mut)]
+    pub nft_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Classic mode only: Metaplex master edition PDA, created and validated
+    /// by the token metadata program
+    #[account(mut)]
+    pub nft_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Program PDA acting as mint and update authority for attendance NFTs
+    /// Seeds: ["nft_authority"]
+    #[account(seeds = [NFT_AUTHORITY_SEED], bump)]
+    pub nft_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Optional: the collection mint, required when Config.collection_mint is set
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// Optional: the collection's metadata (mutable so sized collections can count items)
+    #[account(mut)]
+    pub collection_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    /// CHECK: Optional: the collection's master edition, validated by the token metadata program
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional: collection authority record delegating to nft_authority,
+    /// validated against its derived address. Not needed if nft_authority is
+    /// the collection's update authority.
+    pub collection_authority_record: Option<UncheckedAccount<'info>>,
+
+    /// SPL Token program (classic mode) or Token-2022 program (soulbound mode)
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Associated Token Account program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Classic mode only: Metaplex Token Metadata program
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    /// CHECK: The user's booking PDA, passed whether or not the user booked. A
+    /// booking, required if instance.booking_required, is marked consumed by
+    /// the claim and its spot becomes the claim's; without one the claim is a
+    /// walk-in
+    #[account(
+        mut,
+        seeds = [b"booking", instance.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub booking: UncheckedAccount<'info>,
+
+    /// Optional: the trainer's secret chain, required for hash-chain claims
+    #[account(
+        mut,
+        seeds = [b"secret_chain", instance.trainer.as_ref()],
+        bump = secret_chain.bump
+    )]
+    pub secret_chain: Option<Box<Account<'info, SecretChain>>>,
+
+    /// CHECK: Optional: instructions sysvar, required for trainer voucher claims
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
 
     /// System program for account creation
     pub system_program: Program<'info, System>,
+
+    /// Rent sysvar (required by the token metadata CPIs)
+    pub rent: Sysvar<'info, Rent>,
 }
@@ -0,0 +1,51 @@
+use crate::errors::WorkoutError;
+use crate::state::Attendance;
+use anchor_lang::prelude::*;
+
+/// Close the caller's Attendance record and refund its rent to them.
+///
+/// The record is what stops a user from claiming the same instance twice, so it
+/// can only be closed once its workout instance has been reclaimed (no claims
+/// are possible without the instance) and the retention period since the claim
+/// (`Attendance::RETENTION_SECS`) has passed. The NFT itself is not affected.
+pub fn close_attendance(ctx: Context<CloseAttendance>) -> Result<()> {
+    require!(
+        ctx.accounts.instance.data_is_empty(),
+        WorkoutError::InstanceNotReclaimed
+    );
+    let now = Clock::get()?.unix_timestamp;
+    let attendance = &ctx.accounts.attendance;
+    require!(
+        now >= attendance.claimed_at + Attendance::RETENTION_SECS,
+        WorkoutError::RetentionPeriodActive
+    );
+
+    msg!(
+        "Attendance of {} for instance {} closed",
+        attendance.user,
+        attendance.instance
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseAttendance<'info> {
+    /// The attendee closing their record (receives the rent refund)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The attendance record to close
+    /// Seeds: ["attendance", instance, user]
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"attendance", attendance.instance.as_ref(), user.key().as_ref()],
+        bump = attendance.bump
+    )]
+    pub attendance: Account<'info, Attendance>,
+
+    /// CHECK: The attendance's workout instance, which must have been reclaimed
+    #[account(address = attendance.instance)]
+    pub instance: UncheckedAccount<'info>,
+}
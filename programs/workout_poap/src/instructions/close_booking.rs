@@ -0,0 +1,47 @@
+use crate::errors::WorkoutError;
+use crate::state::Booking;
+use anchor_lang::prelude::*;
+
+/// Close the caller's Booking for a reclaimed workout instance and refund its
+/// rent to them.
+///
+/// Bookings left over when the instance is reclaimed (consumed by a claim, or
+/// never cancelled) cannot go through cancel_booking anymore, since that needs
+/// the instance. Once the instance is gone the booking is meaningless, so it can
+/// be closed at any time.
+pub fn close_booking(ctx: Context<CloseBooking>) -> Result<()> {
+    require!(
+        ctx.accounts.instance.data_is_empty(),
+        WorkoutError::InstanceNotReclaimed
+    );
+
+    let booking = &ctx.accounts.booking;
+    msg!(
+        "Booking of {} for instance {} closed",
+        booking.user,
+        booking.instance
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseBooking<'info> {
+    /// The user closing their booking (receives the rent refund)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The booking to close
+    /// Seeds: ["booking", instance, user]
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"booking", booking.instance.as_ref(), user.key().as_ref()],
+        bump = booking.bump
+    )]
+    pub booking: Account<'info, Booking>,
+
+    /// CHECK: The booking's workout instance, which must have been reclaimed
+    #[account(address = booking.instance)]
+    pub instance: UncheckedAccount<'info>,
+}
@@ -0,0 +1,43 @@
+use crate::calendar::month_end_ts;
+use crate::errors::WorkoutError;
+use crate::state::MonthlyCounter;
+use anchor_lang::prelude::*;
+
+/// Close the caller's MonthlyCounter for a past month and refund its rent to them.
+///
+/// Allowed once `MonthlyCounter::RETENTION_SECS` have passed since the end of
+/// the month (in UTC), so leaderboards for recent months stay complete.
+pub fn close_monthly_counter(ctx: Context<CloseMonthlyCounter>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let counter = &ctx.accounts.monthly_counter;
+    require!(
+        now >= month_end_ts(counter.yyyymm) + MonthlyCounter::RETENTION_SECS,
+        WorkoutError::RetentionPeriodActive
+    );
+
+    msg!(
+        "Monthly counter of {} for {} closed (count: {})",
+        counter.user,
+        counter.yyyymm,
+        counter.count
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseMonthlyCounter<'info> {
+    /// The user closing their counter (receives the rent refund)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The monthly counter to close
+    /// Seeds: ["monthly", user, yyyymm]
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"monthly", user.key().as_ref(), &monthly_counter.yyyymm.to_le_bytes()],
+        bump = monthly_counter.bump
+    )]
+    pub monthly_counter: Account<'info, MonthlyCounter>,
+}
@@ -10,6 +10,8 @@ use anchor_lang::prelude::*;
 /// - The assigned trainer
 ///
 /// This is typically done after the claim window ends or if the workout is cancelled.
+/// A deactivated trainer can no longer close instances; admins still can, and
+/// may omit the Trainer account once the trainer has been removed.
 pub fn close_workout_instance(ctx: Context<CloseWorkoutInstance>) -> Result<()> {
     let instance = &mut ctx.accounts.instance;
     let instance_key = instance.key();
@@ -40,12 +42,13 @@ pub struct CloseWorkoutInstance<'info> {
     )]
     pub admin: Option<Account<'info, Admin>>,
 
-    /// The trainer for this workout
+    /// The trainer for this workout; required when the trainer calls, optional
+    /// for admins so they can still manage instances of a removed trainer
     #[account(
         seeds = [b"trainer", instance.trainer.as_ref()],
         bump = trainer.bump
     )]
-    pub trainer: Account<'info, Trainer>,
+    pub trainer: Option<Account<'info, Trainer>>,
 
     /// The workout instance to close
     #[account(
@@ -65,17 +68,23 @@ pub struct CloseWorkoutInstance<'info> {
 impl<'info> CloseWorkoutInstance<'info> {
     /// Validate that the caller is authorized (either admin or the trainer)
     pub fn validate(&self) -> Result<()> {
-        // If admin account is present and valid, they're authorized
-        if self.admin.is_some() {
+        // If admin account is present and holds the permission, they're authorized
+        if let Some(admin) = &self.admin {
+            require!(
+                admin.has_permission(Admin::MANAGE_INSTANCES),
+                WorkoutError::MissingPermission
+            );
             return Ok(());
         }
 
         // Otherwise, the signer must be the trainer
+        let trainer = self.trainer.as_ref().ok_or(WorkoutError::NotAssignedTrainer)?;
         require_keys_eq!(
             self.authority.key(),
-            self.trainer.authority,
+            trainer.authority,
             WorkoutError::NotAssignedTrainer
         );
+        require!(trainer.is_active, WorkoutError::TrainerInactive);
 
         Ok(())
     }
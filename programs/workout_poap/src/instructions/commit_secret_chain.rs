@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use crate::state::{SecretChain, Trainer};
+use crate::errors::WorkoutError;
+use crate::events::SecretChainCommitted;
+
+/// Commit to the tip of a hash chain of QR secrets for recurring classes.
+///
+/// Only the trainer can call this, for their own chain. The trainer keeps the
+/// seed private and commits to `tip` = h^length(seed); sessions opened with
+/// ClaimMethod::HashChain then reserve h^(length-1)(seed), h^(length-2)(seed), ...
+/// in turn. Calling this again replaces the chain (e.g. once it is exhausted);
+/// sessions opened from the previous chain can then no longer be claimed.
+///
+/// # Arguments
+/// * `tip` - SHA-256 applied `length` times to the trainer's private seed
+/// * `length` - Number of sessions the chain can serve (at least 1)
+pub fn commit_secret_chain(
+    ctx: Context<CommitSecretChain>,
+    tip: [u8; 32],
+    length: u32,
+) -> Result<()> {
+    require!(length > 0, WorkoutError::InvalidChainLength);
+
+    let secret_chain = &mut ctx.accounts.secret_chain;
+    secret_chain.trainer = ctx.accounts.trainer_authority.key();
+    secret_chain.last_value = tip;
+    secret_chain.revealed_depth = 0;
+    secret_chain.reserved_depth = 0;
+    secret_chain.length = length;
+    secret_chain.generation = secret_chain.generation.wrapping_add(1);
+    secret_chain.bump = ctx.bumps.secret_chain;
+
+    emit!(SecretChainCommitted {
+        trainer: secret_chain.trainer,
+        tip,
+        length,
+    });
+
+    msg!(
+        "Secret chain committed for trainer {} ({} sessions)",
+        secret_chain.trainer,
+        length
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CommitSecretChain<'info> {
+    /// The trainer committing to the chain
+    #[account(mut)]
+    pub trainer_authority: Signer<'info>,
+
+    /// The trainer account proving registration
+    #[account(
+        seeds = [b"trainer", trainer_authority.key().as_ref()],
+        bump = trainer.bump
+    )]
+    pub trainer: Account<'info, Trainer>,
+
+    /// The trainer's secret chain (PDA)
+    /// Seeds: ["secret_chain", trainer_pubkey]
+    /// Using init_if_needed to allow recommitting
+    #[account(
+        init_if_needed,
+        payer = trainer_authority,
+        space = SecretChain::LEN,
+        seeds = [b"secret_chain", trainer_authority.key().as_ref()],
+        bump
+    )]
+    pub secret_chain: Account<'info, SecretChain>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::{
+    CreateTreeConfigCpi, CreateTreeConfigCpiAccounts, CreateTreeConfigInstructionArgs,
+};
+use mpl_bubblegum::programs::{SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
+use crate::state::Config;
+use crate::errors::WorkoutError;
+use crate::events::MerkleTreeCreated;
+use crate::nft::NFT_AUTHORITY_SEED;
+
+/// Create the Bubblegum Merkle tree used for compressed NFT claims.
+///
+/// Only the super-admin (Config.authority) can call this. The client must first
+/// allocate `merkle_tree` with the size required for `max_depth`/`max_buffer_size`,
+/// owned by the SPL account-compression program. The program's ["nft_authority"]
+/// PDA becomes the tree creator, so the tree is private to this program.
+///
+/// Calling this again with a new tree (e.g. when the old one is full) replaces
+/// Config.merkle_tree. Assets already minted into the old tree are unaffected.
+///
+/// # Arguments
+/// * `max_depth` - Depth of the tree (capacity is 2^max_depth NFTs)
+/// * `max_buffer_size` - Number of concurrent changes the tree accepts per slot
+pub fn create_merkle_tree(
+    ctx: Context<CreateMerkleTree>,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Result<()> {
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        ctx.accounts.config.authority,
+        WorkoutError::NotConfigAuthority
+    );
+
+    let bump = [ctx.bumps.nft_authority];
+    let signer_seeds: &[&[&[u8]]] = &[&[NFT_AUTHORITY_SEED, &bump]];
+
+    CreateTreeConfigCpi::new(
+        &ctx.accounts.bubblegum_program.to_account_info(),
+        CreateTreeConfigCpiAccounts {
+            tree_config: &ctx.accounts.tree_config.to_account_info(),
+            merkle_tree: &ctx.accounts.merkle_tree.to_account_info(),
+            payer: &ctx.accounts.authority.to_account_info(),
+            tree_creator: &ctx.accounts.nft_authority.to_account_info(),
+            log_wrapper: &ctx.accounts.log_wrapper.to_account_info(),
+            compression_program: &ctx.accounts.compression_program.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+        },
+        CreateTreeConfigInstructionArgs {
+            max_depth,
+            max_buffer_size,
+            public: Some(false),
+        },
+    )
+    .invoke_signed(signer_seeds)?;
+
+    let config = &mut ctx.accounts.config;
+    config.merkle_tree = Some(ctx.accounts.merkle_tree.key());
+
+    emit!(MerkleTreeCreated {
+        merkle_tree: ctx.accounts.merkle_tree.key(),
+        max_depth,
+        max_buffer_size,
+    });
+
+    msg!("Merkle tree created: {}", ctx.accounts.merkle_tree.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateMerkleTree<'info> {
+    /// The super-admin authority from Config
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The global config account
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Pre-allocated concurrent Merkle tree account, initialized by the
+    /// account-compression program during the CPI
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum tree config PDA, created by Bubblegum
+    /// Seeds: [merkle_tree] (Bubblegum program)
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        seeds::program = bubblegum_program.key(),
+        bump
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA that becomes the tree creator
+    /// Seeds: ["nft_authority"]
+    #[account(seeds = [NFT_AUTHORITY_SEED], bump)]
+    pub nft_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex Bubblegum program
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Noop program (Bubblegum log wrapper)
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
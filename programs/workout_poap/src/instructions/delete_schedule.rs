@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::{Admin, Schedule};
+use crate::errors::WorkoutError;
+
+/// Delete a workout schedule template.
+/// 
+/// Only the admin who created the schedule can delete it (delegates cannot).
+/// This closes the Schedule account and returns its rent to the creator.
+/// Instances already opened from the schedule are not affected.
+pub fn delete_schedule(ctx: Context<DeleteSchedule>) -> Result<()> {
+    msg!(
+        "Schedule {} deleted, rent returned to {}",
+        ctx.accounts.schedule.key(),
+        ctx.accounts.admin_authority.key()
+    );
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DeleteSchedule<'info> {
+    /// The admin who created the schedule
+    #[account(mut)]
+    pub admin_authority: Signer<'info>,
+    
+    /// The admin account proving authorization
+    #[account(
+        seeds = [b"admin", admin_authority.key().as_ref()],
+        bump = admin.bump,
+        constraint = admin.has_permission(Admin::MANAGE_SCHEDULES) @ WorkoutError::MissingPermission
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    /// The schedule account to delete (will be closed)
+    #[account(
+        mut,
+        close = admin_authority,
+        constraint = schedule.created_by == admin_authority.key() @ WorkoutError::NotScheduleOwner
+    )]
+    pub schedule: Account<'info, Schedule>,
+}
@@ -0,0 +1,335 @@
+use crate::errors::WorkoutError;
+use crate::events::AttendanceGranted;
+use crate::nft::{
+    attendance_nft_uri, workout_nft_name, AttendanceCollection, MetaplexAccounts, MintAttendanceNft,
+    NFT_AUTHORITY_SEED,
+};
+use crate::state::{
+    Admin, Attendance, Booking, Config, GrantReason, MintMode, MonthlyCounter, Trainer,
+    WorkoutInstance,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token_interface::TokenInterface;
+
+/// Grant attendance to a user who could not claim it themselves.
+///
+/// Can be called by:
+/// - Any admin, OR
+/// - The assigned trainer
+///
+/// Meant for attendees without a phone or whose device failed. The caller pays
+/// for and signs the transaction; the user only provides a wallet address. The
+/// NFT is minted to that wallet in the configured mint mode and the user's
+/// monthly counter is incremented, exactly as for a regular claim.
+///
+/// The secret, claim window, allowlist, booking and check-in requirements are
+/// not checked, since the caller vouches for the attendance. The instance must
+/// still be open and the user must not have claimed already. A user with a
+/// confirmed booking gets the booked spot, consuming the booking as a claim
+/// would; otherwise the grant counts as a walk-in and needs a spot that is not
+/// held by a confirmed booking. A deactivated trainer can no longer grant
+/// attendance.
+///
+/// # Arguments
+/// * `reason` - Why attendance is granted manually, recorded in the AttendanceGranted event
+pub fn grant_attendance(ctx: Context<GrantAttendance>, reason: GrantReason) -> Result<()> {
+    ctx.accounts.instance.require_open()?;
+    require!(
+        !ctx.accounts.attendance.revoked,
+        WorkoutError::AttendanceRevoked
+    );
+    require!(
+        !ctx.accounts.attendance.claimed,
+        WorkoutError::AlreadyClaimed
+    );
+
+    let booking_info = ctx.accounts.booking.to_account_info();
+    let mut booking = Booking::load(&booking_info)?;
+    let ordinal = ctx.accounts.instance.record_claim(booking.as_mut())?;
+    if let Some(booking) = &booking {
+        booking.store(&booking_info)?;
+    }
+    let instance = &ctx.accounts.instance;
+
+    // Mint the attendance NFT to the user's wallet in the configured mode
+    let mint_mode = ctx.accounts.config.mint_mode;
+    let name = workout_nft_name(
+        instance.yyyymmdd,
+        instance.hour,
+        instance.minute,
+        &ctx.accounts.trainer.display_name,
+        ordinal,
+        mint_mode == MintMode::Classic,
+    );
+    let uri = attendance_nft_uri(
+        &ctx.accounts.config.metadata_uri_template,
+        &ctx.accounts.trainer.default_artwork_uri,
+        &instance.key(),
+        instance,
+        ctx.accounts.user.key,
+        ordinal,
+    );
+    let additional_metadata = vec![
+        ("trainer".to_string(), ctx.accounts.trainer.display_name.clone()),
+        ("instance".to_string(), instance.key().to_string()),
+        ("ordinal".to_string(), ordinal.to_string()),
+    ];
+    let nft_authority = ctx.accounts.nft_authority.to_account_info();
+    let collection_mint = ctx.accounts.collection_mint.as_ref().map(|a| a.to_account_info());
+    let collection_master_edition = ctx
+        .accounts
+        .collection_master_edition
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let collection_authority_record = ctx
+        .accounts
+        .collection_authority_record
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let nft_metadata = ctx.accounts.nft_metadata.as_ref().map(|a| a.to_account_info());
+    let nft_master_edition = ctx
+        .accounts
+        .nft_master_edition
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let token_metadata_program = ctx
+        .accounts
+        .token_metadata_program
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let rent = ctx.accounts.rent.to_account_info();
+    let metaplex = match (mint_mode, &nft_metadata, &nft_master_edition, &token_metadata_program) {
+        (MintMode::Classic, Some(metadata), Some(master_edition), Some(token_metadata_program)) => {
+            Some(MetaplexAccounts {
+                metadata,
+                master_edition,
+                token_metadata_program,
+                rent: &rent,
+                collection: AttendanceCollection::resolve(
+                    ctx.accounts.config.collection_mint,
+                    collection_mint.as_ref(),
+                    ctx.accounts.collection_metadata.as_deref(),
+                    collection_master_edition.as_ref(),
+                    collection_authority_record.as_ref(),
+                    nft_authority.key,
+                )?,
+            })
+        }
+        (MintMode::Classic, ..) => return err!(WorkoutError::MissingMetadataAccounts),
+        (MintMode::Soulbound, ..) => None,
+    };
+    MintAttendanceNft {
+        payer: &ctx.accounts.authority.to_account_info(),
+        owner: &ctx.accounts.user.to_account_info(),
+        mint: &ctx.accounts.nft_mint.to_account_info(),
+        token_account: &ctx.accounts.nft_token_account.to_account_info(),
+        nft_authority: &nft_authority,
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        associated_token_program: &ctx.accounts.associated_token_program.to_account_info(),
+        system_program: &ctx.accounts.system_program.to_account_info(),
+        metaplex,
+    }
+    .mint(mint_mode, name, uri, additional_metadata, ctx.bumps.nft_authority)?;
+    let nft_mint = ctx.accounts.nft_mint.key();
+
+    // Month the claim counts toward (see Config.month_source)
+    let now = Clock::get()?.unix_timestamp;
+    let yyyymm = ctx.accounts.config.counted_yyyymm(instance.yyyymmdd, now);
+
+    // Mark attendance as claimed
+    let attendance = &mut ctx.accounts.attendance;
+    attendance.instance = ctx.accounts.instance.key();
+    attendance.user = ctx.accounts.user.key();
+    attendance.claimed = true;
+    attendance.nft_mint = nft_mint;
+    attendance.leaf_index = None;
+    attendance.leaf_data_hash = [0; 32];
+    attendance.claimed_at = now;
+    attendance.counted_yyyymm = yyyymm;
+    attendance.bump = ctx.bumps.attendance;
+
+    let new_monthly_count = ctx.accounts.monthly_counter.increment(
+        ctx.accounts.user.key(),
+        yyyymm,
+        ctx.bumps.monthly_counter,
+    )?;
+
+    emit!(AttendanceGranted {
+        instance: ctx.accounts.instance.key(),
+        user: ctx.accounts.user.key(),
+        granted_by: ctx.accounts.authority.key(),
+        reason,
+        nft_mint,
+        ordinal,
+        new_monthly_count,
+    });
+
+    msg!(
+        "Attendance granted to {} for instance {} by {} ({:?})",
+        ctx.accounts.user.key(),
+        ctx.accounts.instance.key(),
+        ctx.accounts.authority.key(),
+        reason
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct GrantAttendance<'info> {
+    /// The caller (admin or trainer), paying for all created accounts
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Optional: Admin account if called by an admin
+    #[account(
+        seeds = [b"admin", authority.key().as_ref()],
+        bump = admin.bump
+    )]
+    pub admin: Option<Box<Account<'info, Admin>>>,
+
+    /// CHECK: The wallet receiving the attendance NFT; it does not sign
+    pub user: UncheckedAccount<'info>,
+
+    /// The global config account (holds the mint mode and optional collection mint)
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The workout instance attendance is granted for
+    #[account(
+        mut,
+        seeds = [
+            b"instance",
+            instance.trainer.as_ref(),
+            &instance.yyyymmdd.to_le_bytes(),
+            &[instance.hour],
+            &[instance.minute]
+        ],
+        bump = instance.bump
+    )]
+    pub instance: Box<Account<'info, WorkoutInstance>>,
+
+    /// The trainer for this workout
+    #[account(
+        seeds = [b"trainer", instance.trainer.as_ref()],
+        bump = trainer.bump
+    )]
+    pub trainer: Box<Account<'info, Trainer>>,
+
+    /// The attendance record (PDA)
+    /// Seeds: ["attendance", instance, user]
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = Attendance::LEN,
+        seeds = [b"attendance", instance.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub attendance: Box<Account<'info, Attendance>>,
+
+    /// CHECK: The user's booking PDA, passed whether or not the user booked;
+    /// a booking is marked consumed by the grant
+    #[account(
+        mut,
+        seeds = [b"booking", instance.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub booking: UncheckedAccount<'info>,
+
+    /// The user's monthly counter (PDA)
+    /// Seeds: ["monthly", user, yyyymm], yyyymm as chosen by Config.month_source
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = MonthlyCounter::LEN,
+        seeds = [
+            b"monthly",
+            user.key().as_ref(),
+            &config.counted_yyyymm(instance.yyyymmdd, Clock::get()?.unix_timestamp).to_le_bytes()
+        ],
+        bump
+    )]
+    pub monthly_counter: Box<Account<'info, MonthlyCounter>>,
+
+    /// The new NFT mint (fresh keypair, signs the transaction)
+    #[account(mut)]
+    pub nft_mint: Signer<'info>,
+
+    /// CHECK: The user's associated token account for the NFT, created by the
+    /// associated token program (which validates its address)
+    #[account(mut)]
+    pub nft_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Classic mode only: Metaplex metadata PDA, created and validated by
+    /// the token metadata program
+    #[account(mut)]
+    pub nft_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Classic mode only: Metaplex master edition PDA, created and validated
+    /// by the token metadata program
+    #[account(mut)]
+    pub nft_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Program PDA acting as mint and update authority for attendance NFTs
+    /// Seeds: ["nft_authority"]
+    #[account(seeds = [NFT_AUTHORITY_SEED], bump)]
+    pub nft_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Optional: the collection mint, required when Config.collection_mint is set
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// Optional: the collection's metadata (mutable so sized collections can count items)
+    #[account(mut)]
+    pub collection_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    /// CHECK: Optional: the collection's master edition, validated by the token metadata program
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional: collection authority record delegating to nft_authority,
+    /// validated by the token metadata program
+    pub collection_authority_record: Option<UncheckedAccount<'info>>,
+
+    /// SPL Token program (classic mode) or Token-2022 program (soulbound mode)
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Associated Token Account program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Classic mode only: Metaplex Token Metadata program
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar (required by the token metadata CPIs)
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> GrantAttendance<'info> {
+    /// Validate that the caller is authorized (either admin or the trainer)
+    pub fn validate(&self) -> Result<()> {
+        // If admin account is present and holds the permission, they're authorized
+        if let Some(admin) = &self.admin {
+            require!(
+                admin.has_permission(Admin::GRANT_ATTENDANCE),
+                WorkoutError::MissingPermission
+            );
+            return Ok(());
+        }
+
+        // Otherwise, the signer must be the trainer
+        require_keys_eq!(
+            self.authority.key(),
+            self.trainer.authority,
+            WorkoutError::NotAssignedTrainer
+        );
+        require!(self.trainer.is_active, WorkoutError::TrainerInactive);
+
+        Ok(())
+    }
+}
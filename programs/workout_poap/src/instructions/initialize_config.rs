@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::state::Config;
+use crate::state::{Config, MintMode, MonthSource};
 
 /// Initialize the global configuration for the workout POAP program.
 /// 
@@ -17,6 +17,12 @@ pub fn initialize_config(
     // Set the authority to the signer (super-admin)
     config.authority = ctx.accounts.authority.key();
     config.collection_mint = collection_mint;
+    config.merkle_tree = None;
+    config.mint_mode = MintMode::Classic;
+    config.metadata_uri_template = String::new();
+    config.utc_offset_mins = 0;
+    config.month_source = MonthSource::WorkoutDate;
+    config.pending_authority = None;
     config.bump = ctx.bumps.config;
     
     msg!("Workout POAP config initialized with authority: {}", config.authority);
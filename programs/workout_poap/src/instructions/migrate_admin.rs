@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use crate::state::{Admin, Config, LegacyAdmin};
+use crate::errors::WorkoutError;
+
+/// Upgrade an Admin created before permissions existed.
+///
+/// Only the super-admin (Config.authority) can call this. The account is grown
+/// from Admin::LEGACY_LEN to Admin::LEN (the caller pays the extra rent) and
+/// rewritten in the current layout. Legacy admins could do everything, so they
+/// keep Admin::ALL_PERMISSIONS; update_admin_permissions can narrow them later.
+pub fn migrate_admin(ctx: Context<MigrateAdmin>) -> Result<()> {
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        ctx.accounts.config.authority,
+        WorkoutError::NotConfigAuthority
+    );
+
+    let admin_info = ctx.accounts.admin.to_account_info();
+    require!(
+        admin_info.data_len() == Admin::LEGACY_LEN,
+        WorkoutError::AlreadyMigrated
+    );
+
+    let legacy = {
+        let data = admin_info.try_borrow_data()?;
+        require!(
+            data[..8] == Admin::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacyAdmin::deserialize(&mut &data[8..])?
+    };
+
+    // Fund the larger account before growing it
+    let required_lamports = Rent::get()?.minimum_balance(Admin::LEN);
+    let top_up = required_lamports.saturating_sub(admin_info.lamports());
+    if top_up > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: admin_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    admin_info.realloc(Admin::LEN, true)?;
+
+    let admin = Admin {
+        authority: legacy.authority,
+        permissions: Admin::ALL_PERMISSIONS,
+        bump: legacy.bump,
+    };
+    let mut data = admin_info.try_borrow_mut_data()?;
+    admin.try_serialize(&mut &mut data[..])?;
+
+    msg!("Admin migrated to the current layout: {}", admin.authority);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateAdmin<'info> {
+    /// The super-admin authority from Config, paying for the extra space
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The global config account
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: The public key of the admin being migrated (doesn't need to sign)
+    pub admin_authority: UncheckedAccount<'info>,
+
+    /// CHECK: The admin account in its legacy layout, which Account<Admin>
+    /// cannot deserialize; owner and seeds are checked here and the
+    /// discriminator and contents in the handler
+    #[account(
+        mut,
+        seeds = [b"admin", admin_authority.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub admin: UncheckedAccount<'info>,
+
+    /// System program for the rent transfer when growing the account
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use crate::calendar::local_time_ts;
+use crate::state::{Attendance, Config, LegacyAttendance};
+use crate::errors::WorkoutError;
+
+/// Upgrade the caller's Attendance created by the first program version.
+///
+/// The account is grown from Attendance::LEGACY_LEN to Attendance::LEN (the
+/// user pays the extra rent and gets it back with the rest when closing the
+/// record) and rewritten in the current layout. The workout's date and time are
+/// passed as the instance's seeds, since the instance may already be gone:
+/// the first version counted claims in the month of the workout date, and the
+/// workout time stands in for the claim time, which it did not record.
+///
+/// # Arguments
+/// * `trainer`, `yyyymmdd`, `hour`, `minute` - The seeds of the attendance's
+///   workout instance
+pub fn migrate_attendance(
+    ctx: Context<MigrateAttendance>,
+    _trainer: Pubkey,
+    yyyymmdd: u32,
+    hour: u8,
+    minute: u8,
+) -> Result<()> {
+    let attendance_info = ctx.accounts.attendance.to_account_info();
+    require!(
+        attendance_info.data_len() == Attendance::LEGACY_LEN,
+        WorkoutError::AlreadyMigrated
+    );
+
+    let legacy = {
+        let data = attendance_info.try_borrow_data()?;
+        require!(
+            data[..8] == Attendance::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacyAttendance::deserialize(&mut &data[8..])?
+    };
+
+    // Fund the larger account before growing it
+    let required_lamports = Rent::get()?.minimum_balance(Attendance::LEN);
+    let top_up = required_lamports.saturating_sub(attendance_info.lamports());
+    if top_up > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: attendance_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    attendance_info.realloc(Attendance::LEN, true)?;
+
+    let attendance = Attendance {
+        instance: legacy.instance,
+        user: legacy.user,
+        claimed: legacy.claimed,
+        nft_mint: legacy.nft_mint,
+        leaf_index: None,
+        leaf_data_hash: [0; 32],
+        checked_in_at: None,
+        revoked: false,
+        claimed_at: local_time_ts(yyyymmdd, hour, minute, ctx.accounts.config.utc_offset_mins),
+        counted_yyyymm: yyyymmdd / 100,
+        bump: legacy.bump,
+    };
+    let mut data = attendance_info.try_borrow_mut_data()?;
+    attendance.try_serialize(&mut &mut data[..])?;
+
+    msg!(
+        "Attendance of {} for instance {} migrated to the current layout",
+        attendance.user,
+        attendance.instance
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(trainer: Pubkey, yyyymmdd: u32, hour: u8, minute: u8)]
+pub struct MigrateAttendance<'info> {
+    /// The attendee, paying for the extra space
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The global config account
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: The attendance's workout instance, only used for its address,
+    /// which is checked against the seeds; it may already have been reclaimed
+    #[account(
+        seeds = [
+            b"instance",
+            trainer.as_ref(),
+            &yyyymmdd.to_le_bytes(),
+            &[hour],
+            &[minute]
+        ],
+        bump
+    )]
+    pub instance: UncheckedAccount<'info>,
+
+    /// CHECK: The attendance record in its legacy layout, which
+    /// Account<Attendance> cannot deserialize; owner and seeds are checked here
+    /// and the discriminator and contents in the handler
+    #[account(
+        mut,
+        seeds = [b"attendance", instance.key().as_ref(), user.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub attendance: UncheckedAccount<'info>,
+
+    /// System program for the rent transfer when growing the account
+    pub system_program: Program<'info, System>,
+}
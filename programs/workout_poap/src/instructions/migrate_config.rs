@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use crate::state::{Config, LegacyConfig, MintMode, MonthSource};
+use crate::errors::WorkoutError;
+
+/// Upgrade a Config created before merkle_tree and the later fields existed.
+///
+/// Only the super-admin stored in the legacy Config can call this. The account
+/// is grown from Config::LEGACY_LEN to Config::LEN (the caller pays the extra
+/// rent) and rewritten in the current layout. The authority, collection mint
+/// and bump are kept; every new field gets the same default as in
+/// initialize_config.
+pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+    let config_info = ctx.accounts.config.to_account_info();
+    require!(
+        config_info.data_len() == Config::LEGACY_LEN,
+        WorkoutError::AlreadyMigrated
+    );
+
+    let legacy = {
+        let data = config_info.try_borrow_data()?;
+        require!(
+            data[..8] == Config::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacyConfig::deserialize(&mut &data[8..])?
+    };
+
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        legacy.authority,
+        WorkoutError::NotConfigAuthority
+    );
+
+    // Fund the larger account before growing it
+    let required_lamports = Rent::get()?.minimum_balance(Config::LEN);
+    let top_up = required_lamports.saturating_sub(config_info.lamports());
+    if top_up > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: config_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    config_info.realloc(Config::LEN, true)?;
+
+    let config = Config {
+        authority: legacy.authority,
+        collection_mint: legacy.collection_mint,
+        merkle_tree: None,
+        mint_mode: MintMode::Classic,
+        metadata_uri_template: String::new(),
+        utc_offset_mins: 0,
+        month_source: MonthSource::WorkoutDate,
+        pending_authority: None,
+        bump: legacy.bump,
+    };
+    let mut data = config_info.try_borrow_mut_data()?;
+    config.try_serialize(&mut &mut data[..])?;
+
+    msg!("Config migrated to the current layout ({} bytes)", Config::LEN);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// The super-admin authority from the legacy Config, paying for the extra space
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: The global config account in its legacy layout, which
+    /// Account<Config> cannot deserialize; owner and seeds are checked here and
+    /// the discriminator and contents in the handler
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        owner = crate::ID
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// System program for the rent transfer when growing the account
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use crate::calendar::local_yyyymmdd;
+use crate::state::{Config, DaySlot, LegacySchedule, Schedule};
+use crate::errors::WorkoutError;
+
+/// Upgrade a Schedule created by the first program version.
+///
+/// Only the super-admin (Config.authority) can call this. The account is grown
+/// from Schedule::LEGACY_LEN to Schedule::LEN (the caller pays the extra rent)
+/// and rewritten in the current layout, keeping its creator, who can still
+/// edit it.
+///
+/// Legacy slots name no trainer, so the migrated slots keep their day and time
+/// but have no trainer assigned (the default pubkey) and a 60-minute duration,
+/// and the schedule is deactivated: nothing can be opened from it until its
+/// creator assigns trainers with set_schedule. It applies from today in the
+/// gym's time zone (Config.utc_offset_mins), with a claim window covering each
+/// slot.
+///
+/// # Arguments
+/// * `schedule_id` - The schedule's seed
+pub fn migrate_schedule(ctx: Context<MigrateSchedule>, _schedule_id: String) -> Result<()> {
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        ctx.accounts.config.authority,
+        WorkoutError::NotConfigAuthority
+    );
+
+    let schedule_info = ctx.accounts.schedule.to_account_info();
+    require!(
+        schedule_info.data_len() == Schedule::LEGACY_LEN,
+        WorkoutError::AlreadyMigrated
+    );
+
+    let legacy = {
+        let data = schedule_info.try_borrow_data()?;
+        require!(
+            data[..8] == Schedule::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacySchedule::deserialize(&mut &data[8..])?
+    };
+
+    // Fund the larger account before growing it
+    let required_lamports = Rent::get()?.minimum_balance(Schedule::LEN);
+    let top_up = required_lamports.saturating_sub(schedule_info.lamports());
+    if top_up > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: schedule_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    schedule_info.realloc(Schedule::LEN, true)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let utc_offset_mins = ctx.accounts.config.utc_offset_mins;
+    let schedule = Schedule {
+        created_by: legacy.created_by,
+        slots: legacy
+            .slots
+            .iter()
+            .map(|slot| DaySlot {
+                dow: slot.dow,
+                hour: slot.hour,
+                minute: slot.minute,
+                trainer: Pubkey::default(),
+                duration_mins: 60,
+            })
+            .collect(),
+        is_active: false,
+        window_start_offset_secs: 0,
+        window_end_offset_secs: 0,
+        start_yyyymmdd: local_yyyymmdd(now, utc_offset_mins),
+        end_yyyymmdd: None,
+        utc_offset_mins,
+        blackout_dates: Vec::new(),
+        delegates: Vec::new(),
+        last_modified_by: ctx.accounts.authority.key(),
+        last_modified_at: now,
+        bump: legacy.bump,
+    };
+    let mut data = schedule_info.try_borrow_mut_data()?;
+    schedule.try_serialize(&mut &mut data[..])?;
+
+    msg!(
+        "Schedule migrated to the current layout with {} slots, now inactive",
+        schedule.slots.len()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(schedule_id: String)]
+pub struct MigrateSchedule<'info> {
+    /// The super-admin authority from Config, paying for the extra space
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The global config account
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: The schedule account in its legacy layout, which Account<Schedule>
+    /// cannot deserialize; owner and seeds are checked here and the
+    /// discriminator and contents in the handler
+    #[account(
+        mut,
+        seeds = [b"schedule", schedule_id.as_bytes()],
+        bump,
+        owner = crate::ID
+    )]
+    pub schedule: UncheckedAccount<'info>,
+
+    /// System program for the rent transfer when growing the account
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use crate::state::{Config, LegacyTrainer, Trainer};
+use crate::errors::WorkoutError;
+
+/// Upgrade a Trainer created before activation and profiles existed.
+///
+/// Only the super-admin (Config.authority) can call this. The account is grown
+/// from Trainer::LEGACY_LEN to Trainer::LEN (the caller pays the extra rent) and
+/// rewritten in the current layout. Legacy trainers could always open workouts,
+/// so they stay active, with an empty profile the trainer can fill in with
+/// update_trainer_profile.
+pub fn migrate_trainer(ctx: Context<MigrateTrainer>) -> Result<()> {
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        ctx.accounts.config.authority,
+        WorkoutError::NotConfigAuthority
+    );
+
+    let trainer_info = ctx.accounts.trainer.to_account_info();
+    require!(
+        trainer_info.data_len() == Trainer::LEGACY_LEN,
+        WorkoutError::AlreadyMigrated
+    );
+
+    let legacy = {
+        let data = trainer_info.try_borrow_data()?;
+        require!(
+            data[..8] == Trainer::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacyTrainer::deserialize(&mut &data[8..])?
+    };
+
+    // Fund the larger account before growing it
+    let required_lamports = Rent::get()?.minimum_balance(Trainer::LEN);
+    let top_up = required_lamports.saturating_sub(trainer_info.lamports());
+    if top_up > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: trainer_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    trainer_info.realloc(Trainer::LEN, true)?;
+
+    let trainer = Trainer {
+        authority: legacy.authority,
+        display_name: legacy.display_name,
+        is_active: true,
+        bio_uri: String::new(),
+        avatar_uri: String::new(),
+        specialties: Vec::new(),
+        default_artwork_uri: String::new(),
+        bump: legacy.bump,
+    };
+    let mut data = trainer_info.try_borrow_mut_data()?;
+    trainer.try_serialize(&mut &mut data[..])?;
+
+    msg!("Trainer migrated to the current layout: {}", trainer.authority);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateTrainer<'info> {
+    /// The super-admin authority from Config, paying for the extra space
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The global config account
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: The public key of the trainer being migrated (doesn't need to sign)
+    pub trainer_authority: UncheckedAccount<'info>,
+
+    /// CHECK: The trainer account in its legacy layout, which Account<Trainer>
+    /// cannot deserialize; owner and seeds are checked here and the
+    /// discriminator and contents in the handler
+    #[account(
+        mut,
+        seeds = [b"trainer", trainer_authority.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub trainer: UncheckedAccount<'info>,
+
+    /// System program for the rent transfer when growing the account
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use crate::calendar::local_time_ts;
+use crate::state::{ClaimMethod, Config, LegacyWorkoutInstance, WorkoutInstance};
+use crate::errors::WorkoutError;
+
+/// Upgrade a WorkoutInstance created by the first program version.
+///
+/// Only the super-admin (Config.authority) can call this. The account is grown
+/// from WorkoutInstance::LEGACY_LEN to WorkoutInstance::LEN (the caller pays the
+/// extra rent) and rewritten in the current layout, so it can be closed or
+/// reclaimed like any other instance. The legacy layout does not record who
+/// paid for the account, so the caller becomes its payer and gets the whole
+/// rent back on reclaim. The new fields get the behaviour of the first
+/// version: a plain reveal secret, no allowlist, capacity, booking or check-in,
+/// and the workout time in the gym's current time zone.
+///
+/// # Arguments
+/// * `trainer`, `yyyymmdd`, `hour`, `minute` - The instance's seeds
+pub fn migrate_workout_instance(
+    ctx: Context<MigrateWorkoutInstance>,
+    _trainer: Pubkey,
+    _yyyymmdd: u32,
+    _hour: u8,
+    _minute: u8,
+) -> Result<()> {
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        ctx.accounts.config.authority,
+        WorkoutError::NotConfigAuthority
+    );
+
+    let instance_info = ctx.accounts.instance.to_account_info();
+    require!(
+        instance_info.data_len() == WorkoutInstance::LEGACY_LEN,
+        WorkoutError::AlreadyMigrated
+    );
+
+    let legacy = {
+        let data = instance_info.try_borrow_data()?;
+        require!(
+            data[..8] == WorkoutInstance::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacyWorkoutInstance::deserialize(&mut &data[8..])?
+    };
+
+    // Fund the larger account before growing it
+    let required_lamports = Rent::get()?.minimum_balance(WorkoutInstance::LEN);
+    let top_up = required_lamports.saturating_sub(instance_info.lamports());
+    if top_up > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: instance_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    instance_info.realloc(WorkoutInstance::LEN, true)?;
+
+    let instance = WorkoutInstance {
+        trainer: legacy.trainer,
+        start_ts: legacy.start_ts,
+        window_start_ts: legacy.window_start_ts,
+        window_end_ts: legacy.window_end_ts,
+        secret_hash: legacy.secret_hash,
+        is_closed: legacy.is_closed,
+        yyyymmdd: legacy.yyyymmdd,
+        hour: legacy.hour,
+        minute: legacy.minute,
+        workout_ts: local_time_ts(
+            legacy.yyyymmdd,
+            legacy.hour,
+            legacy.minute,
+            ctx.accounts.config.utc_offset_mins,
+        ),
+        metadata_uri_override: legacy.metadata_uri_override,
+        claim_method: ClaimMethod::RevealSecret,
+        chain_link_bound: false,
+        chain_generation: 0,
+        chain_depth: 0,
+        allowlist_root: None,
+        max_claims: None,
+        claim_count: 0,
+        booking_required: false,
+        booked_count: 0,
+        check_in: None,
+        cancel_reason: None,
+        schedule: None,
+        payer: ctx.accounts.authority.key(),
+        bump: legacy.bump,
+    };
+    let mut data = instance_info.try_borrow_mut_data()?;
+    instance.try_serialize(&mut &mut data[..])?;
+
+    msg!(
+        "Workout instance migrated to the current layout: {}",
+        instance_info.key()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(trainer: Pubkey, yyyymmdd: u32, hour: u8, minute: u8)]
+pub struct MigrateWorkoutInstance<'info> {
+    /// The super-admin authority from Config, paying for the extra space
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The global config account
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: The workout instance in its legacy layout, which
+    /// Account<WorkoutInstance> cannot deserialize; owner and seeds are checked
+    /// here and the discriminator and contents in the handler
+    #[account(
+        mut,
+        seeds = [
+            b"instance",
+            trainer.as_ref(),
+            &yyyymmdd.to_le_bytes(),
+            &[hour],
+            &[minute]
+        ],
+        bump,
+        owner = crate::ID
+    )]
+    pub instance: UncheckedAccount<'info>,
+
+    /// System program for the rent transfer when growing the account
+    pub system_program: Program<'info, System>,
+}
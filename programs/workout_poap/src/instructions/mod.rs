@@ -1,18 +1,84 @@
 pub mod initialize_config;
+pub mod migrate_config;
+pub mod propose_authority_transfer;
+pub mod accept_authority_transfer;
+pub mod cancel_authority_transfer;
 pub mod add_admin;
 pub mod remove_admin;
+pub mod update_admin_permissions;
+pub mod migrate_admin;
 pub mod register_trainer;
+pub mod update_trainer_profile;
+pub mod set_trainer_active;
+pub mod remove_trainer;
+pub mod migrate_trainer;
 pub mod set_schedule;
+pub mod set_schedule_delegates;
+pub mod delete_schedule;
+pub mod migrate_schedule;
 pub mod open_workout_instance;
 pub mod close_workout_instance;
+pub mod cancel_workout_instance;
+pub mod update_workout_instance;
+pub mod open_instance_from_schedule;
 pub mod claim_nft;
+pub mod create_merkle_tree;
+pub mod claim_compressed_nft;
+pub mod set_mint_mode;
+pub mod set_timezone;
+pub mod set_metadata_uri_template;
+pub mod commit_secret_chain;
+pub mod book_spot;
+pub mod cancel_booking;
+pub mod check_in;
+pub mod grant_attendance;
+pub mod revoke_attendance;
+pub mod reclaim_workout_instance;
+pub mod close_attendance;
+pub mod close_booking;
+pub mod close_monthly_counter;
+pub mod migrate_workout_instance;
+pub mod migrate_attendance;
 
 pub use initialize_config::*;
+pub use migrate_config::*;
+pub use propose_authority_transfer::*;
+pub use accept_authority_transfer::*;
+pub use cancel_authority_transfer::*;
 pub use add_admin::*;
 pub use remove_admin::*;
+pub use update_admin_permissions::*;
+pub use migrate_admin::*;
 pub use register_trainer::*;
+pub use update_trainer_profile::*;
+pub use set_trainer_active::*;
+pub use remove_trainer::*;
+pub use migrate_trainer::*;
 pub use set_schedule::*;
+pub use set_schedule_delegates::*;
+pub use delete_schedule::*;
+pub use migrate_schedule::*;
 pub use open_workout_instance::*;
 pub use close_workout_instance::*;
+pub use cancel_workout_instance::*;
+pub use update_workout_instance::*;
+pub use open_instance_from_schedule::*;
 pub use claim_nft::*;
+pub use create_merkle_tree::*;
+pub use claim_compressed_nft::*;
+pub use set_mint_mode::*;
+pub use set_timezone::*;
+pub use set_metadata_uri_template::*;
+pub use commit_secret_chain::*;
+pub use book_spot::*;
+pub use cancel_booking::*;
+pub use check_in::*;
+pub use grant_attendance::*;
+pub use revoke_attendance::*;
+pub use reclaim_workout_instance::*;
+pub use close_attendance::*;
+pub use close_booking::*;
+pub use close_monthly_counter::*;
+pub use migrate_workout_instance::*;
+pub use migrate_attendance::*;
 
@@ -0,0 +1,168 @@
+use crate::calendar::{day_of_week, is_valid_date, local_time_ts};
+use crate::errors::WorkoutError;
+use crate::instructions::open_workout_instance::init_workout_instance;
+use crate::state::{
+    Admin, CheckInPhase, ClaimMethod, Schedule, SecretChain, Trainer, WorkoutInstance,
+};
+use anchor_lang::prelude::*;
+
+/// Open a workout instance for one occurrence of a schedule slot.
+///
+/// Works like open_workout_instance, except that:
+/// - The schedule must be active and apply on `yyyymmdd` (within its date range
+///   and not a blackout date)
+/// - It must have a slot for the given trainer on the day of week of `yyyymmdd`
+///   (computed on-chain) at `hour:minute`
+/// - The claim window is derived from the schedule's default offsets relative
+///   to the slot's start and end, in the schedule's UTC offset
+/// - The schedule's address is recorded on the instance
+///
+/// Can be called by:
+/// - Any admin, OR
+/// - The assigned trainer
+///
+/// # Arguments
+/// * `yyyymmdd` - Date in YYYYMMDD format (e.g., 20251028)
+/// * `hour` - Hour in 24-hour format (0-23)
+/// * `minute` - Minute (0-59)
+/// * Remaining arguments as for open_workout_instance
+pub fn open_instance_from_schedule(
+    ctx: Context<OpenInstanceFromSchedule>,
+    yyyymmdd: u32,
+    hour: u8,
+    minute: u8,
+    secret_hash: [u8; 32],
+    metadata_uri_override: Option<String>,
+    claim_method: ClaimMethod,
+    allowlist_root: Option<[u8; 32]>,
+    max_claims: Option<u32>,
+    booking_required: bool,
+    check_in: Option<CheckInPhase>,
+) -> Result<()> {
+    require!(ctx.accounts.trainer.is_active, WorkoutError::TrainerInactive);
+
+    let schedule = &ctx.accounts.schedule;
+    require!(schedule.is_active, WorkoutError::ScheduleInactive);
+    require!(is_valid_date(yyyymmdd), WorkoutError::InvalidDate);
+    require!(
+        schedule.is_effective_on(yyyymmdd),
+        WorkoutError::DateNotInSchedule
+    );
+    let slot = schedule
+        .find_slot(
+            day_of_week(yyyymmdd),
+            hour,
+            minute,
+            &ctx.accounts.trainer.authority,
+        )
+        .ok_or(WorkoutError::NotAScheduledSlot)?;
+
+    // Slot times are local to the schedule's UTC offset
+    let slot_start_ts = local_time_ts(yyyymmdd, hour, minute, schedule.utc_offset_mins);
+    let slot_end_ts = slot_start_ts + i64::from(slot.duration_mins) * 60;
+    let window_start_ts = slot_start_ts + i64::from(schedule.window_start_offset_secs);
+    let window_end_ts = slot_end_ts + i64::from(schedule.window_end_offset_secs);
+
+    let schedule_key = schedule.key();
+    let trainer_key = ctx.accounts.trainer.authority;
+    let payer = ctx.accounts.authority.key();
+    init_workout_instance(
+        &mut ctx.accounts.instance,
+        ctx.bumps.instance,
+        trainer_key,
+        payer,
+        Some(schedule_key),
+        yyyymmdd,
+        hour,
+        minute,
+        slot_start_ts,
+        window_start_ts,
+        window_end_ts,
+        secret_hash,
+        metadata_uri_override,
+        claim_method,
+        allowlist_root,
+        max_claims,
+        booking_required,
+        check_in,
+        ctx.accounts.secret_chain.as_deref_mut(),
+    )
+}
+
+#[derive(Accounts)]
+#[instruction(yyyymmdd: u32, hour: u8, minute: u8)]
+pub struct OpenInstanceFromSchedule<'info> {
+    /// The caller (admin or trainer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Optional: Admin account if called by an admin
+    #[account(
+        seeds = [b"admin", authority.key().as_ref()],
+        bump = admin.bump
+    )]
+    pub admin: Option<Account<'info, Admin>>,
+
+    /// The schedule the instance is an occurrence of
+    pub schedule: Box<Account<'info, Schedule>>,
+
+    /// The trainer for this workout
+    /// If authority is not an admin, they must be this trainer
+    #[account(
+        seeds = [b"trainer", trainer.authority.as_ref()],
+        bump = trainer.bump
+    )]
+    pub trainer: Account<'info, Trainer>,
+
+    /// The workout instance to create (PDA)
+    /// Seeds: ["instance", trainer_pubkey, yyyymmdd, hour, minute]
+    #[account(
+        init,
+        payer = authority,
+        space = WorkoutInstance::LEN,
+        seeds = [
+            b"instance",
+            trainer.authority.as_ref(),
+            &yyyymmdd.to_le_bytes(),
+            &[hour],
+            &[minute]
+        ],
+        bump
+    )]
+    pub instance: Box<Account<'info, WorkoutInstance>>,
+
+    /// Optional: The trainer's secret chain, required for ClaimMethod::HashChain
+    /// to reserve the session's link
+    #[account(
+        mut,
+        seeds = [b"secret_chain", trainer.authority.as_ref()],
+        bump = secret_chain.bump
+    )]
+    pub secret_chain: Option<Account<'info, SecretChain>>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenInstanceFromSchedule<'info> {
+    /// Validate that the caller is authorized (either admin or the trainer)
+    pub fn validate(&self) -> Result<()> {
+        // If admin account is present and holds the permission, they're authorized
+        if let Some(admin) = &self.admin {
+            require!(
+                admin.has_permission(Admin::MANAGE_INSTANCES),
+                WorkoutError::MissingPermission
+            );
+            return Ok(());
+        }
+
+        // Otherwise, the signer must be the trainer
+        require_keys_eq!(
+            self.authority.key(),
+            self.trainer.authority,
+            WorkoutError::NotAssignedTrainer
+        );
+
+        Ok(())
+    }
+}
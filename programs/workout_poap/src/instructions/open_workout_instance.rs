@@ -1,6 +1,9 @@
+use crate::calendar::{is_valid_date, is_valid_time, local_time_ts};
 use crate::errors::WorkoutError;
 use crate::events::WorkoutOpened;
-use crate::state::{Admin, Trainer, WorkoutInstance};
+use crate::state::{
+    Admin, CheckInPhase, ClaimMethod, Config, SecretChain, Trainer, WorkoutInstance,
+};
 use anchor_lang::prelude::*;
 
 /// Open a new workout instance for a specific date and time.
@@ -14,14 +17,27 @@ use anchor_lang::prelude::*;
 /// - Any admin, OR
 /// - The assigned trainer
 ///
+/// The trainer must be active.
+///
 /// # Arguments
-/// * `yyyymmdd` - Date in YYYYMMDD format (e.g., 20251028)
-/// * `hour` - Hour in 24-hour format (0-23)
+/// * `yyyymmdd` - Date in YYYYMMDD format (e.g., 20251028), in the gym's time zone
+/// * `hour` - Hour in 24-hour format (0-23), in the gym's time zone
 /// * `minute` - Minute (0-59)
 /// * `window_start_ts` - Unix timestamp when claims can begin
-/// * `window_end_ts` - Unix timestamp when claims end
+/// * `window_end_ts` - Unix timestamp when claims end; the window must lie within
+///   a day of the workout time
 /// * `secret_hash` - SHA-256 hash of the reveal_secret (will be shown in QR)
 /// * `metadata_uri_override` - Optional custom metadata URI
+/// * `claim_method` - RevealSecret (shared QR secret), RotatingSecret (QR code
+///   changing every period; `secret_hash` is the hash chain tip), HashChain (next
+///   link of the trainer's SecretChain, reserved now; `secret_hash` is ignored)
+///   or TrainerVoucher (per-user vouchers signed by the trainer; `secret_hash` is
+///   then unused)
+/// * `allowlist_root` - Optional Merkle root of users allowed to claim
+/// * `max_claims` - Optional capacity; further claims fail with CapacityReached
+///   and further bookings go to the waitlist
+/// * `booking_required` - Whether only users with a confirmed booking can claim
+/// * `check_in` - Optional check-in phase; claims then act as the check-out scan
 pub fn open_workout_instance(
     ctx: Context<OpenWorkoutInstance>,
     yyyymmdd: u32,
@@ -31,23 +47,113 @@ pub fn open_workout_instance(
     window_end_ts: i64,
     secret_hash: [u8; 32],
     metadata_uri_override: Option<String>,
+    claim_method: ClaimMethod,
+    allowlist_root: Option<[u8; 32]>,
+    max_claims: Option<u32>,
+    booking_required: bool,
+    check_in: Option<CheckInPhase>,
 ) -> Result<()> {
-    // Validate time window
-    require!(
-        window_end_ts > window_start_ts,
-        WorkoutError::InvalidTimeWindow
-    );
+    require!(ctx.accounts.trainer.is_active, WorkoutError::TrainerInactive);
+
+    let trainer_key = ctx.accounts.trainer.authority;
+    let payer = ctx.accounts.authority.key();
+    let workout_ts = local_time_ts(yyyymmdd, hour, minute, ctx.accounts.config.utc_offset_mins);
+    init_workout_instance(
+        &mut ctx.accounts.instance,
+        ctx.bumps.instance,
+        trainer_key,
+        payer,
+        None,
+        yyyymmdd,
+        hour,
+        minute,
+        workout_ts,
+        window_start_ts,
+        window_end_ts,
+        secret_hash,
+        metadata_uri_override,
+        claim_method,
+        allowlist_root,
+        max_claims,
+        booking_required,
+        check_in,
+        ctx.accounts.secret_chain.as_deref_mut(),
+    )
+}
+
+/// Validate the parameters of a new workout instance, fill in the freshly
+/// created account and emit WorkoutOpened. Shared by open_workout_instance and
+/// open_instance_from_schedule.
+pub(crate) fn init_workout_instance(
+    instance: &mut Account<WorkoutInstance>,
+    bump: u8,
+    trainer_key: Pubkey,
+    payer: Pubkey,
+    schedule: Option<Pubkey>,
+    yyyymmdd: u32,
+    hour: u8,
+    minute: u8,
+    workout_ts: i64,
+    window_start_ts: i64,
+    window_end_ts: i64,
+    secret_hash: [u8; 32],
+    metadata_uri_override: Option<String>,
+    claim_method: ClaimMethod,
+    allowlist_root: Option<[u8; 32]>,
+    max_claims: Option<u32>,
+    booking_required: bool,
+    check_in: Option<CheckInPhase>,
+    secret_chain: Option<&mut SecretChain>,
+) -> Result<()> {
+    // Validate the stated workout date and time
+    require!(is_valid_date(yyyymmdd), WorkoutError::InvalidDate);
+    require!(is_valid_time(hour, minute), WorkoutError::InvalidTime);
+
+    // Validate time window (plausible for the workout time, and rotation period
+    // for rotating codes)
+    WorkoutInstance::validate_claim_window(window_start_ts, window_end_ts, claim_method, workout_ts)?;
+
+    // Validate the check-in window of two-phase instances
+    if let Some(ref check_in) = check_in {
+        require!(
+            check_in.window_end_ts > check_in.window_start_ts,
+            WorkoutError::InvalidTimeWindow
+        );
+        WorkoutInstance::validate_window_plausible(
+            check_in.window_start_ts,
+            check_in.window_end_ts,
+            workout_ts,
+        )?;
+    }
 
     // Validate metadata URI if provided
     if let Some(ref uri) = metadata_uri_override {
-        require!(uri.len() <= 200, WorkoutError::MetadataUriTooLong);
+        require!(
+            uri.len() <= WorkoutInstance::MAX_URI_LEN,
+            WorkoutError::MetadataUriTooLong
+        );
     }
 
+    // Hash-chain sessions reserve their link now, so concurrent sessions of
+    // the same trainer cannot take each other's link
+    let (chain_generation, chain_depth) = match claim_method {
+        ClaimMethod::HashChain => {
+            let secret_chain = secret_chain.ok_or(WorkoutError::MissingSecretChain)?;
+            (secret_chain.generation, secret_chain.reserve_link()?)
+        }
+        _ => (0, 0),
+    };
+
     let clock = Clock::get()?;
-    let instance_key = ctx.accounts.instance.key();
-    let trainer_key = ctx.accounts.trainer.authority;
+    let instance_key = instance.key();
+
+    // A window that has already ended could only serve to re-open a reclaimed
+    // instance, whose attendance records may be gone
+    require!(
+        window_end_ts > clock.unix_timestamp,
+        WorkoutError::InvalidClaimWindow
+    );
 
-    let instance = &mut ctx.accounts.instance;
     instance.trainer = trainer_key;
     instance.start_ts = clock.unix_timestamp;
     instance.window_start_ts = window_start_ts;
@@ -57,8 +163,22 @@ pub fn open_workout_instance(
     instance.yyyymmdd = yyyymmdd;
     instance.hour = hour;
     instance.minute = minute;
+    instance.workout_ts = workout_ts;
     instance.metadata_uri_override = metadata_uri_override.unwrap_or_default();
-    instance.bump = ctx.bumps.instance;
+    instance.claim_method = claim_method;
+    instance.chain_link_bound = false;
+    instance.chain_generation = chain_generation;
+    instance.chain_depth = chain_depth;
+    instance.allowlist_root = allowlist_root;
+    instance.max_claims = max_claims;
+    instance.claim_count = 0;
+    instance.booking_required = booking_required;
+    instance.booked_count = 0;
+    instance.check_in = check_in;
+    instance.cancel_reason = None;
+    instance.schedule = schedule;
+    instance.payer = payer;
+    instance.bump = bump;
 
     emit!(WorkoutOpened {
         instance: instance_key,
@@ -98,6 +218,13 @@ pub struct OpenWorkoutInstance<'info> {
     )]
     pub admin: Option<Account<'info, Admin>>,
 
+    /// The global config account (holds the gym's UTC offset)
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
     /// The trainer for this workout
     /// If authority is not an admin, they must be this trainer
     #[account(
@@ -123,6 +250,15 @@ pub struct OpenWorkoutInstance<'info> {
     )]
     pub instance: Account<'info, WorkoutInstance>,
 
+    /// Optional: The trainer's secret chain, required for ClaimMethod::HashChain
+    /// to reserve the session's link
+    #[account(
+        mut,
+        seeds = [b"secret_chain", trainer.authority.as_ref()],
+        bump = secret_chain.bump
+    )]
+    pub secret_chain: Option<Account<'info, SecretChain>>,
+
     /// System program for account creation
     pub system_program: Program<'info, System>,
 }
@@ -130,8 +266,12 @@ pub struct OpenWorkoutInstance<'info> {
 impl<'info> OpenWorkoutInstance<'info> {
     /// Validate that the caller is authorized (either admin or the trainer)
     pub fn validate(&self) -> Result<()> {
-        // If admin account is present and valid, they're authorized
-        if self.admin.is_some() {
+        // If admin account is present and holds the permission, they're authorized
+        if let Some(admin) = &self.admin {
+            require!(
+                admin.has_permission(Admin::MANAGE_INSTANCES),
+                WorkoutError::MissingPermission
+            );
             return Ok(());
         }
 
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::state::Config;
+use crate::errors::WorkoutError;
+use crate::events::AuthorityTransferProposed;
+
+/// Propose a new super-admin (step 1 of 2).
+/// 
+/// Only the super-admin (Config.authority) can call this. Nothing changes until
+/// the proposed key signs accept_authority_transfer, so a mistyped key cannot
+/// take control. Proposing again replaces any pending proposal.
+/// 
+/// # Arguments
+/// * `new_authority` - The public key proposed as the new super-admin
+pub fn propose_authority_transfer(
+    ctx: Context<ProposeAuthorityTransfer>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        ctx.accounts.config.authority,
+        WorkoutError::NotConfigAuthority
+    );
+    
+    let config = &mut ctx.accounts.config;
+    config.pending_authority = Some(new_authority);
+    
+    emit!(AuthorityTransferProposed {
+        current_authority: config.authority,
+        proposed_authority: new_authority,
+    });
+    
+    msg!("Authority transfer proposed: {} -> {}", config.authority, new_authority);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthorityTransfer<'info> {
+    /// The super-admin authority from Config
+    pub authority: Signer<'info>,
+    
+    /// The global config account
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
@@ -0,0 +1,90 @@
+use crate::errors::WorkoutError;
+use crate::events::WorkoutReclaimed;
+use crate::state::{Waitlist, WorkoutInstance};
+use anchor_lang::prelude::*;
+
+/// Close an ended workout instance and return its rent to whoever paid for it.
+///
+/// Anyone can call this once the claim window (and the check-in window of
+/// two-phase instances) has ended; the rent always goes to the instance's
+/// recorded payer. Once the instance is gone nobody can claim it anymore, which
+/// lets attendees close their Attendance records (see close_attendance) and
+/// leftover Bookings (see close_booking).
+///
+/// If the instance was booked, its waitlist should be passed as well: it is
+/// closed too, refunding the user whose booking created it.
+pub fn reclaim_workout_instance(ctx: Context<ReclaimWorkoutInstance>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let instance = &ctx.accounts.instance;
+    require!(
+        now > instance.activity_end_ts(),
+        WorkoutError::ClaimWindowNotEnded
+    );
+
+    if let Some(waitlist) = &ctx.accounts.waitlist {
+        let waitlist_payer = ctx
+            .accounts
+            .waitlist_payer
+            .as_ref()
+            .ok_or(anchor_lang::error::ErrorCode::AccountNotEnoughKeys)?;
+        require_keys_eq!(
+            waitlist_payer.key(),
+            waitlist.payer,
+            anchor_lang::error::ErrorCode::ConstraintAddress
+        );
+        waitlist.close(waitlist_payer.to_account_info())?;
+    }
+
+    emit!(WorkoutReclaimed {
+        instance: instance.key(),
+        trainer: instance.trainer,
+        payer: instance.payer,
+    });
+
+    msg!(
+        "Workout instance {} reclaimed, rent returned to {}",
+        instance.key(),
+        instance.payer
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ReclaimWorkoutInstance<'info> {
+    /// Anyone can crank the reclamation
+    pub caller: Signer<'info>,
+
+    /// The workout instance to close
+    #[account(
+        mut,
+        close = payer,
+        seeds = [
+            b"instance",
+            instance.trainer.as_ref(),
+            &instance.yyyymmdd.to_le_bytes(),
+            &[instance.hour],
+            &[instance.minute]
+        ],
+        bump = instance.bump
+    )]
+    pub instance: Account<'info, WorkoutInstance>,
+
+    /// CHECK: The account that paid for the instance, receives its rent
+    #[account(mut, address = instance.payer)]
+    pub payer: UncheckedAccount<'info>,
+
+    /// Optional: the instance's waitlist, closed along with it
+    /// Seeds: ["waitlist", instance]
+    #[account(
+        mut,
+        seeds = [b"waitlist", instance.key().as_ref()],
+        bump = waitlist.bump
+    )]
+    pub waitlist: Option<Account<'info, Waitlist>>,
+
+    /// CHECK: Required with `waitlist`: the user who created it, checked against
+    /// Waitlist.payer; receives its rent
+    #[account(mut)]
+    pub waitlist_payer: Option<UncheckedAccount<'info>>,
+}
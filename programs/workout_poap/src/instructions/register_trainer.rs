@@ -16,13 +16,18 @@ pub fn register_trainer(
 ) -> Result<()> {
     // Validate display name length
     require!(
-        display_name.len() <= 64,
+        display_name.len() <= Trainer::MAX_DISPLAY_NAME_LEN,
         WorkoutError::DisplayNameTooLong
     );
     
     let trainer = &mut ctx.accounts.trainer;
     trainer.authority = ctx.accounts.trainer_pubkey.key();
     trainer.display_name = display_name.clone();
+    trainer.is_active = true;
+    trainer.bio_uri = String::new();
+    trainer.avatar_uri = String::new();
+    trainer.specialties = Vec::new();
+    trainer.default_artwork_uri = String::new();
     trainer.bump = ctx.bumps.trainer;
     
     emit!(TrainerRegistered {
@@ -44,7 +49,8 @@ pub struct RegisterTrainer<'info> {
     /// The admin account proving authorization
     #[account(
         seeds = [b"admin", admin_authority.key().as_ref()],
-        bump = admin.bump
+        bump = admin.bump,
+        constraint = admin.has_permission(Admin::REGISTER_TRAINERS) @ WorkoutError::MissingPermission
     )]
     pub admin: Account<'info, Admin>,
     
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use crate::state::{Admin, Trainer};
+use crate::errors::WorkoutError;
+use crate::events::TrainerRemoved;
+
+/// Remove a trainer from the program.
+/// 
+/// Only admins can remove trainers, and only once they have been deactivated
+/// with set_trainer_active. This closes the Trainer account and returns rent to
+/// the admin. Instances opened by the trainer can no longer be claimed, since
+/// claims read the trainer's display name; admins can still close, cancel or
+/// update them without the Trainer account. The trainer's SecretChain, if any,
+/// is closed as well and its rent returned to the trainer.
+pub fn remove_trainer(ctx: Context<RemoveTrainer>) -> Result<()> {
+    require!(
+        !ctx.accounts.trainer.is_active,
+        WorkoutError::TrainerStillActive
+    );
+    
+    // Return the rent of the trainer's secret chain, if one was committed. Only
+    // a SecretChain can live at this PDA, so close it the way `close = ` does
+    let secret_chain_info = ctx.accounts.secret_chain.to_account_info();
+    if secret_chain_info.owner == &crate::ID {
+        let trainer_info = ctx.accounts.trainer_authority.to_account_info();
+        **trainer_info.try_borrow_mut_lamports()? += secret_chain_info.lamports();
+        **secret_chain_info.try_borrow_mut_lamports()? = 0;
+        secret_chain_info.assign(&System::id());
+        secret_chain_info.realloc(0, false)?;
+    }
+    
+    emit!(TrainerRemoved {
+        trainer: ctx.accounts.trainer.authority,
+        removed_by: ctx.accounts.admin_authority.key(),
+    });
+    
+    msg!("Trainer removed: {}", ctx.accounts.trainer.authority);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveTrainer<'info> {
+    /// An admin who can manage trainers
+    #[account(mut)]
+    pub admin_authority: Signer<'info>,
+    
+    /// The admin account proving authorization
+    #[account(
+        seeds = [b"admin", admin_authority.key().as_ref()],
+        bump = admin.bump,
+        constraint = admin.has_permission(Admin::MANAGE_TRAINERS) @ WorkoutError::MissingPermission
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    /// The trainer account to remove (will be closed)
+    #[account(
+        mut,
+        close = admin_authority,
+        seeds = [b"trainer", trainer.authority.as_ref()],
+        bump = trainer.bump
+    )]
+    pub trainer: Account<'info, Trainer>,
+    
+    /// CHECK: The trainer's wallet, which paid for its SecretChain and gets that
+    /// rent back
+    #[account(mut, address = trainer.authority)]
+    pub trainer_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: The trainer's SecretChain PDA, closed if the trainer committed one
+    #[account(
+        mut,
+        seeds = [b"secret_chain", trainer.authority.as_ref()],
+        bump
+    )]
+    pub secret_chain: UncheckedAccount<'info>,
+}
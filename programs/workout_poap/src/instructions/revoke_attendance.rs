@@ -0,0 +1,256 @@
+use crate::errors::WorkoutError;
+use crate::events::AttendanceRevoked;
+use crate::nft::{BurnCompressedAttendanceNft, RevokeAttendanceNft, NFT_AUTHORITY_SEED};
+use crate::state::{Admin, Attendance, MonthlyCounter, RevokeReason};
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token_interface::TokenInterface;
+use mpl_bubblegum::programs::{SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
+use mpl_bubblegum::utils::get_asset_id;
+
+/// Revoke a fraudulent or mistaken attendance.
+///
+/// Only admins can call this. The attendance record is kept and marked as
+/// revoked, so the user can never claim this instance again, and the user's
+/// monthly counter is decremented. This still works after the instance has
+/// been reclaimed, until the user closes the attendance record.
+///
+/// The NFT is disabled through the program's `nft_authority` PDA:
+/// - Soulbound NFTs: the holder's token account is frozen
+/// - Classic NFTs: the token account is frozen through Metaplex if the claim
+///   delegated it to `nft_authority` and the master edition is passed; the
+///   metadata is always renamed with a "REVOKED" prefix and its URI cleared
+/// - Compressed NFTs: the leaf is burned with `nft_authority` as leaf delegate
+///   if `root` is given, with the leaf's proof nodes as remaining accounts.
+///   Without it only the attendance record is marked (e.g. when the owner
+///   transferred the leaf, which resets its delegate)
+///
+/// # Arguments
+/// * `reason` - Why the attendance is revoked, recorded in the AttendanceRevoked event
+/// * `root` - Compressed NFTs only: the current root of the leaf's Merkle tree
+pub fn revoke_attendance<'info>(
+    ctx: Context<'_, '_, '_, 'info, RevokeAttendance<'info>>,
+    reason: RevokeReason,
+    root: Option<[u8; 32]>,
+) -> Result<()> {
+    let attendance = &ctx.accounts.attendance;
+    require!(attendance.claimed, WorkoutError::AttendanceNotClaimed);
+    require!(!attendance.revoked, WorkoutError::AttendanceRevoked);
+
+    if let Some(leaf_index) = attendance.leaf_index {
+        if let Some(root) = root {
+            burn_compressed_nft(&ctx, root, leaf_index)?;
+        }
+    } else {
+        let nft_mint = ctx
+            .accounts
+            .nft_mint
+            .as_ref()
+            .ok_or(WorkoutError::MissingNftAccounts)?;
+        let nft_token_account = ctx
+            .accounts
+            .nft_token_account
+            .as_ref()
+            .map(|a| a.to_account_info());
+        let token_program = ctx
+            .accounts
+            .token_program
+            .as_ref()
+            .ok_or(WorkoutError::MissingNftAccounts)?;
+        let nft_metadata = ctx.accounts.nft_metadata.as_ref().map(|a| a.to_account_info());
+        let nft_master_edition = ctx
+            .accounts
+            .nft_master_edition
+            .as_ref()
+            .map(|a| a.to_account_info());
+        let token_metadata_program = ctx
+            .accounts
+            .token_metadata_program
+            .as_ref()
+            .map(|a| a.to_account_info());
+        let metaplex = match (
+            &nft_metadata,
+            ctx.accounts.nft_metadata.as_deref().map(|a| &**a),
+            &token_metadata_program,
+        ) {
+            (Some(info), Some(account), Some(program)) => Some((info, account, program)),
+            _ => None,
+        };
+        RevokeAttendanceNft {
+            mint: &nft_mint.to_account_info(),
+            token_account: nft_token_account.as_ref(),
+            nft_authority: &ctx.accounts.nft_authority.to_account_info(),
+            token_program: &token_program.to_account_info(),
+            metaplex,
+            master_edition: nft_master_edition.as_ref(),
+        }
+        .revoke(ctx.bumps.nft_authority)?;
+    }
+
+    let attendance = &mut ctx.accounts.attendance;
+    attendance.revoked = true;
+    let new_monthly_count = ctx.accounts.monthly_counter.decrement()?;
+
+    emit!(AttendanceRevoked {
+        instance: attendance.instance,
+        user: attendance.user,
+        revoked_by: ctx.accounts.admin_authority.key(),
+        reason,
+        nft_mint: attendance.nft_mint,
+        new_monthly_count,
+    });
+
+    msg!(
+        "Attendance of {} for instance {} revoked ({:?})",
+        attendance.user,
+        attendance.instance,
+        reason
+    );
+
+    Ok(())
+}
+
+/// Burn the compressed NFT of the attendance through Bubblegum.
+fn burn_compressed_nft<'info>(
+    ctx: &Context<'_, '_, '_, 'info, RevokeAttendance<'info>>,
+    root: [u8; 32],
+    leaf_index: u64,
+) -> Result<()> {
+    let missing = || error!(WorkoutError::MissingNftAccounts);
+    let merkle_tree = ctx.accounts.merkle_tree.as_ref().ok_or_else(missing)?;
+    require_keys_eq!(
+        get_asset_id(merkle_tree.key, leaf_index),
+        ctx.accounts.attendance.nft_mint,
+        WorkoutError::NftMismatch
+    );
+
+    BurnCompressedAttendanceNft {
+        leaf_owner: &ctx.accounts.leaf_owner.as_ref().ok_or_else(missing)?.to_account_info(),
+        tree_config: &ctx.accounts.tree_config.as_ref().ok_or_else(missing)?.to_account_info(),
+        merkle_tree: &merkle_tree.to_account_info(),
+        nft_authority: &ctx.accounts.nft_authority.to_account_info(),
+        bubblegum_program: &ctx
+            .accounts
+            .bubblegum_program
+            .as_ref()
+            .ok_or_else(missing)?
+            .to_account_info(),
+        log_wrapper: &ctx.accounts.log_wrapper.as_ref().ok_or_else(missing)?.to_account_info(),
+        compression_program: &ctx
+            .accounts
+            .compression_program
+            .as_ref()
+            .ok_or_else(missing)?
+            .to_account_info(),
+        system_program: &ctx
+            .accounts
+            .system_program
+            .as_ref()
+            .ok_or_else(missing)?
+            .to_account_info(),
+        proof: ctx.remaining_accounts,
+    }
+    .burn(
+        root,
+        ctx.accounts.attendance.leaf_data_hash,
+        leaf_index,
+        ctx.bumps.nft_authority,
+    )
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttendance<'info> {
+    /// An admin revoking the attendance
+    pub admin_authority: Signer<'info>,
+
+    /// The admin account proving authorization
+    #[account(
+        seeds = [b"admin", admin_authority.key().as_ref()],
+        bump = admin.bump,
+        constraint = admin.has_permission(Admin::REVOKE_ATTENDANCE) @ WorkoutError::MissingPermission
+    )]
+    pub admin: Account<'info, Admin>,
+
+    /// CHECK: The workout instance the attendance belongs to, which may
+    /// already have been reclaimed
+    #[account(address = attendance.instance)]
+    pub instance: UncheckedAccount<'info>,
+
+    /// The attendance record to revoke
+    /// Seeds: ["attendance", instance, user]
+    #[account(
+        mut,
+        seeds = [b"attendance", attendance.instance.as_ref(), attendance.user.as_ref()],
+        bump = attendance.bump
+    )]
+    pub attendance: Box<Account<'info, Attendance>>,
+
+    /// The user's monthly counter the claim was counted in
+    /// Seeds: ["monthly", user, yyyymm]
+    #[account(
+        mut,
+        seeds = [
+            b"monthly",
+            attendance.user.as_ref(),
+            &attendance.counted_yyyymm.to_le_bytes()
+        ],
+        bump = monthly_counter.bump
+    )]
+    pub monthly_counter: Box<Account<'info, MonthlyCounter>>,
+
+    /// CHECK: Regular NFTs only: the attendance NFT mint, checked against the record
+    #[account(mut, address = attendance.nft_mint)]
+    pub nft_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Regular NFTs only: the holder's token account, validated by the
+    /// token program when freezing. Required for soulbound NFTs
+    #[account(mut)]
+    pub nft_token_account: Option<UncheckedAccount<'info>>,
+
+    /// Classic NFTs only: the NFT's Metaplex metadata
+    #[account(mut)]
+    pub nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    /// CHECK: Classic NFTs only: the NFT's master edition, validated by the
+    /// token metadata program when freezing the delegated token account
+    pub nft_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Program PDA acting as freeze and update authority (and delegate)
+    /// for attendance NFTs
+    /// Seeds: ["nft_authority"]
+    #[account(seeds = [NFT_AUTHORITY_SEED], bump)]
+    pub nft_authority: UncheckedAccount<'info>,
+
+    /// Regular NFTs only: SPL Token program (classic) or Token-2022 program (soulbound)
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// Classic NFTs only: Metaplex Token Metadata program
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    /// CHECK: Compressed NFTs only: the owner of the leaf
+    #[account(address = attendance.user)]
+    pub leaf_owner: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Compressed NFTs only: the Merkle tree holding the leaf, checked
+    /// against the asset id in the attendance record
+    #[account(mut)]
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Compressed NFTs only: Bubblegum tree config PDA, validated by Bubblegum
+    pub tree_config: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Compressed NFTs only: Metaplex Bubblegum program
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Compressed NFTs only: SPL Noop program (Bubblegum log wrapper)
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Compressed NFTs only: SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
+
+    /// Compressed NFTs only: System program, required by the Bubblegum burn
+    pub system_program: Option<Program<'info, System>>,
+}
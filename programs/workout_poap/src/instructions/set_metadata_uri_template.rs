@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::state::Config;
+use crate::errors::WorkoutError;
+use crate::events::MetadataUriTemplateChanged;
+
+/// Set the default metadata URI template used for instances without an override.
+///
+/// Only the super-admin (Config.authority) can call this. Placeholders are
+/// expanded when an NFT is claimed (see `nft::attendance_nft_uri`); an empty
+/// template leaves such NFTs without a URI.
+///
+/// # Arguments
+/// * `template` - URI template, e.g. "https://example.com/poap/{instance}/{user}.json"
+pub fn set_metadata_uri_template(
+    ctx: Context<SetMetadataUriTemplate>,
+    template: String,
+) -> Result<()> {
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        ctx.accounts.config.authority,
+        WorkoutError::NotConfigAuthority
+    );
+
+    require!(
+        template.len() <= Config::MAX_URI_TEMPLATE_LEN,
+        WorkoutError::UriTemplateTooLong
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.metadata_uri_template = template.clone();
+
+    emit!(MetadataUriTemplateChanged { template });
+
+    msg!("Metadata URI template set to: {}", config.metadata_uri_template);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMetadataUriTemplate<'info> {
+    /// The super-admin authority from Config
+    pub authority: Signer<'info>,
+
+    /// The global config account
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
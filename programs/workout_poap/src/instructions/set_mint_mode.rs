@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use crate::state::{Config, MintMode};
+use crate::errors::WorkoutError;
+use crate::events::MintModeChanged;
+
+/// Choose how claim_nft mints attendance NFTs.
+///
+/// Only the super-admin (Config.authority) can call this. The new mode applies
+/// to subsequent claims; NFTs already minted keep their token standard.
+///
+/// # Arguments
+/// * `mint_mode` - Classic (SPL Token + Metaplex) or Soulbound (non-transferable Token-2022)
+pub fn set_mint_mode(ctx: Context<SetMintMode>, mint_mode: MintMode) -> Result<()> {
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        ctx.accounts.config.authority,
+        WorkoutError::NotConfigAuthority
+    );
+
+    let config = &mut ctx.accounts.config;
+    let old_mode = config.mint_mode;
+    config.mint_mode = mint_mode;
+
+    emit!(MintModeChanged {
+        old_mode,
+        new_mode: mint_mode,
+    });
+
+    msg!("Mint mode changed from {:?} to {:?}", old_mode, mint_mode);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMintMode<'info> {
+    /// The super-admin authority from Config
+    pub authority: Signer<'info>,
+
+    /// The global config account
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
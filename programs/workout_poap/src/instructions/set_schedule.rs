@@ -1,35 +1,127 @@
 use anchor_lang::prelude::*;
+use crate::calendar::{is_valid_date, is_valid_utc_offset};
 use crate::state::{Admin, Schedule, DaySlot};
 use crate::errors::WorkoutError;
+use crate::events::{ScheduleCreated, ScheduleUpdated};
 
 /// Create or update a workout schedule template.
 /// 
 /// Schedules define recurring workout times (e.g., Mon-Fri at 7:00 AM and 6:00 PM).
 /// Admins use schedules as templates to create actual WorkoutInstance accounts.
 /// 
+/// All fields are validated strictly: slots must use valid days, times and
+/// durations and appear only once, dates must be real calendar dates and the
+/// UTC offset must be between -12:00 and +14:00.
+/// 
+/// The admin who creates a schedule owns it. Afterwards only the creator, or
+/// admins they delegate to with set_schedule_delegates, can edit it.
+/// 
 /// # Arguments
 /// * `schedule_id` - Unique identifier for this schedule (e.g., "morning-bootcamp")
-/// * `slots` - Array of time slots (day of week + time)
+/// * `slots` - Array of time slots (day of week + time, trainer and duration)
 /// * `is_active` - Whether this schedule is currently in use
+/// * `window_start_offset_secs` - Default claim window start, relative to the slot start
+/// * `window_end_offset_secs` - Default claim window end, relative to the slot end
+/// * `start_yyyymmdd` - First date the schedule applies
+/// * `end_yyyymmdd` - Optional last date the schedule applies
+/// * `utc_offset_mins` - Offset of the slots' local time from UTC, in minutes
+/// * `blackout_dates` - Dates (YYYYMMDD) to skip, e.g. holidays
 pub fn set_schedule(
     ctx: Context<SetSchedule>,
     _schedule_id: String, // Used in seeds, but not stored (kept for clarity)
     slots: Vec<DaySlot>,
     is_active: bool,
+    window_start_offset_secs: i32,
+    window_end_offset_secs: i32,
+    start_yyyymmdd: u32,
+    end_yyyymmdd: Option<u32>,
+    utc_offset_mins: i16,
+    blackout_dates: Vec<u32>,
 ) -> Result<()> {
+    let editor = ctx.accounts.admin_authority.key();
+    let is_new = ctx.accounts.schedule.created_by == Pubkey::default();
+    require!(
+        is_new || ctx.accounts.schedule.can_edit(&editor),
+        WorkoutError::NotScheduleEditor
+    );
+
     // Validate slots
     require!(!slots.is_empty(), WorkoutError::EmptySchedule);
     require!(
         slots.len() <= Schedule::MAX_SLOTS,
         WorkoutError::TooManySlots
     );
+    for (i, slot) in slots.iter().enumerate() {
+        require!(slot.is_valid(), WorkoutError::InvalidSlot);
+        require!(
+            !slots[..i].iter().any(|other| other.dow == slot.dow
+                && other.hour == slot.hour
+                && other.minute == slot.minute
+                && other.trainer == slot.trainer),
+            WorkoutError::DuplicateSlot
+        );
+        // The default window must end after it starts for every slot
+        let window_end = i64::from(slot.duration_mins) * 60 + i64::from(window_end_offset_secs);
+        require!(
+            window_end > i64::from(window_start_offset_secs),
+            WorkoutError::InvalidTimeWindow
+        );
+    }
+
+    // Validate the effective date range and blackout dates
+    require!(is_valid_date(start_yyyymmdd), WorkoutError::InvalidDate);
+    if let Some(end) = end_yyyymmdd {
+        require!(is_valid_date(end), WorkoutError::InvalidDate);
+        require!(end >= start_yyyymmdd, WorkoutError::InvalidDateRange);
+    }
+    require!(
+        blackout_dates.len() <= Schedule::MAX_BLACKOUT_DATES,
+        WorkoutError::TooManyBlackoutDates
+    );
+    require!(
+        blackout_dates.iter().all(|date| is_valid_date(*date)),
+        WorkoutError::InvalidDate
+    );
+    require!(
+        is_valid_utc_offset(utc_offset_mins),
+        WorkoutError::InvalidUtcOffset
+    );
     
     let schedule = &mut ctx.accounts.schedule;
-    schedule.created_by = ctx.accounts.admin_authority.key();
+    if is_new {
+        // First write: the creator owns the schedule and gets the rent back on delete
+        schedule.created_by = editor;
+        schedule.delegates = Vec::new();
+    }
     schedule.slots = slots.clone();
     schedule.is_active = is_active;
+    schedule.window_start_offset_secs = window_start_offset_secs;
+    schedule.window_end_offset_secs = window_end_offset_secs;
+    schedule.start_yyyymmdd = start_yyyymmdd;
+    schedule.end_yyyymmdd = end_yyyymmdd;
+    schedule.utc_offset_mins = utc_offset_mins;
+    schedule.blackout_dates = blackout_dates;
+    schedule.last_modified_by = editor;
+    schedule.last_modified_at = Clock::get()?.unix_timestamp;
     schedule.bump = ctx.bumps.schedule;
     
+    if is_new {
+        emit!(ScheduleCreated {
+            schedule: schedule.key(),
+            created_by: editor,
+            slot_count: slots.len() as u8,
+            is_active,
+        });
+    } else {
+        emit!(ScheduleUpdated {
+            schedule: schedule.key(),
+            updated_by: editor,
+            slot_count: slots.len() as u8,
+            is_active,
+            delegates: schedule.delegates.clone(),
+        });
+    }
+    
     msg!(
         "Schedule set with {} slots, active: {}",
         slots.len(),
@@ -49,13 +141,14 @@ pub struct SetSchedule<'info> {
     /// The admin account proving authorization
     #[account(
         seeds = [b"admin", admin_authority.key().as_ref()],
-        bump = admin.bump
+        bump = admin.bump,
+        constraint = admin.has_permission(Admin::MANAGE_SCHEDULES) @ WorkoutError::MissingPermission
     )]
     pub admin: Account<'info, Admin>,
     
     /// The schedule account (PDA)
     /// Seeds: ["schedule", schedule_id]
-    /// Using init_if_needed to allow updates
+    /// Using init_if_needed to allow updates (by the creator or a delegate)
     #[account(
         init_if_needed,
         payer = admin_authority,
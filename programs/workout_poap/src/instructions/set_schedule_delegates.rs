@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::{Admin, Schedule};
+use crate::errors::WorkoutError;
+use crate::events::ScheduleUpdated;
+
+/// Set the admins allowed to edit a schedule besides its creator.
+/// 
+/// Only the schedule's creator can call this. The list replaces any previous
+/// delegates; pass an empty list to remove them all.
+/// 
+/// # Arguments
+/// * `delegates` - Admin wallets allowed to edit the schedule (max 5)
+pub fn set_schedule_delegates(
+    ctx: Context<SetScheduleDelegates>,
+    delegates: Vec<Pubkey>,
+) -> Result<()> {
+    require!(
+        delegates.len() <= Schedule::MAX_DELEGATES,
+        WorkoutError::TooManyDelegates
+    );
+    
+    let schedule = &mut ctx.accounts.schedule;
+    schedule.delegates = delegates;
+    schedule.last_modified_by = ctx.accounts.admin_authority.key();
+    schedule.last_modified_at = Clock::get()?.unix_timestamp;
+    
+    emit!(ScheduleUpdated {
+        schedule: schedule.key(),
+        updated_by: ctx.accounts.admin_authority.key(),
+        slot_count: schedule.slots.len() as u8,
+        is_active: schedule.is_active,
+        delegates: schedule.delegates.clone(),
+    });
+    
+    msg!(
+        "Schedule {} now has {} delegates",
+        schedule.key(),
+        schedule.delegates.len()
+    );
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetScheduleDelegates<'info> {
+    /// The admin who created the schedule
+    pub admin_authority: Signer<'info>,
+    
+    /// The admin account proving authorization
+    #[account(
+        seeds = [b"admin", admin_authority.key().as_ref()],
+        bump = admin.bump,
+        constraint = admin.has_permission(Admin::MANAGE_SCHEDULES) @ WorkoutError::MissingPermission
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    /// The schedule to update
+    #[account(
+        mut,
+        constraint = schedule.created_by == admin_authority.key() @ WorkoutError::NotScheduleOwner
+    )]
+    pub schedule: Account<'info, Schedule>,
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use crate::calendar::is_valid_utc_offset;
+use crate::state::{Config, MonthSource};
+use crate::errors::WorkoutError;
+use crate::events::TimezoneChanged;
+
+/// Set the gym's time zone and how claims are assigned to a month.
+///
+/// Only the super-admin (Config.authority) can call this. The offset applies to
+/// instances opened afterwards; existing instances keep their workout time.
+/// The month source applies to subsequent claims.
+///
+/// # Arguments
+/// * `utc_offset_mins` - Offset of the gym's local time from UTC, in minutes
+///   (-720 to +840)
+/// * `month_source` - WorkoutDate (month of the workout) or ClaimTime (month of
+///   the claim in the gym's local time)
+pub fn set_timezone(
+    ctx: Context<SetTimezone>,
+    utc_offset_mins: i16,
+    month_source: MonthSource,
+) -> Result<()> {
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        ctx.accounts.config.authority,
+        WorkoutError::NotConfigAuthority
+    );
+    require!(
+        is_valid_utc_offset(utc_offset_mins),
+        WorkoutError::InvalidUtcOffset
+    );
+
+    let config = &mut ctx.accounts.config;
+    let old_utc_offset_mins = config.utc_offset_mins;
+    let old_month_source = config.month_source;
+    config.utc_offset_mins = utc_offset_mins;
+    config.month_source = month_source;
+
+    emit!(TimezoneChanged {
+        old_utc_offset_mins,
+        new_utc_offset_mins: utc_offset_mins,
+        old_month_source,
+        new_month_source: month_source,
+    });
+
+    msg!(
+        "Timezone set to UTC{:+} minutes, months counted by {:?}",
+        utc_offset_mins,
+        month_source
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetTimezone<'info> {
+    /// The super-admin authority from Config
+    pub authority: Signer<'info>,
+
+    /// The global config account
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use crate::errors::WorkoutError;
+use crate::state::{Admin, Trainer};
+use crate::events::TrainerActiveChanged;
+
+/// Activate or deactivate a trainer.
+/// 
+/// Only admins can call this. Inactive trainers cannot open workout instances
+/// or close them themselves; instances they already opened stay claimable.
+/// 
+/// # Arguments
+/// * `is_active` - Whether the trainer may open and close workout instances
+pub fn set_trainer_active(ctx: Context<SetTrainerActive>, is_active: bool) -> Result<()> {
+    let trainer = &mut ctx.accounts.trainer;
+    trainer.is_active = is_active;
+    
+    emit!(TrainerActiveChanged {
+        trainer: trainer.authority,
+        changed_by: ctx.accounts.admin_authority.key(),
+        is_active,
+    });
+    
+    msg!("Trainer {} active: {}", trainer.authority, is_active);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetTrainerActive<'info> {
+    /// An admin who can manage trainers
+    pub admin_authority: Signer<'info>,
+    
+    /// The admin account proving authorization
+    #[account(
+        seeds = [b"admin", admin_authority.key().as_ref()],
+        bump = admin.bump,
+        constraint = admin.has_permission(Admin::MANAGE_TRAINERS) @ WorkoutError::MissingPermission
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    /// The trainer account to update
+    #[account(
+        mut,
+        seeds = [b"trainer", trainer.authority.as_ref()],
+        bump = trainer.bump
+    )]
+    pub trainer: Account<'info, Trainer>,
+}
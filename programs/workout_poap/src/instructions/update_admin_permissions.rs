@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::state::{Config, Admin};
+use crate::errors::WorkoutError;
+use crate::events::AdminPermissionsUpdated;
+
+/// Change the permissions of an existing admin.
+/// 
+/// Only the super-admin (Config.authority) can call this. The new bitmask
+/// replaces the old one.
+/// 
+/// # Arguments
+/// * `permissions` - Bitmask of Admin permission bits
+pub fn update_admin_permissions(
+    ctx: Context<UpdateAdminPermissions>,
+    permissions: u16,
+) -> Result<()> {
+    // Verify the signer is the config authority (super-admin)
+    require_keys_eq!(
+        ctx.accounts.authority.key(),
+        ctx.accounts.config.authority,
+        WorkoutError::NotConfigAuthority
+    );
+    require!(
+        permissions & !Admin::ALL_PERMISSIONS == 0,
+        WorkoutError::InvalidPermissions
+    );
+    
+    let admin = &mut ctx.accounts.admin;
+    let old_permissions = admin.permissions;
+    admin.permissions = permissions;
+    
+    emit!(AdminPermissionsUpdated {
+        admin: admin.authority,
+        old_permissions,
+        new_permissions: permissions,
+    });
+    
+    msg!(
+        "Admin {} permissions changed from {:#06b} to {:#06b}",
+        admin.authority,
+        old_permissions,
+        permissions
+    );
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateAdminPermissions<'info> {
+    /// The super-admin authority from Config
+    pub authority: Signer<'info>,
+    
+    /// The global config account
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    /// The admin account to update
+    #[account(
+        mut,
+        seeds = [b"admin", admin.authority.as_ref()],
+        bump = admin.bump
+    )]
+    pub admin: Account<'info, Admin>,
+}
@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::state::{Admin, Trainer};
+use crate::errors::WorkoutError;
+use crate::events::TrainerUpdated;
+
+/// Update a trainer's profile.
+/// 
+/// Can be called by:
+/// - Any admin, OR
+/// - The trainer themselves
+/// 
+/// Each argument left as None keeps its current value; an empty string clears
+/// a URI. The Trainer account is reallocated to fit the new profile: the caller
+/// pays for any extra rent, and the excess goes back to the trainer when the
+/// profile shrinks, even if an admin made the change.
+/// 
+/// The new display name and default artwork are used for NFTs minted
+/// afterwards; NFTs already minted keep their metadata.
+/// 
+/// # Arguments
+/// * `display_name` - New human-readable name for the trainer (max 64 bytes)
+/// * `bio_uri` - URI of the trainer's bio (max 200 bytes)
+/// * `avatar_uri` - URI of the trainer's avatar image (max 200 bytes)
+/// * `specialties` - Specialty tags (max 8, each max 32 bytes)
+/// * `default_artwork_uri` - Metadata URI for claims on instances without an
+///   override (max 200 bytes)
+pub fn update_trainer_profile(
+    ctx: Context<UpdateTrainerProfile>,
+    display_name: Option<String>,
+    bio_uri: Option<String>,
+    avatar_uri: Option<String>,
+    specialties: Option<Vec<String>>,
+    default_artwork_uri: Option<String>,
+) -> Result<()> {
+    // Validate field lengths
+    if let Some(ref display_name) = display_name {
+        require!(
+            display_name.len() <= Trainer::MAX_DISPLAY_NAME_LEN,
+            WorkoutError::DisplayNameTooLong
+        );
+    }
+    for uri in [&bio_uri, &avatar_uri, &default_artwork_uri].into_iter().flatten() {
+        require!(
+            uri.len() <= Trainer::MAX_PROFILE_URI_LEN,
+            WorkoutError::ProfileUriTooLong
+        );
+    }
+    if let Some(ref specialties) = specialties {
+        require!(
+            specialties.len() <= Trainer::MAX_SPECIALTIES,
+            WorkoutError::TooManySpecialties
+        );
+        require!(
+            specialties.iter().all(|tag| tag.len() <= Trainer::MAX_SPECIALTY_LEN),
+            WorkoutError::SpecialtyTooLong
+        );
+    }
+    
+    let space = ctx.accounts.trainer.space_after_update(
+        &bio_uri,
+        &avatar_uri,
+        &specialties,
+        &default_artwork_uri,
+    );
+    ctx.accounts.resize(space)?;
+    
+    let trainer = &mut ctx.accounts.trainer;
+    let old_display_name = trainer.display_name.clone();
+    if let Some(display_name) = display_name {
+        trainer.display_name = display_name;
+    }
+    if let Some(bio_uri) = bio_uri {
+        trainer.bio_uri = bio_uri;
+    }
+    if let Some(avatar_uri) = avatar_uri {
+        trainer.avatar_uri = avatar_uri;
+    }
+    if let Some(specialties) = specialties {
+        trainer.specialties = specialties;
+    }
+    if let Some(default_artwork_uri) = default_artwork_uri {
+        trainer.default_artwork_uri = default_artwork_uri;
+    }
+    
+    emit!(TrainerUpdated {
+        trainer: trainer.authority,
+        updated_by: ctx.accounts.authority.key(),
+        old_display_name,
+        new_display_name: trainer.display_name.clone(),
+        bio_uri: trainer.bio_uri.clone(),
+        avatar_uri: trainer.avatar_uri.clone(),
+        specialties: trainer.specialties.clone(),
+        default_artwork_uri: trainer.default_artwork_uri.clone(),
+    });
+    
+    msg!("Trainer updated: {} ({})", trainer.authority, trainer.display_name);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateTrainerProfile<'info> {
+    /// The caller (admin or the trainer), paying for any extra space
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// Optional: Admin account if called by an admin
+    #[account(
+        seeds = [b"admin", authority.key().as_ref()],
+        bump = admin.bump
+    )]
+    pub admin: Option<Account<'info, Admin>>,
+    
+    /// The trainer account to update, resized to fit the new profile
+    #[account(
+        mut,
+        seeds = [b"trainer", trainer.authority.as_ref()],
+        bump = trainer.bump
+    )]
+    pub trainer: Account<'info, Trainer>,
+    
+    /// CHECK: The trainer's wallet, which receives the excess rent when the
+    /// profile shrinks
+    #[account(mut, address = trainer.authority)]
+    pub trainer_authority: UncheckedAccount<'info>,
+    
+    /// System program for the rent transfer when growing the account
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UpdateTrainerProfile<'info> {
+    /// Validate that the caller is authorized (either admin or the trainer)
+    pub fn validate(&self) -> Result<()> {
+        // If admin account is present and holds the permission, they're authorized
+        if let Some(admin) = &self.admin {
+            require!(
+                admin.has_permission(Admin::MANAGE_TRAINERS),
+                WorkoutError::MissingPermission
+            );
+            return Ok(());
+        }
+        
+        // Otherwise, the signer must be the trainer
+        require_keys_eq!(
+            self.authority.key(),
+            self.trainer.authority,
+            WorkoutError::NotAssignedTrainer
+        );
+        
+        Ok(())
+    }
+
+    /// Reallocate the trainer account to `space` bytes, topping up its rent
+    /// from the caller or refunding the excess to the trainer's wallet.
+    fn resize(&self, space: usize) -> Result<()> {
+        let trainer_info = self.trainer.to_account_info();
+        let required_lamports = Rent::get()?.minimum_balance(space);
+        let lamports = trainer_info.lamports();
+        if required_lamports > lamports {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.authority.to_account_info(),
+                        to: trainer_info.clone(),
+                    },
+                ),
+                required_lamports - lamports,
+            )?;
+        } else if lamports > required_lamports {
+            let excess = lamports - required_lamports;
+            **trainer_info.try_borrow_mut_lamports()? -= excess;
+            **self.trainer_authority.try_borrow_mut_lamports()? += excess;
+        }
+        trainer_info.realloc(space, false)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use crate::errors::WorkoutError;
+use crate::events::WorkoutUpdated;
+use crate::state::{Admin, ClaimMethod, Trainer, WorkoutInstance};
+use anchor_lang::prelude::*;
+
+/// Update the claim window, secret hash or metadata URI of an open workout instance.
+///
+/// Can be called by:
+/// - Any admin, OR
+/// - The assigned trainer
+///
+/// Each argument left as None keeps its current value. The resulting window is
+/// validated with the same rules as open_workout_instance, including that it
+/// must not have ended yet. Hash-chain instances take their secret from the
+/// trainer's SecretChain, so their secret_hash cannot be replaced here, and
+/// rotating-code instances cannot move window_start_ts, which their codes are
+/// counted from.
+///
+/// A deactivated trainer can no longer update instances; admins still can, and
+/// may omit the Trainer account once the trainer has been removed.
+///
+/// # Arguments
+/// * `window_start_ts` - New time when claims can begin
+/// * `window_end_ts` - New time when claims end
+/// * `secret_hash` - New SHA-256 hash of the reveal_secret (or hash chain tip for
+///   rotating codes), e.g. to replace a leaked secret
+/// * `metadata_uri_override` - New metadata URI; an empty string removes the override
+pub fn update_workout_instance(
+    ctx: Context<UpdateWorkoutInstance>,
+    window_start_ts: Option<i64>,
+    window_end_ts: Option<i64>,
+    secret_hash: Option<[u8; 32]>,
+    metadata_uri_override: Option<String>,
+) -> Result<()> {
+    let instance = &mut ctx.accounts.instance;
+    instance.require_open()?;
+
+    // Rotating codes are counted in periods from window_start_ts, so moving it
+    // would shift every code the trainer's app shows
+    if let ClaimMethod::RotatingSecret { .. } = instance.claim_method {
+        if let Some(start) = window_start_ts {
+            require!(
+                start == instance.window_start_ts,
+                WorkoutError::RotationStartFixed
+            );
+        }
+    }
+
+    let new_window_start_ts = window_start_ts.unwrap_or(instance.window_start_ts);
+    let new_window_end_ts = window_end_ts.unwrap_or(instance.window_end_ts);
+    WorkoutInstance::validate_claim_window(
+        new_window_start_ts,
+        new_window_end_ts,
+        instance.claim_method,
+        instance.workout_ts,
+    )?;
+    require!(
+        new_window_end_ts > Clock::get()?.unix_timestamp,
+        WorkoutError::InvalidClaimWindow
+    );
+
+    if secret_hash.is_some() {
+        require!(
+            instance.claim_method != ClaimMethod::HashChain,
+            WorkoutError::WrongClaimMethod
+        );
+    }
+    let new_secret_hash = secret_hash.unwrap_or(instance.secret_hash);
+
+    if let Some(ref uri) = metadata_uri_override {
+        require!(
+            uri.len() <= WorkoutInstance::MAX_URI_LEN,
+            WorkoutError::MetadataUriTooLong
+        );
+    }
+    let new_metadata_uri =
+        metadata_uri_override.unwrap_or_else(|| instance.metadata_uri_override.clone());
+
+    emit!(WorkoutUpdated {
+        instance: instance.key(),
+        updated_by: ctx.accounts.authority.key(),
+        old_window_start_ts: instance.window_start_ts,
+        new_window_start_ts,
+        old_window_end_ts: instance.window_end_ts,
+        new_window_end_ts,
+        old_secret_hash: instance.secret_hash,
+        new_secret_hash,
+        old_metadata_uri: instance.metadata_uri_override.clone(),
+        new_metadata_uri: new_metadata_uri.clone(),
+    });
+
+    instance.window_start_ts = new_window_start_ts;
+    instance.window_end_ts = new_window_end_ts;
+    instance.secret_hash = new_secret_hash;
+    instance.metadata_uri_override = new_metadata_uri;
+
+    msg!("Workout instance updated: {}", instance.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateWorkoutInstance<'info> {
+    /// The caller (admin or trainer)
+    pub authority: Signer<'info>,
+
+    /// Optional: Admin account if called by an admin
+    #[account(
+        seeds = [b"admin", authority.key().as_ref()],
+        bump = admin.bump
+    )]
+    pub admin: Option<Account<'info, Admin>>,
+
+    /// The trainer for this workout; required when the trainer calls, optional
+    /// for admins so they can still manage instances of a removed trainer
+    #[account(
+        seeds = [b"trainer", instance.trainer.as_ref()],
+        bump = trainer.bump
+    )]
+    pub trainer: Option<Account<'info, Trainer>>,
+
+    /// The workout instance to update
+    #[account(
+        mut,
+        seeds = [
+            b"instance",
+            instance.trainer.as_ref(),
+            &instance.yyyymmdd.to_le_bytes(),
+            &[instance.hour],
+            &[instance.minute]
+        ],
+        bump = instance.bump
+    )]
+    pub instance: Account<'info, WorkoutInstance>,
+}
+
+impl<'info> UpdateWorkoutInstance<'info> {
+    /// Validate that the caller is authorized (either admin or the trainer)
+    pub fn validate(&self) -> Result<()> {
+        // If admin account is present and holds the permission, they're authorized
+        if let Some(admin) = &self.admin {
+            require!(
+                admin.has_permission(Admin::MANAGE_INSTANCES),
+                WorkoutError::MissingPermission
+            );
+            return Ok(());
+        }
+
+        // Otherwise, the signer must be the trainer
+        let trainer = self.trainer.as_ref().ok_or(WorkoutError::NotAssignedTrainer)?;
+        require_keys_eq!(
+            self.authority.key(),
+            trainer.authority,
+            WorkoutError::NotAssignedTrainer
+        );
+        require!(trainer.is_active, WorkoutError::TrainerInactive);
+
+        Ok(())
+    }
+}
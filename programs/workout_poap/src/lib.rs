@@ -1,12 +1,21 @@
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
 
+pub mod allowlist;
+pub mod calendar;
 pub mod errors;
 pub mod events;
 pub mod instructions;
+pub mod nft;
 pub mod state;
+pub mod voucher;
 
 use instructions::*;
-use state::DaySlot;
+use state::{
+    CancelReason, CheckInPhase, ClaimMethod, ClaimProof, DaySlot, GrantReason, MintMode,
+    MonthSource, RevokeReason,
+};
 
 declare_id!("7CLhdcpry5nkB1YmnzDnCrSHNiEmVsvSxdhB3LCReJAf");
 
@@ -31,6 +40,7 @@ declare_id!("7CLhdcpry5nkB1YmnzDnCrSHNiEmVsvSxdhB3LCReJAf");
 ///    - Claim window (when attendees can claim)
 ///    - Secret hash (SHA-256 of a random secret)
 /// 4. Trainer displays QR code containing {instance_pubkey, reveal_secret}
+///    Alternatively, the trainer signs a per-user voucher for each attendee
 /// 5. Attendees scan QR and call claim_nft
 /// 6. Program verifies:
 ///    - Current time is within claim window
@@ -66,11 +76,44 @@ pub mod workout_poap {
         instructions::initialize_config(ctx, collection_mint)
     }
 
-    /// Add a new admin.
+    /// Upgrade a Config created by an earlier program version.
+    ///
+    /// Only the super-admin (Config.authority) can call this. Grows the
+    /// account to the current layout, with defaults for the new fields.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        instructions::migrate_config(ctx)
+    }
+
+    /// Propose a new super-admin.
+    ///
+    /// Only the super-admin (Config.authority) can call this. The transfer
+    /// takes effect once the proposed key calls accept_authority_transfer.
+    pub fn propose_authority_transfer(
+        ctx: Context<ProposeAuthorityTransfer>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::propose_authority_transfer(ctx, new_authority)
+    }
+
+    /// Accept a pending super-admin transfer.
+    ///
+    /// Must be signed by the proposed new super-admin.
+    pub fn accept_authority_transfer(ctx: Context<AcceptAuthorityTransfer>) -> Result<()> {
+        instructions::accept_authority_transfer(ctx)
+    }
+
+    /// Cancel a pending super-admin transfer.
     ///
     /// Only the super-admin (Config.authority) can call this.
-    pub fn add_admin(ctx: Context<AddAdmin>) -> Result<()> {
-        instructions::add_admin(ctx)
+    pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
+        instructions::cancel_authority_transfer(ctx)
+    }
+
+    /// Add a new admin with the given permission bitmask.
+    ///
+    /// Only the super-admin (Config.authority) can call this.
+    pub fn add_admin(ctx: Context<AddAdmin>, permissions: u16) -> Result<()> {
+        instructions::add_admin(ctx, permissions)
     }
 
     /// Remove an admin.
@@ -80,31 +123,147 @@ pub mod workout_poap {
         instructions::remove_admin(ctx)
     }
 
+    /// Change an admin's permission bitmask.
+    ///
+    /// Only the super-admin (Config.authority) can call this.
+    pub fn update_admin_permissions(
+        ctx: Context<UpdateAdminPermissions>,
+        permissions: u16,
+    ) -> Result<()> {
+        instructions::update_admin_permissions(ctx, permissions)
+    }
+
+    /// Upgrade an Admin created before permissions existed.
+    ///
+    /// Only the super-admin (Config.authority) can call this. Grows the
+    /// account to the current layout with all permissions, which the
+    /// super-admin can then narrow with update_admin_permissions.
+    pub fn migrate_admin(ctx: Context<MigrateAdmin>) -> Result<()> {
+        instructions::migrate_admin(ctx)
+    }
+
     /// Register a new trainer.
     ///
-    /// Any admin can call this. Trainers can then start/close workout instances.
+    /// Requires the REGISTER_TRAINERS admin permission. Trainers can then
+    /// start/close workout instances.
     pub fn register_trainer(ctx: Context<RegisterTrainer>, display_name: String) -> Result<()> {
         instructions::register_trainer(ctx, display_name)
     }
 
+    /// Update a trainer's display name and profile.
+    ///
+    /// Can be called by an admin with MANAGE_TRAINERS or the trainer themselves.
+    /// The Trainer account is resized to fit the new profile.
+    pub fn update_trainer_profile(
+        ctx: Context<UpdateTrainerProfile>,
+        display_name: Option<String>,
+        bio_uri: Option<String>,
+        avatar_uri: Option<String>,
+        specialties: Option<Vec<String>>,
+        default_artwork_uri: Option<String>,
+    ) -> Result<()> {
+        // Validate caller is authorized
+        ctx.accounts.validate()?;
+
+        instructions::update_trainer_profile(
+            ctx,
+            display_name,
+            bio_uri,
+            avatar_uri,
+            specialties,
+            default_artwork_uri,
+        )
+    }
+
+    /// Activate or deactivate a trainer.
+    ///
+    /// Requires MANAGE_TRAINERS. Inactive trainers cannot open, update, cancel or
+    /// close instances, nor grant attendance.
+    pub fn set_trainer_active(ctx: Context<SetTrainerActive>, is_active: bool) -> Result<()> {
+        instructions::set_trainer_active(ctx, is_active)
+    }
+
+    /// Remove a deactivated trainer.
+    ///
+    /// Requires MANAGE_TRAINERS. Closes the Trainer account and returns rent to the admin.
+    /// Admins can still close, cancel or update the trainer's instances afterwards.
+    /// The trainer's SecretChain, if any, is closed and its rent returned to them.
+    pub fn remove_trainer(ctx: Context<RemoveTrainer>) -> Result<()> {
+        instructions::remove_trainer(ctx)
+    }
+
+    /// Upgrade a Trainer created before activation and profiles existed.
+    ///
+    /// Only the super-admin (Config.authority) can call this. Grows the
+    /// account to the current layout; the trainer stays active with an empty
+    /// profile.
+    pub fn migrate_trainer(ctx: Context<MigrateTrainer>) -> Result<()> {
+        instructions::migrate_trainer(ctx)
+    }
+
     /// Create or update a workout schedule template.
     ///
     /// Schedules define recurring workout times (e.g., Mon-Fri at 7:00 AM).
     /// Admins use these as templates to create WorkoutInstance accounts.
+    /// Only the creator or their delegates can edit an existing schedule.
     pub fn set_schedule(
         ctx: Context<SetSchedule>,
         schedule_id: String,
         slots: Vec<DaySlot>,
         is_active: bool,
+        window_start_offset_secs: i32,
+        window_end_offset_secs: i32,
+        start_yyyymmdd: u32,
+        end_yyyymmdd: Option<u32>,
+        utc_offset_mins: i16,
+        blackout_dates: Vec<u32>,
     ) -> Result<()> {
-        instructions::set_schedule(ctx, schedule_id, slots, is_active)
+        instructions::set_schedule(
+            ctx,
+            schedule_id,
+            slots,
+            is_active,
+            window_start_offset_secs,
+            window_end_offset_secs,
+            start_yyyymmdd,
+            end_yyyymmdd,
+            utc_offset_mins,
+            blackout_dates,
+        )
+    }
+
+    /// Set the admins allowed to edit a schedule besides its creator.
+    ///
+    /// Only the schedule's creator can call this.
+    pub fn set_schedule_delegates(
+        ctx: Context<SetScheduleDelegates>,
+        delegates: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::set_schedule_delegates(ctx, delegates)
+    }
+
+    /// Delete a workout schedule template.
+    ///
+    /// Only the schedule's creator can call this and gets the rent back.
+    pub fn delete_schedule(ctx: Context<DeleteSchedule>) -> Result<()> {
+        instructions::delete_schedule(ctx)
+    }
+
+    /// Upgrade a Schedule created by the first program version.
+    ///
+    /// Only the super-admin (Config.authority) can call this. Grows the
+    /// account to the current layout. Legacy slots have no trainer, so the
+    /// schedule stays inactive until its creator assigns them with set_schedule.
+    pub fn migrate_schedule(ctx: Context<MigrateSchedule>, schedule_id: String) -> Result<()> {
+        instructions::migrate_schedule(ctx, schedule_id)
     }
 
     /// Open a new workout instance.
     ///
-    /// Can be called by any admin or the assigned trainer.
+    /// Can be called by an admin with MANAGE_INSTANCES or the assigned trainer.
     /// Creates a WorkoutInstance with a claim window and secret hash.
-    /// After the workout, the trainer displays a QR code with the reveal_secret.
+    /// After the workout, the trainer displays a QR code with the reveal_secret,
+    /// or issues per-user signed vouchers if claim_method is TrainerVoucher.
     pub fn open_workout_instance(
         ctx: Context<OpenWorkoutInstance>,
         yyyymmdd: u32,
@@ -114,6 +273,11 @@ pub mod workout_poap {
         window_end_ts: i64,
         secret_hash: [u8; 32],
         metadata_uri_override: Option<String>,
+        claim_method: ClaimMethod,
+        allowlist_root: Option<[u8; 32]>,
+        max_claims: Option<u32>,
+        booking_required: bool,
+        check_in: Option<CheckInPhase>,
     ) -> Result<()> {
         // Validate caller is authorized
         ctx.accounts.validate()?;
@@ -127,12 +291,53 @@ pub mod workout_poap {
             window_end_ts,
             secret_hash,
             metadata_uri_override,
+            claim_method,
+            allowlist_root,
+            max_claims,
+            booking_required,
+            check_in,
+        )
+    }
+
+    /// Open a workout instance for an occurrence of a schedule slot.
+    ///
+    /// Can be called by an admin with MANAGE_INSTANCES or the assigned trainer.
+    /// The date and time must match one of the schedule's slots; the claim
+    /// window comes from the schedule's default offsets.
+    pub fn open_instance_from_schedule(
+        ctx: Context<OpenInstanceFromSchedule>,
+        yyyymmdd: u32,
+        hour: u8,
+        minute: u8,
+        secret_hash: [u8; 32],
+        metadata_uri_override: Option<String>,
+        claim_method: ClaimMethod,
+        allowlist_root: Option<[u8; 32]>,
+        max_claims: Option<u32>,
+        booking_required: bool,
+        check_in: Option<CheckInPhase>,
+    ) -> Result<()> {
+        // Validate caller is authorized
+        ctx.accounts.validate()?;
+
+        instructions::open_instance_from_schedule(
+            ctx,
+            yyyymmdd,
+            hour,
+            minute,
+            secret_hash,
+            metadata_uri_override,
+            claim_method,
+            allowlist_root,
+            max_claims,
+            booking_required,
+            check_in,
         )
     }
 
     /// Close a workout instance.
     ///
-    /// Can be called by any admin or the assigned trainer.
+    /// Can be called by an admin with MANAGE_INSTANCES or the assigned trainer.
     /// Prevents any further claims for this workout.
     pub fn close_workout_instance(ctx: Context<CloseWorkoutInstance>) -> Result<()> {
         // Validate caller is authorized
@@ -141,17 +346,250 @@ pub mod workout_poap {
         instructions::close_workout_instance(ctx)
     }
 
+    /// Cancel a workout instance.
+    ///
+    /// Can be called by an admin with MANAGE_INSTANCES or the assigned trainer,
+    /// only before any claim.
+    /// Stores the reason on the instance and emits WorkoutCancelled.
+    pub fn cancel_workout_instance(
+        ctx: Context<CancelWorkoutInstance>,
+        reason: CancelReason,
+    ) -> Result<()> {
+        // Validate caller is authorized
+        ctx.accounts.validate()?;
+
+        instructions::cancel_workout_instance(ctx, reason)
+    }
+
+    /// Update an open workout instance.
+    ///
+    /// Can be called by an admin with MANAGE_INSTANCES or the assigned trainer.
+    /// Changes the claim window, secret hash and/or metadata URI override,
+    /// with the same validation as open_workout_instance.
+    pub fn update_workout_instance(
+        ctx: Context<UpdateWorkoutInstance>,
+        window_start_ts: Option<i64>,
+        window_end_ts: Option<i64>,
+        secret_hash: Option<[u8; 32]>,
+        metadata_uri_override: Option<String>,
+    ) -> Result<()> {
+        // Validate caller is authorized
+        ctx.accounts.validate()?;
+
+        instructions::update_workout_instance(
+            ctx,
+            window_start_ts,
+            window_end_ts,
+            secret_hash,
+            metadata_uri_override,
+        )
+    }
+
     /// Claim an NFT for attending a workout.
     ///
     /// Users call this after scanning the QR code shown by the trainer.
     /// The QR code contains the reveal_secret, which must hash to the stored secret_hash.
+    /// Instances using trainer vouchers instead take a voucher signed for this user.
+    /// Instances with an allowlist also require a Merkle inclusion proof for the user.
     ///
     /// This instruction:
     /// - Verifies the claim window is valid
-    /// - Verifies the secret matches
+    /// - Verifies the secret or voucher matches
+    /// - Consumes the user's booking, if they have one
     /// - Mints an NFT with workout metadata
     /// - Increments the user's monthly counter
-    pub fn claim_nft(ctx: Context<ClaimNft>, reveal_secret: Vec<u8>) -> Result<()> {
-        instructions::claim_nft(ctx, reveal_secret)
+    pub fn claim_nft(
+        ctx: Context<ClaimNft>,
+        proof: ClaimProof,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::claim_nft(ctx, proof, allowlist_proof)
+    }
+
+    /// Create the Bubblegum Merkle tree for compressed NFT claims.
+    ///
+    /// Only the super-admin (Config.authority) can call this.
+    /// Records the tree in Config.merkle_tree.
+    pub fn create_merkle_tree(
+        ctx: Context<CreateMerkleTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        instructions::create_merkle_tree(ctx, max_depth, max_buffer_size)
+    }
+
+    /// Claim a compressed NFT for attending a workout.
+    ///
+    /// Same verification as claim_nft, but mints a Bubblegum compressed NFT
+    /// into Config.merkle_tree, avoiding per-claim mint and metadata rent.
+    pub fn claim_compressed_nft(
+        ctx: Context<ClaimCompressedNft>,
+        proof: ClaimProof,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::claim_compressed_nft(ctx, proof, allowlist_proof)
+    }
+
+    /// Choose how claim_nft mints attendance NFTs.
+    ///
+    /// Only the super-admin (Config.authority) can call this.
+    /// Classic mints transferable Metaplex NFTs; Soulbound mints
+    /// non-transferable Token-2022 tokens.
+    pub fn set_mint_mode(ctx: Context<SetMintMode>, mint_mode: MintMode) -> Result<()> {
+        instructions::set_mint_mode(ctx, mint_mode)
+    }
+
+    /// Set the gym's UTC offset and how claims are assigned to a month.
+    ///
+    /// Only the super-admin (Config.authority) can call this. Workout times
+    /// passed to open_workout_instance are read in this time zone.
+    pub fn set_timezone(
+        ctx: Context<SetTimezone>,
+        utc_offset_mins: i16,
+        month_source: MonthSource,
+    ) -> Result<()> {
+        instructions::set_timezone(ctx, utc_offset_mins, month_source)
+    }
+
+    /// Set the default metadata URI template.
+    ///
+    /// Only the super-admin (Config.authority) can call this.
+    /// Used for instances without a metadata_uri_override; placeholders
+    /// {instance}, {trainer}, {yyyymmdd}, {hhmm}, {user} and {ordinal} are
+    /// expanded at claim time.
+    pub fn set_metadata_uri_template(
+        ctx: Context<SetMetadataUriTemplate>,
+        template: String,
+    ) -> Result<()> {
+        instructions::set_metadata_uri_template(ctx, template)
+    }
+
+    /// Commit to the tip of a hash chain of QR secrets.
+    ///
+    /// Only the trainer can call this, for their own chain. Instances opened
+    /// with ClaimMethod::HashChain each reserve the next preimage of the chain,
+    /// so recurring classes need no new secret_hash per session.
+    pub fn commit_secret_chain(
+        ctx: Context<CommitSecretChain>,
+        tip: [u8; 32],
+        length: u32,
+    ) -> Result<()> {
+        instructions::commit_secret_chain(ctx, tip, length)
+    }
+
+    /// Book a spot in a workout instance.
+    ///
+    /// Confirmed while spots (max_claims) remain that are neither claimed nor
+    /// held by another confirmed booking, otherwise the user joins the
+    /// instance's waitlist.
+    pub fn book_spot(ctx: Context<BookSpot>) -> Result<()> {
+        instructions::book_spot(ctx)
+    }
+
+    /// Cancel a booking.
+    ///
+    /// A freed confirmed spot goes to the first user on the waitlist. Bookings
+    /// of users who already claimed cannot be cancelled.
+    pub fn cancel_booking(ctx: Context<CancelBooking>) -> Result<()> {
+        instructions::cancel_booking(ctx)
+    }
+
+    /// Check in to a two-phase workout.
+    ///
+    /// The user scans the check-in QR near the start; claim_nft then acts as
+    /// the check-out scan near the end and issues the NFT.
+    pub fn check_in(ctx: Context<CheckIn>, check_in_secret: Vec<u8>) -> Result<()> {
+        instructions::check_in(ctx, check_in_secret)
+    }
+
+    /// Grant attendance to a user who cannot claim it themselves.
+    ///
+    /// Can be called by an admin with GRANT_ATTENDANCE or the assigned trainer.
+    /// Mints the NFT to the given user wallet and increments their monthly
+    /// counter without a user signature or secret. The reason is recorded in
+    /// the AttendanceGranted event.
+    pub fn grant_attendance(ctx: Context<GrantAttendance>, reason: GrantReason) -> Result<()> {
+        // Validate caller is authorized
+        ctx.accounts.validate()?;
+
+        instructions::grant_attendance(ctx, reason)
+    }
+
+    /// Revoke a user's attendance.
+    ///
+    /// Requires REVOKE_ATTENDANCE. Marks the attendance as revoked so the user
+    /// cannot claim the instance again, decrements their monthly counter and
+    /// freezes (soulbound, delegated classic) or burns (compressed) the NFT.
+    /// Classic metadata is also marked as revoked. Compressed burns need the
+    /// tree's current `root` and the leaf's proof as remaining accounts.
+    /// Also works once the instance has been reclaimed.
+    pub fn revoke_attendance<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevokeAttendance<'info>>,
+        reason: RevokeReason,
+        root: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::revoke_attendance(ctx, reason, root)
+    }
+
+    /// Close an ended workout instance and refund its rent.
+    ///
+    /// Anyone can call this after the claim window has ended.
+    /// The rent goes back to whoever paid for the instance, and that of its
+    /// waitlist (if passed) to whoever created the waitlist.
+    pub fn reclaim_workout_instance(ctx: Context<ReclaimWorkoutInstance>) -> Result<()> {
+        instructions::reclaim_workout_instance(ctx)
+    }
+
+    /// Close the caller's attendance record and refund its rent.
+    ///
+    /// Requires the instance to be reclaimed and the retention period to have passed,
+    /// so the record can no longer be needed to prevent a double claim.
+    pub fn close_attendance(ctx: Context<CloseAttendance>) -> Result<()> {
+        instructions::close_attendance(ctx)
+    }
+
+    /// Close the caller's booking for a reclaimed instance and refund its rent.
+    ///
+    /// Covers bookings cancel_booking can no longer reach, such as ones consumed
+    /// by a claim.
+    pub fn close_booking(ctx: Context<CloseBooking>) -> Result<()> {
+        instructions::close_booking(ctx)
+    }
+
+    /// Close the caller's monthly counter for a past month and refund its rent.
+    ///
+    /// Requires the retention period after the end of the month to have passed.
+    pub fn close_monthly_counter(ctx: Context<CloseMonthlyCounter>) -> Result<()> {
+        instructions::close_monthly_counter(ctx)
+    }
+
+    /// Upgrade a WorkoutInstance created by the first program version.
+    ///
+    /// Only the super-admin (Config.authority) can call this. Grows the
+    /// account to the current layout, with the caller as its payer, so it
+    /// can be closed and reclaimed.
+    pub fn migrate_workout_instance(
+        ctx: Context<MigrateWorkoutInstance>,
+        trainer: Pubkey,
+        yyyymmdd: u32,
+        hour: u8,
+        minute: u8,
+    ) -> Result<()> {
+        instructions::migrate_workout_instance(ctx, trainer, yyyymmdd, hour, minute)
+    }
+
+    /// Upgrade the caller's Attendance created by the first program version.
+    ///
+    /// Grows the account to the current layout, so it can be revoked and
+    /// closed. Takes the seeds of the attendance's workout instance, whose
+    /// date fills in the month and claim time the old layout lacked.
+    pub fn migrate_attendance(
+        ctx: Context<MigrateAttendance>,
+        trainer: Pubkey,
+        yyyymmdd: u32,
+        hour: u8,
+        minute: u8,
+    ) -> Result<()> {
+        instructions::migrate_attendance(ctx, trainer, yyyymmdd, hour, minute)
     }
 }
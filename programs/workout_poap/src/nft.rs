@@ -0,0 +1,947 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program::{create_account, CreateAccount};
+use anchor_spl::associated_token::{create as create_associated_token_account, Create};
+use anchor_spl::metadata::mpl_token_metadata::accounts::CollectionAuthorityRecord;
+use anchor_spl::metadata::mpl_token_metadata::types::{Collection, DataV2};
+use anchor_spl::metadata::mpl_token_metadata::{MAX_NAME_LENGTH, MAX_URI_LENGTH};
+use anchor_spl::metadata::{
+    create_master_edition_v3, create_metadata_accounts_v3, freeze_delegated_account,
+    update_metadata_accounts_v2, verify_collection, verify_sized_collection_item,
+    CreateMasterEditionV3, CreateMetadataAccountsV3, FreezeDelegatedAccount, MetadataAccount,
+    UpdateMetadataAccountsV2, VerifyCollection, VerifySizedCollectionItem,
+};
+use anchor_spl::token::spl_token::state::{Account as SplTokenAccount, Mint as SplMint};
+use anchor_spl::token::{approve, Approve};
+use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+use anchor_spl::token_2022::spl_token_2022::instruction::AuthorityType;
+use anchor_spl::token_2022::spl_token_2022::state::Mint as Token2022Mint;
+use anchor_spl::token_2022_extensions::spl_pod::optional_keys::OptionalNonZeroPubkey;
+use anchor_spl::token_2022_extensions::spl_token_metadata_interface::state::{
+    Field, TokenMetadata,
+};
+use anchor_spl::token_interface::{
+    freeze_account, initialize_mint2, metadata_pointer_initialize, mint_to,
+    non_transferable_mint_initialize, set_authority, token_metadata_initialize,
+    token_metadata_update_field, FreezeAccount, InitializeMint2, MetadataPointerInitialize,
+    MintTo, NonTransferableMintInitialize, SetAuthority, TokenMetadataInitialize,
+    TokenMetadataUpdateField,
+};
+use mpl_bubblegum::accounts::TreeConfig;
+use mpl_bubblegum::hash::{hash_creators, hash_metadata};
+use mpl_bubblegum::instructions::{
+    BurnCpi, BurnCpiAccounts, BurnInstructionArgs, MintToCollectionV1Cpi,
+    MintToCollectionV1CpiAccounts, MintToCollectionV1InstructionArgs, MintV1Cpi,
+    MintV1CpiAccounts, MintV1InstructionArgs,
+};
+use mpl_bubblegum::types::{MetadataArgs, TokenProgramVersion, TokenStandard};
+use mpl_bubblegum::utils::get_asset_id;
+
+use crate::errors::WorkoutError;
+use crate::state::{MintMode, WorkoutInstance};
+
+/// Seed of the program PDA that acts as mint, freeze and update authority
+/// for every attendance NFT.
+pub const NFT_AUTHORITY_SEED: &[u8] = b"nft_authority";
+
+/// Symbol written into every attendance NFT's metadata.
+pub const NFT_SYMBOL: &str = "WRKOUT";
+
+/// Prefix put in front of the name of a revoked classic attendance NFT.
+pub const REVOKED_NAME_PREFIX: &str = "REVOKED ";
+
+/// Build the NFT name for an attendee, e.g. "2025-10-28 07:00 • Coach Alex #7"
+/// for the 7th claim.
+///
+/// There is no "Workout •" prefix (the symbol carries that instead). Soulbound
+/// (Token-2022) names have no length limit and always end with the ordinal.
+/// For Metaplex and Bubblegum names (`metaplex` set), which are cut to 32 bytes
+/// with `metaplex_name` when minting, the ordinal is only appended if the whole
+/// name still fits; it remains available through the `{ordinal}` URI
+/// placeholder either way.
+pub fn workout_nft_name(
+    yyyymmdd: u32,
+    hour: u8,
+    minute: u8,
+    trainer_name: &str,
+    ordinal: u32,
+    metaplex: bool,
+) -> String {
+    let name = format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} • {}",
+        yyyymmdd / 10000,
+        (yyyymmdd / 100) % 100,
+        yyyymmdd % 100,
+        hour,
+        minute,
+        trainer_name
+    );
+    let numbered = format!("{} #{}", name, ordinal);
+    if metaplex && numbered.len() > MAX_NAME_LENGTH {
+        name
+    } else {
+        numbered
+    }
+}
+
+/// Cut a name to the 32-byte limit of Metaplex (and Bubblegum) metadata, on a
+/// UTF-8 character boundary.
+pub fn metaplex_name(name: &str) -> String {
+    let mut end = 0;
+    for (i, c) in name.char_indices() {
+        if i + c.len_utf8() > MAX_NAME_LENGTH {
+            break;
+        }
+        end = i + c.len_utf8();
+    }
+    name[..end].to_string()
+}
+
+/// Metadata URI for an attendance NFT.
+///
+/// Uses the instance's `metadata_uri_override` if set, then the trainer's
+/// `default_artwork_uri`, otherwise expands `Config.metadata_uri_template`.
+/// Supported placeholders:
+/// - `{instance}`: the WorkoutInstance address
+/// - `{trainer}`: the trainer's wallet address
+/// - `{yyyymmdd}`: the workout date, e.g. 20251028
+/// - `{hhmm}`: the workout start time, e.g. 0700
+/// - `{user}`: the claiming user's wallet address
+/// - `{ordinal}`: the attendee's ordinal, e.g. 7 for the 7th claim
+///
+/// The result may exceed the metadata URI limit once addresses are substituted;
+/// the minting helpers reject it with `MetadataUriTooLong` in that case.
+pub fn attendance_nft_uri(
+    template: &str,
+    trainer_artwork_uri: &str,
+    instance_key: &Pubkey,
+    instance: &WorkoutInstance,
+    user: &Pubkey,
+    ordinal: u32,
+) -> String {
+    if !instance.metadata_uri_override.is_empty() {
+        return instance.metadata_uri_override.clone();
+    }
+    if !trainer_artwork_uri.is_empty() {
+        return trainer_artwork_uri.to_string();
+    }
+    template
+        .replace("{instance}", &instance_key.to_string())
+        .replace("{trainer}", &instance.trainer.to_string())
+        .replace("{yyyymmdd}", &format!("{:08}", instance.yyyymmdd))
+        .replace("{hhmm}", &format!("{:02}{:02}", instance.hour, instance.minute))
+        .replace("{user}", &user.to_string())
+        .replace("{ordinal}", &ordinal.to_string())
+}
+
+/// Collection accounts used to verify an attendance NFT into `Config.collection_mint`.
+pub struct AttendanceCollection<'a, 'info> {
+    pub mint: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub master_edition: &'a AccountInfo<'info>,
+    /// Collection authority record delegating to the program PDA, if the PDA
+    /// is not itself the collection's update authority
+    pub authority_record: Option<&'a AccountInfo<'info>>,
+    /// Whether the collection is a sized collection (tracks its item count)
+    pub is_sized: bool,
+}
+
+impl<'a, 'info> AttendanceCollection<'a, 'info> {
+    /// Resolve the collection for a claim from `Config.collection_mint` and the
+    /// optional collection accounts passed to the instruction.
+    ///
+    /// Returns `None` if no collection is configured. Otherwise all collection
+    /// accounts must be present and match the configured mint.
+    pub fn resolve(
+        config_collection_mint: Option<Pubkey>,
+        mint: Option<&'a AccountInfo<'info>>,
+        metadata: Option<&'a Account<'info, MetadataAccount>>,
+        master_edition: Option<&'a AccountInfo<'info>>,
+        authority_record: Option<&'a AccountInfo<'info>>,
+        nft_authority: &Pubkey,
+    ) -> Result<Option<Self>> {
+        let Some(expected_mint) = config_collection_mint else {
+            return Ok(None);
+        };
+        let (Some(mint), Some(metadata), Some(master_edition)) = (mint, metadata, master_edition)
+        else {
+            return err!(WorkoutError::CollectionMismatch);
+        };
+        require_keys_eq!(mint.key(), expected_mint, WorkoutError::CollectionMismatch);
+
+        Self::new(mint, metadata, master_edition, authority_record, nft_authority).map(Some)
+    }
+
+    /// Check that the program PDA may verify items into this collection.
+    ///
+    /// The PDA is authorized either by being the collection's update authority or
+    /// through a collection authority record created with Metaplex
+    /// `ApproveCollectionAuthority`.
+    pub fn new(
+        mint: &'a AccountInfo<'info>,
+        metadata: &'a Account<'info, MetadataAccount>,
+        master_edition: &'a AccountInfo<'info>,
+        authority_record: Option<&'a AccountInfo<'info>>,
+        nft_authority: &Pubkey,
+    ) -> Result<Self> {
+        require_keys_eq!(metadata.mint, mint.key(), WorkoutError::CollectionMismatch);
+
+        let authority_record = if metadata.update_authority == *nft_authority {
+            None
+        } else {
+            let record = authority_record.ok_or(WorkoutError::CollectionAuthorityNotDelegated)?;
+            let (expected, _) = CollectionAuthorityRecord::find_pda(&mint.key(), nft_authority);
+            require!(
+                record.key() == expected
+                    && record.owner == &anchor_spl::metadata::ID
+                    && !record.data_is_empty(),
+                WorkoutError::CollectionAuthorityNotDelegated
+            );
+            Some(record)
+        };
+
+        Ok(Self {
+            mint,
+            metadata: metadata.as_ref(),
+            master_edition,
+            authority_record,
+            is_sized: metadata.collection_details.is_some(),
+        })
+    }
+}
+
+/// Metaplex accounts for a classic attendance NFT (metadata + master edition).
+pub struct MetaplexAccounts<'a, 'info> {
+    pub metadata: &'a AccountInfo<'info>,
+    pub master_edition: &'a AccountInfo<'info>,
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub rent: &'a AccountInfo<'info>,
+    pub collection: Option<AttendanceCollection<'a, 'info>>,
+}
+
+/// Accounts needed to mint a single attendance NFT.
+///
+/// `mint` must be a fresh keypair signing the transaction; it is created here
+/// together with the owner's associated token account, in either mint mode.
+/// If the owner signs too (a claim rather than a grant), classic NFTs approve
+/// `nft_authority` as delegate of the token so a revocation can freeze it.
+pub struct MintAttendanceNft<'a, 'info> {
+    pub payer: &'a AccountInfo<'info>,
+    pub owner: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub token_account: &'a AccountInfo<'info>,
+    pub nft_authority: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    /// Required for MintMode::Classic, ignored for MintMode::Soulbound
+    pub metaplex: Option<MetaplexAccounts<'a, 'info>>,
+}
+
+impl<'a, 'info> MintAttendanceNft<'a, 'info> {
+    /// Mint the attendance NFT in the given mode.
+    ///
+    /// `additional_metadata` is written as extra on-chain fields in soulbound mode;
+    /// classic Metaplex metadata has no room for it.
+    pub fn mint(
+        &self,
+        mode: MintMode,
+        name: String,
+        uri: String,
+        additional_metadata: Vec<(String, String)>,
+        nft_authority_bump: u8,
+    ) -> Result<()> {
+        require!(uri.len() <= MAX_URI_LENGTH, WorkoutError::MetadataUriTooLong);
+
+        let bump = [nft_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[NFT_AUTHORITY_SEED, &bump]];
+
+        match mode {
+            MintMode::Classic => self.mint_classic(name, uri, signer_seeds),
+            MintMode::Soulbound => {
+                self.mint_soulbound(name, uri, additional_metadata, signer_seeds)
+            }
+        }
+    }
+
+    /// Mint one SPL token, attach Metaplex metadata and lock the supply with a
+    /// master edition. If a collection is given, the NFT is then verified as a
+    /// member of it. A signing owner delegates the token to `nft_authority`.
+    ///
+    /// Each CPI lives in its own non-inlined function so their stack frames
+    /// don't pile up inside the instruction handler.
+    fn mint_classic(&self, name: String, uri: String, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        require_keys_eq!(
+            self.token_program.key(),
+            anchor_spl::token::ID,
+            WorkoutError::TokenProgramMismatch
+        );
+        let metaplex = self
+            .metaplex
+            .as_ref()
+            .ok_or(WorkoutError::MissingMetadataAccounts)?;
+
+        self.create_mint_account(SplMint::LEN, 0)?;
+        self.initialize_mint()?;
+        self.create_token_account()?;
+        self.mint_token(signer_seeds)?;
+        if self.owner.is_signer {
+            self.approve_revoke_delegate()?;
+        }
+        self.create_metadata(metaplex, name, uri, signer_seeds)?;
+        self.create_master_edition(metaplex, signer_seeds)?;
+        if let Some(collection) = &metaplex.collection {
+            self.verify_collection(metaplex, collection, signer_seeds)?;
+        }
+        Ok(())
+    }
+
+    /// Mint one non-transferable Token-2022 token whose name, URI and
+    /// `additional_metadata` live in the mint itself (metadata pointer and token
+    /// metadata extensions). The mint authority is removed afterwards so the
+    /// supply stays at 1.
+    fn mint_soulbound(
+        &self,
+        name: String,
+        uri: String,
+        additional_metadata: Vec<(String, String)>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        require_keys_eq!(
+            self.token_program.key(),
+            anchor_spl::token_2022::ID,
+            WorkoutError::TokenProgramMismatch
+        );
+
+        let token_metadata = TokenMetadata {
+            update_authority: OptionalNonZeroPubkey::try_from(Some(self.nft_authority.key()))?,
+            mint: self.mint.key(),
+            name,
+            symbol: NFT_SYMBOL.to_string(),
+            uri,
+            additional_metadata,
+        };
+        let space = ExtensionType::try_calculate_account_len::<Token2022Mint>(&[
+            ExtensionType::NonTransferable,
+            ExtensionType::MetadataPointer,
+        ])?;
+        // The token metadata extension reallocs the mint, so fund it up front
+        self.create_mint_account(space, token_metadata.tlv_size_of()?)?;
+        self.initialize_soulbound_extensions()?;
+        self.initialize_mint()?;
+        self.initialize_token_metadata(token_metadata, signer_seeds)?;
+        self.create_token_account()?;
+        self.mint_token(signer_seeds)?;
+        self.remove_mint_authority(signer_seeds)
+    }
+
+    #[inline(never)]
+    fn create_mint_account(&self, space: usize, extra_rent_bytes: usize) -> Result<()> {
+        let lamports = Rent::get()?.minimum_balance(space + extra_rent_bytes);
+        create_account(
+            CpiContext::new(
+                self.system_program.clone(),
+                CreateAccount {
+                    from: self.payer.clone(),
+                    to: self.mint.clone(),
+                },
+            ),
+            lamports,
+            space as u64,
+            self.token_program.key,
+        )
+    }
+
+    #[inline(never)]
+    fn initialize_mint(&self) -> Result<()> {
+        initialize_mint2(
+            CpiContext::new(
+                self.token_program.clone(),
+                InitializeMint2 {
+                    mint: self.mint.clone(),
+                },
+            ),
+            0,
+            self.nft_authority.key,
+            Some(self.nft_authority.key),
+        )
+    }
+
+    #[inline(never)]
+    fn create_token_account(&self) -> Result<()> {
+        create_associated_token_account(CpiContext::new(
+            self.associated_token_program.clone(),
+            Create {
+                payer: self.payer.clone(),
+                associated_token: self.token_account.clone(),
+                authority: self.owner.clone(),
+                mint: self.mint.clone(),
+                system_program: self.system_program.clone(),
+                token_program: self.token_program.clone(),
+            },
+        ))
+    }
+
+    #[inline(never)]
+    fn mint_token(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                MintTo {
+                    mint: self.mint.clone(),
+                    to: self.token_account.clone(),
+                    authority: self.nft_authority.clone(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )
+    }
+
+    /// Approve `nft_authority` as delegate of the owner's token, which lets
+    /// revoke_attendance freeze it through Metaplex (the master edition holds
+    /// the freeze authority of classic mints).
+    #[inline(never)]
+    fn approve_revoke_delegate(&self) -> Result<()> {
+        approve(
+            CpiContext::new(
+                self.token_program.clone(),
+                Approve {
+                    to: self.token_account.clone(),
+                    delegate: self.nft_authority.clone(),
+                    authority: self.owner.clone(),
+                },
+            ),
+            1,
+        )
+    }
+
+    #[inline(never)]
+    fn create_metadata(
+        &self,
+        metaplex: &MetaplexAccounts<'a, 'info>,
+        name: String,
+        uri: String,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        create_metadata_accounts_v3(
+            CpiContext::new_with_signer(
+                metaplex.token_metadata_program.clone(),
+                CreateMetadataAccountsV3 {
+                    metadata: metaplex.metadata.clone(),
+                    mint: self.mint.clone(),
+                    mint_authority: self.nft_authority.clone(),
+                    payer: self.payer.clone(),
+                    update_authority: self.nft_authority.clone(),
+                    system_program: self.system_program.clone(),
+                    rent: metaplex.rent.clone(),
+                },
+                signer_seeds,
+            ),
+            DataV2 {
+                name: metaplex_name(&name),
+                symbol: NFT_SYMBOL.to_string(),
+                uri,
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: metaplex.collection.as_ref().map(|c| Collection {
+                    verified: false,
+                    key: c.mint.key(),
+                }),
+                uses: None,
+            },
+            true,
+            true,
+            None,
+        )
+    }
+
+    #[inline(never)]
+    fn create_master_edition(
+        &self,
+        metaplex: &MetaplexAccounts<'a, 'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        create_master_edition_v3(
+            CpiContext::new_with_signer(
+                metaplex.token_metadata_program.clone(),
+                CreateMasterEditionV3 {
+                    edition: metaplex.master_edition.clone(),
+                    mint: self.mint.clone(),
+                    update_authority: self.nft_authority.clone(),
+                    mint_authority: self.nft_authority.clone(),
+                    payer: self.payer.clone(),
+                    metadata: metaplex.metadata.clone(),
+                    token_program: self.token_program.clone(),
+                    system_program: self.system_program.clone(),
+                    rent: metaplex.rent.clone(),
+                },
+                signer_seeds,
+            ),
+            // Max supply 0: the master edition itself is the only token, no prints.
+            Some(0),
+        )
+    }
+
+    #[inline(never)]
+    fn verify_collection(
+        &self,
+        metaplex: &MetaplexAccounts<'a, 'info>,
+        collection: &AttendanceCollection<'a, 'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let record_key = collection.authority_record.map(|r| r.key());
+        let remaining_accounts: Vec<AccountInfo<'info>> =
+            collection.authority_record.into_iter().cloned().collect();
+
+        if collection.is_sized {
+            verify_sized_collection_item(
+                CpiContext::new_with_signer(
+                    metaplex.token_metadata_program.clone(),
+                    VerifySizedCollectionItem {
+                        payer: self.payer.clone(),
+                        metadata: metaplex.metadata.clone(),
+                        collection_authority: self.nft_authority.clone(),
+                        collection_mint: collection.mint.clone(),
+                        collection_metadata: collection.metadata.clone(),
+                        collection_master_edition: collection.master_edition.clone(),
+                    },
+                    signer_seeds,
+                )
+                .with_remaining_accounts(remaining_accounts),
+                record_key,
+            )
+        } else {
+            verify_collection(
+                CpiContext::new_with_signer(
+                    metaplex.token_metadata_program.clone(),
+                    VerifyCollection {
+                        payer: self.payer.clone(),
+                        metadata: metaplex.metadata.clone(),
+                        collection_authority: self.nft_authority.clone(),
+                        collection_mint: collection.mint.clone(),
+                        collection_metadata: collection.metadata.clone(),
+                        collection_master_edition: collection.master_edition.clone(),
+                    },
+                    signer_seeds,
+                )
+                .with_remaining_accounts(remaining_accounts),
+                record_key,
+            )
+        }
+    }
+
+    #[inline(never)]
+    fn initialize_soulbound_extensions(&self) -> Result<()> {
+        non_transferable_mint_initialize(CpiContext::new(
+            self.token_program.clone(),
+            NonTransferableMintInitialize {
+                token_program_id: self.token_program.clone(),
+                mint: self.mint.clone(),
+            },
+        ))?;
+        // The mint is its own metadata account
+        metadata_pointer_initialize(
+            CpiContext::new(
+                self.token_program.clone(),
+                MetadataPointerInitialize {
+                    token_program_id: self.token_program.clone(),
+                    mint: self.mint.clone(),
+                },
+            ),
+            Some(self.nft_authority.key()),
+            Some(self.mint.key()),
+        )
+    }
+
+    #[inline(never)]
+    fn initialize_token_metadata(
+        &self,
+        token_metadata: TokenMetadata,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        token_metadata_initialize(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                TokenMetadataInitialize {
+                    token_program_id: self.token_program.clone(),
+                    metadata: self.mint.clone(),
+                    update_authority: self.nft_authority.clone(),
+                    mint_authority: self.nft_authority.clone(),
+                    mint: self.mint.clone(),
+                },
+                signer_seeds,
+            ),
+            token_metadata.name,
+            token_metadata.symbol,
+            token_metadata.uri,
+        )?;
+        for (key, value) in token_metadata.additional_metadata {
+            token_metadata_update_field(
+                CpiContext::new_with_signer(
+                    self.token_program.clone(),
+                    TokenMetadataUpdateField {
+                        token_program_id: self.token_program.clone(),
+                        metadata: self.mint.clone(),
+                        update_authority: self.nft_authority.clone(),
+                    },
+                    signer_seeds,
+                ),
+                Field::Key(key),
+                value,
+            )?;
+        }
+        Ok(())
+    }
+
+    #[inline(never)]
+    fn remove_mint_authority(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        set_authority(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                SetAuthority {
+                    current_authority: self.nft_authority.clone(),
+                    account_or_mint: self.mint.clone(),
+                },
+                signer_seeds,
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )
+    }
+}
+
+/// Accounts needed to revoke a regular (non-compressed) attendance NFT.
+///
+/// Soulbound Token-2022 NFTs keep `nft_authority` as their freeze authority, so
+/// the holder's token account is frozen. Classic NFTs hand their freeze authority
+/// to the Metaplex master edition; a claimed token is delegated to
+/// `nft_authority`, which freezes it through Metaplex. The metadata of classic
+/// NFTs is rewritten as well, since granted tokens (or ones the owner moved or
+/// un-delegated) cannot be frozen: the name gets a "REVOKED" prefix and the URI
+/// is cleared.
+pub struct RevokeAttendanceNft<'a, 'info> {
+    pub mint: &'a AccountInfo<'info>,
+    /// The holder's token account; required for soulbound NFTs
+    pub token_account: Option<&'a AccountInfo<'info>>,
+    pub nft_authority: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    /// Classic NFTs only: the NFT's Metaplex metadata and the token metadata program
+    pub metaplex: Option<(&'a AccountInfo<'info>, &'a MetadataAccount, &'a AccountInfo<'info>)>,
+    /// Classic NFTs only: the NFT's master edition, needed to freeze the token
+    pub master_edition: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> RevokeAttendanceNft<'a, 'info> {
+    /// Freeze or mark the NFT, depending on which token program it was minted with.
+    pub fn revoke(&self, nft_authority_bump: u8) -> Result<()> {
+        let bump = [nft_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[NFT_AUTHORITY_SEED, &bump]];
+
+        if self.token_program.key() == anchor_spl::token_2022::ID {
+            let token_account = self.token_account.ok_or(WorkoutError::MissingNftAccounts)?;
+            return self.freeze(token_account, signer_seeds);
+        }
+        require_keys_eq!(
+            self.token_program.key(),
+            anchor_spl::token::ID,
+            WorkoutError::TokenProgramMismatch
+        );
+        let (metadata, metadata_account, token_metadata_program) = self
+            .metaplex
+            .ok_or(WorkoutError::MissingMetadataAccounts)?;
+        require_keys_eq!(
+            metadata_account.mint,
+            self.mint.key(),
+            WorkoutError::NftMismatch
+        );
+        if let (Some(token_account), Some(master_edition)) = (self.token_account, self.master_edition)
+        {
+            if self.is_delegated_to_program(token_account)? {
+                self.freeze_delegated(
+                    metadata,
+                    token_metadata_program,
+                    token_account,
+                    master_edition,
+                    signer_seeds,
+                )?;
+            }
+        }
+        self.mark_metadata_revoked(metadata, metadata_account, token_metadata_program, signer_seeds)
+    }
+
+    /// Whether `token_account` holds this NFT, is delegated to `nft_authority`
+    /// and is not frozen yet.
+    fn is_delegated_to_program(&self, token_account: &AccountInfo<'info>) -> Result<bool> {
+        if token_account.owner != self.token_program.key {
+            return Ok(false);
+        }
+        let account = SplTokenAccount::unpack(&token_account.try_borrow_data()?)?;
+        Ok(account.mint == self.mint.key()
+            && account.amount == 1
+            && account.delegate == Some(self.nft_authority.key()).into()
+            && !account.is_frozen())
+    }
+
+    #[inline(never)]
+    fn freeze_delegated(
+        &self,
+        metadata: &AccountInfo<'info>,
+        token_metadata_program: &AccountInfo<'info>,
+        token_account: &AccountInfo<'info>,
+        master_edition: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        freeze_delegated_account(CpiContext::new_with_signer(
+            token_metadata_program.clone(),
+            FreezeDelegatedAccount {
+                metadata: metadata.clone(),
+                delegate: self.nft_authority.clone(),
+                token_account: token_account.clone(),
+                edition: master_edition.clone(),
+                mint: self.mint.clone(),
+                token_program: self.token_program.clone(),
+            },
+            signer_seeds,
+        ))
+    }
+
+    #[inline(never)]
+    fn freeze(&self, token_account: &AccountInfo<'info>, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        freeze_account(CpiContext::new_with_signer(
+            self.token_program.clone(),
+            FreezeAccount {
+                account: token_account.clone(),
+                mint: self.mint.clone(),
+                authority: self.nft_authority.clone(),
+            },
+            signer_seeds,
+        ))
+    }
+
+    #[inline(never)]
+    fn mark_metadata_revoked(
+        &self,
+        metadata: &AccountInfo<'info>,
+        metadata_account: &MetadataAccount,
+        token_metadata_program: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        // Stored names are padded with NUL bytes
+        let name = metaplex_name(&format!(
+            "{}{}",
+            REVOKED_NAME_PREFIX,
+            metadata_account.name.trim_end_matches('\0')
+        ));
+        update_metadata_accounts_v2(
+            CpiContext::new_with_signer(
+                token_metadata_program.clone(),
+                UpdateMetadataAccountsV2 {
+                    metadata: metadata.clone(),
+                    update_authority: self.nft_authority.clone(),
+                },
+                signer_seeds,
+            ),
+            None,
+            Some(DataV2 {
+                name,
+                symbol: NFT_SYMBOL.to_string(),
+                uri: String::new(),
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: metadata_account.collection.clone(),
+                uses: None,
+            }),
+            None,
+            None,
+        )
+    }
+}
+
+/// Accounts needed to mint a compressed attendance NFT into the program's Merkle tree.
+///
+/// The `nft_authority` PDA is the tree creator, so only this program can mint into it.
+/// It is also the leaf delegate of every minted NFT, so revoke_attendance can burn
+/// the leaf without the owner's signature.
+pub struct MintCompressedAttendanceNft<'a, 'info> {
+    pub payer: &'a AccountInfo<'info>,
+    pub leaf_owner: &'a AccountInfo<'info>,
+    pub tree_config: &'a AccountInfo<'info>,
+    pub merkle_tree: &'a AccountInfo<'info>,
+    pub nft_authority: &'a AccountInfo<'info>,
+    pub bubblegum_program: &'a AccountInfo<'info>,
+    pub bubblegum_signer: &'a AccountInfo<'info>,
+    pub log_wrapper: &'a AccountInfo<'info>,
+    pub compression_program: &'a AccountInfo<'info>,
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub collection: Option<AttendanceCollection<'a, 'info>>,
+}
+
+impl<'a, 'info> MintCompressedAttendanceNft<'a, 'info> {
+    /// Append a compressed NFT leaf owned by `leaf_owner`.
+    ///
+    /// Returns the Bubblegum asset id, the leaf index and the leaf's data hash
+    /// (needed to burn it later) of the new NFT.
+    pub fn mint(
+        &self,
+        name: String,
+        uri: String,
+        nft_authority_bump: u8,
+    ) -> Result<(Pubkey, u64, [u8; 32])> {
+        require!(uri.len() <= MAX_URI_LENGTH, WorkoutError::MetadataUriTooLong);
+
+        // The new leaf's index is the number of assets minted into the tree so far
+        let leaf_index = TreeConfig::try_from(self.tree_config)
+            .map_err(|_| error!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))?
+            .num_minted;
+
+        let bump = [nft_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[NFT_AUTHORITY_SEED, &bump]];
+
+        let metadata = MetadataArgs {
+            name: metaplex_name(&name),
+            symbol: NFT_SYMBOL.to_string(),
+            uri,
+            seller_fee_basis_points: 0,
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: Some(TokenStandard::NonFungible),
+            collection: self.collection.as_ref().map(|c| mpl_bubblegum::types::Collection {
+                verified: false,
+                key: c.mint.key(),
+            }),
+            uses: None,
+            token_program_version: TokenProgramVersion::Original,
+            creators: vec![],
+        };
+
+        // Bubblegum hashes the metadata as stored, i.e. with the collection verified
+        let mut leaf_metadata = metadata.clone();
+        if let Some(collection) = leaf_metadata.collection.as_mut() {
+            collection.verified = true;
+        }
+        let data_hash = hash_metadata(&leaf_metadata).map_err(ProgramError::from)?;
+
+        match &self.collection {
+            Some(collection) => self.mint_to_collection(collection, metadata, signer_seeds)?,
+            None => self.mint_leaf(metadata, signer_seeds)?,
+        }
+
+        Ok((get_asset_id(self.merkle_tree.key, leaf_index), leaf_index, data_hash))
+    }
+
+    #[inline(never)]
+    fn mint_leaf(&self, metadata: MetadataArgs, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        MintV1Cpi::new(
+            self.bubblegum_program,
+            MintV1CpiAccounts {
+                tree_config: self.tree_config,
+                leaf_owner: self.leaf_owner,
+                leaf_delegate: self.nft_authority,
+                merkle_tree: self.merkle_tree,
+                payer: self.payer,
+                tree_creator_or_delegate: self.nft_authority,
+                log_wrapper: self.log_wrapper,
+                compression_program: self.compression_program,
+                system_program: self.system_program,
+            },
+            MintV1InstructionArgs { metadata },
+        )
+        .invoke_signed(signer_seeds)
+        .map_err(Into::into)
+    }
+
+    #[inline(never)]
+    fn mint_to_collection(
+        &self,
+        collection: &AttendanceCollection<'a, 'info>,
+        metadata: MetadataArgs,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        MintToCollectionV1Cpi::new(
+            self.bubblegum_program,
+            MintToCollectionV1CpiAccounts {
+                tree_config: self.tree_config,
+                leaf_owner: self.leaf_owner,
+                leaf_delegate: self.nft_authority,
+                merkle_tree: self.merkle_tree,
+                payer: self.payer,
+                tree_creator_or_delegate: self.nft_authority,
+                collection_authority: self.nft_authority,
+                collection_authority_record_pda: collection.authority_record,
+                collection_mint: collection.mint,
+                collection_metadata: collection.metadata,
+                collection_edition: collection.master_edition,
+                bubblegum_signer: self.bubblegum_signer,
+                log_wrapper: self.log_wrapper,
+                compression_program: self.compression_program,
+                token_metadata_program: self.token_metadata_program,
+                system_program: self.system_program,
+            },
+            MintToCollectionV1InstructionArgs { metadata },
+        )
+        .invoke_signed(signer_seeds)
+        .map_err(Into::into)
+    }
+}
+
+/// Accounts needed to burn a compressed attendance NFT when it is revoked.
+///
+/// The program's `nft_authority` PDA signs as leaf delegate, which Bubblegum
+/// resets when the owner transfers or re-delegates the leaf; burning such a
+/// leaf fails. `proof` holds the leaf's Merkle proof nodes, which the client
+/// passes as remaining accounts.
+pub struct BurnCompressedAttendanceNft<'a, 'info> {
+    pub leaf_owner: &'a AccountInfo<'info>,
+    pub tree_config: &'a AccountInfo<'info>,
+    pub merkle_tree: &'a AccountInfo<'info>,
+    pub nft_authority: &'a AccountInfo<'info>,
+    pub bubblegum_program: &'a AccountInfo<'info>,
+    pub log_wrapper: &'a AccountInfo<'info>,
+    pub compression_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub proof: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> BurnCompressedAttendanceNft<'a, 'info> {
+    /// Burn the leaf at `leaf_index`, proven against the tree's current `root`.
+    ///
+    /// `data_hash` is the hash returned by `MintCompressedAttendanceNft::mint`;
+    /// attendance NFTs have no creators.
+    pub fn burn(
+        &self,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        leaf_index: u64,
+        nft_authority_bump: u8,
+    ) -> Result<()> {
+        let bump = [nft_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[NFT_AUTHORITY_SEED, &bump]];
+        let proof: Vec<(&AccountInfo<'info>, bool, bool)> =
+            self.proof.iter().map(|node| (node, false, false)).collect();
+
+        BurnCpi::new(
+            self.bubblegum_program,
+            BurnCpiAccounts {
+                tree_config: self.tree_config,
+                leaf_owner: (self.leaf_owner, false),
+                leaf_delegate: (self.nft_authority, true),
+                merkle_tree: self.merkle_tree,
+                log_wrapper: self.log_wrapper,
+                compression_program: self.compression_program,
+                system_program: self.system_program,
+            },
+            BurnInstructionArgs {
+                root,
+                data_hash,
+                creator_hash: hash_creators(&[]),
+                nonce: leaf_index,
+                index: u32::try_from(leaf_index).map_err(|_| ProgramError::InvalidArgument)?,
+            },
+        )
+        .invoke_signed_with_remaining_accounts(signer_seeds, &proof)
+        .map_err(Into::into)
+    }
+}
@@ -4,22 +4,71 @@ use anchor_lang::prelude::*;
 ///
 /// Seeds: ["admin", admin_pubkey]
 ///
-/// Admins can:
-/// - Register trainers
+/// Depending on the permissions granted by the super-admin, admins can:
+/// - Register trainers, and update, suspend or remove them
 /// - Create and manage workout schedules
-/// - Open and close workout instances
-/// - Add or remove other admins (if they are the super-admin from Config)
+/// - Open, update, cancel and close workout instances
+/// - Grant or revoke attendance
+///
+/// Adding or removing other admins is reserved to the super-admin from Config.
 #[account]
 pub struct Admin {
     /// The public key of this admin
     pub authority: Pubkey,
 
+    /// Bitmask of the actions this admin may perform (see the Admin::* permission bits)
+    pub permissions: u16,
+
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl Admin {
     /// Calculate the space needed for this account
+    /// Discriminator (8) + Pubkey (32) + u16 (2) + u8 (1) = 43 bytes
+    pub const LEN: usize = 8 + 32 + 2 + 1;
+
+    /// Size of an Admin created before permissions were added
     /// Discriminator (8) + Pubkey (32) + u8 (1) = 41 bytes
-    pub const LEN: usize = 8 + 32 + 1;
+    /// Such accounts must be upgraded with migrate_admin before use
+    pub const LEGACY_LEN: usize = 8 + 32 + 1;
+
+    /// register_trainer
+    pub const REGISTER_TRAINERS: u16 = 1 << 0;
+
+    /// update_trainer_profile (for other trainers), set_trainer_active, remove_trainer
+    pub const MANAGE_TRAINERS: u16 = 1 << 1;
+
+    /// set_schedule, set_schedule_delegates, delete_schedule
+    pub const MANAGE_SCHEDULES: u16 = 1 << 2;
+
+    /// open_workout_instance, open_instance_from_schedule, update, cancel and
+    /// close_workout_instance for any trainer
+    pub const MANAGE_INSTANCES: u16 = 1 << 3;
+
+    /// grant_attendance
+    pub const GRANT_ATTENDANCE: u16 = 1 << 4;
+
+    /// revoke_attendance
+    pub const REVOKE_ATTENDANCE: u16 = 1 << 5;
+
+    /// All permission bits
+    pub const ALL_PERMISSIONS: u16 = Self::REGISTER_TRAINERS
+        | Self::MANAGE_TRAINERS
+        | Self::MANAGE_SCHEDULES
+        | Self::MANAGE_INSTANCES
+        | Self::GRANT_ATTENDANCE
+        | Self::REVOKE_ATTENDANCE;
+
+    /// Whether this admin holds `permission`.
+    pub fn has_permission(&self, permission: u16) -> bool {
+        self.permissions & permission == permission
+    }
+}
+
+/// Admin layout before permissions were added, read by migrate_admin.
+#[derive(AnchorDeserialize)]
+pub struct LegacyAdmin {
+    pub authority: Pubkey,
+    pub bump: u8,
 }
@@ -7,7 +7,9 @@ use anchor_lang::prelude::*;
 /// This account:
 /// - Proves a user attended a specific workout
 /// - Prevents double-claiming (one NFT per user per workout)
-/// - Stores the minted NFT address for reference
+/// - Records the check-in of two-phase workouts (checked in -> completed)
+/// - Marks attendance revoked by an admin, which also blocks any new claim
+/// - Stores the minted NFT address (or compressed asset id) for reference
 #[account]
 pub struct Attendance {
     /// The workout instance this attendance record belongs to
@@ -17,17 +19,98 @@ pub struct Attendance {
     pub user: Pubkey,
 
     /// Whether the user has claimed their NFT
+    /// For two-phase instances this marks completion (checked in, then out)
     pub claimed: bool,
 
     /// The NFT mint address (set after successful claim)
+    /// For compressed claims this is the Bubblegum asset id instead
     pub nft_mint: Pubkey,
 
+    /// Leaf index in Config.merkle_tree for compressed claims, None for regular NFTs
+    pub leaf_index: Option<u64>,
+
+    /// Bubblegum data hash of the compressed NFT's leaf, needed to burn it when
+    /// the attendance is revoked. Zero for regular NFTs
+    pub leaf_data_hash: [u8; 32],
+
+    /// Unix timestamp of the user's check-in, for two-phase instances
+    pub checked_in_at: Option<i64>,
+
+    /// Whether an admin revoked this attendance (the record is kept so the
+    /// user cannot claim the instance again)
+    pub revoked: bool,
+
+    /// Unix timestamp of the claim (or grant); starts the retention period
+    pub claimed_at: i64,
+
+    /// Month (YYYYMM) of the MonthlyCounter the claim was counted in (see
+    /// Config.month_source)
+    pub counted_yyyymm: u32,
+
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl Attendance {
     /// Calculate the space needed for this account
+    /// Discriminator (8) + Pubkey (32) + Pubkey (32) + bool (1) + Pubkey (32)
+    /// + Option<u64> (1 + 8) + [u8; 32] (32) + Option<i64> (1 + 8) + bool (1)
+    /// + i64 (8) + u32 (4) + u8 (1) = 169 bytes
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 32 + 9 + 32 + 9 + 1 + 8 + 4 + 1;
+
+    /// Size of an Attendance created by the first program version
     /// Discriminator (8) + Pubkey (32) + Pubkey (32) + bool (1) + Pubkey (32) + u8 (1) = 106 bytes
-    pub const LEN: usize = 8 + 32 + 32 + 1 + 32 + 1;
+    /// Such accounts must be upgraded with migrate_attendance before use
+    pub const LEGACY_LEN: usize = 8 + 32 + 32 + 1 + 32 + 1;
+
+    /// How long after the claim a user must wait before closing the record
+    /// (90 days).
+    pub const RETENTION_SECS: i64 = 90 * 86_400;
+
+    /// Whether `info`, a user's ["attendance", instance, user] PDA, holds a
+    /// claimed attendance record. The account does not exist before the user
+    /// checks in or claims, which counts as not claimed.
+    pub fn is_claimed(info: &AccountInfo) -> Result<bool> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(false);
+        }
+        let data = info.try_borrow_data()?;
+        Ok(Attendance::try_deserialize(&mut &data[..])?.claimed)
+    }
+}
+
+/// Attendance layout of the first program version, read by migrate_attendance.
+#[derive(AnchorDeserialize)]
+pub struct LegacyAttendance {
+    pub instance: Pubkey,
+    pub user: Pubkey,
+    pub claimed: bool,
+    pub nft_mint: Pubkey,
+    pub bump: u8,
+}
+
+/// Why an admin or trainer granted attendance manually instead of the user claiming it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GrantReason {
+    /// The attendee has no smartphone
+    NoPhone,
+    /// The attendee's device failed (dead battery, broken camera, ...)
+    DeviceFailure,
+    /// The QR code could not be scanned or the claim kept failing
+    ScanFailure,
+    /// Any other reason, documented off-chain
+    Other,
+}
+
+/// Why an admin revoked an attendance.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevokeReason {
+    /// Claimed with a leaked or shared secret
+    LeakedSecret,
+    /// The user was not actually present
+    NotPresent,
+    /// Attendance was granted by mistake
+    GrantedInError,
+    /// Any other reason, documented off-chain
+    Other,
 }
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+/// Status of a reservation for a workout instance.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BookingStatus {
+    /// The user holds one of the instance's max_claims spots
+    Confirmed,
+    /// The instance was full; the user is queued in the instance's Waitlist
+    Waitlisted,
+    /// The user claimed with this booking; it can no longer be cancelled
+    Consumed,
+}
+
+/// Booking (RSVP) of a user for a specific workout instance.
+///
+/// Seeds: ["booking", instance_pubkey, user_pubkey]
+///
+/// Created by book_spot and closed by cancel_booking, or by close_booking once
+/// the instance has been reclaimed. Claiming with the booking marks it consumed
+/// instead of closing it, so the user can neither cancel it (promoting someone
+/// after the spot was used) nor book the instance again.
+#[account]
+pub struct Booking {
+    /// The workout instance this booking is for
+    pub instance: Pubkey,
+
+    /// The user who booked
+    pub user: Pubkey,
+
+    /// Whether the user has a spot or is on the waitlist
+    pub status: BookingStatus,
+
+    /// Unix timestamp when the booking was made
+    pub booked_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Booking {
+    /// Calculate the space needed for this account
+    /// Discriminator (8) + Pubkey (32) + Pubkey (32) + BookingStatus (1) + i64 (8)
+    /// + u8 (1) = 82 bytes
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 1;
+
+    /// Read the booking at `info`, a user's ["booking", instance, user] PDA.
+    /// The account does not exist if the user never booked (or cancelled),
+    /// which gives None.
+    pub fn load(info: &AccountInfo) -> Result<Option<Booking>> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+        let data = info.try_borrow_data()?;
+        Ok(Some(Booking::try_deserialize(&mut &data[..])?))
+    }
+
+    /// Write this booking back to `info` after `load` and a status change.
+    pub fn store(&self, info: &AccountInfo) -> Result<()> {
+        let mut data = info.try_borrow_mut_data()?;
+        self.try_serialize(&mut &mut data[..])
+    }
+}
+
+/// First-come, first-served waitlist for a full workout instance.
+///
+/// Seeds: ["waitlist", instance_pubkey]
+///
+/// When a confirmed booking is cancelled, the user at the front is promoted.
+/// Closed by reclaim_workout_instance, refunding whoever created it.
+#[account]
+pub struct Waitlist {
+    /// The workout instance this waitlist belongs to
+    pub instance: Pubkey,
+
+    /// The user whose booking created the waitlist (paid its rent)
+    pub payer: Pubkey,
+
+    /// Waitlisted users in booking order
+    pub users: Vec<Pubkey>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Waitlist {
+    /// Maximum number of users on a waitlist
+    pub const MAX_USERS: usize = 20;
+
+    /// Calculate the space needed for this account
+    /// Discriminator (8) + Pubkey (32) + Pubkey (32) + Vec<Pubkey> (4 + 20*32)
+    /// + u8 (1) = 717 bytes
+    pub const LEN: usize = 8 + 32 + 32 + 4 + (Self::MAX_USERS * 32) + 1;
+}
@@ -1,27 +1,108 @@
 use anchor_lang::prelude::*;
 
+use crate::calendar::local_yyyymm;
+
 /// Global configuration for the workout POAP program.
 ///
 /// Seeds: ["config"]
 ///
 /// This account stores:
-/// - The super-admin authority who can add/remove other admins
+/// - The super-admin authority who can add/remove other admins, and a pending
+///   successor while a two-step authority transfer is in progress
 /// - An optional collection mint to group all workout NFTs under one collection
+/// - An optional Bubblegum Merkle tree for compressed NFT claims
+/// - The token standard used by claim_nft (classic SPL or soulbound Token-2022)
+/// - The default metadata URI template for attendance NFTs
+/// - The gym's time zone and how claims are assigned to a month
 #[account]
 pub struct Config {
     /// The super-admin public key with ultimate control
     pub authority: Pubkey,
 
     /// Optional: A pre-created collection NFT mint to group all session NFTs
-    /// If set, all minted NFTs will be set as part of this collection
+    /// If set, all minted NFTs are verified as part of this collection. The
+    /// collection's update authority must delegate collection authority to the
+    /// program's ["nft_authority"] PDA (Metaplex ApproveCollectionAuthority)
     pub collection_mint: Option<Pubkey>,
 
+    /// Optional: Bubblegum Merkle tree for compressed NFT claims
+    /// Set by create_merkle_tree; the program's ["nft_authority"] PDA is its tree creator
+    pub merkle_tree: Option<Pubkey>,
+
+    /// Token standard used by claim_nft, chosen by the super-admin via set_mint_mode
+    pub mint_mode: MintMode,
+
+    /// Default metadata URI used when an instance has no metadata_uri_override
+    /// Placeholders {instance}, {trainer}, {yyyymmdd}, {hhmm}, {user} and {ordinal}
+    /// are expanded at claim time. Empty until set by set_metadata_uri_template
+    pub metadata_uri_template: String,
+
+    /// Offset of the gym's local time from UTC, in minutes (e.g. -300 for EST).
+    /// Workout dates and times passed to open_workout_instance are local to it
+    pub utc_offset_mins: i16,
+
+    /// Which month a claim counts toward in the user's MonthlyCounter
+    pub month_source: MonthSource,
+
+    /// Proposed new super-admin, set by propose_authority_transfer until the
+    /// new key accepts (or the transfer is cancelled)
+    pub pending_authority: Option<Pubkey>,
+
     /// PDA bump seed for secure derivation
     pub bump: u8,
 }
 
 impl Config {
     /// Calculate the space needed for this account
+    /// Discriminator (8) + Pubkey (32) + Option<Pubkey> (1 + 32) + Option<Pubkey> (1 + 32)
+    /// + MintMode (1) + String (4 + 200) + i16 (2) + MonthSource (1) + Option<Pubkey> (1 + 32)
+    /// + u8 (1) = 348 bytes
+    pub const LEN: usize =
+        8 + 32 + 33 + 33 + 1 + 4 + Self::MAX_URI_TEMPLATE_LEN + 2 + 1 + 33 + 1;
+
+    /// Size of a Config created before merkle_tree and the later fields were added
     /// Discriminator (8) + Pubkey (32) + Option<Pubkey> (1 + 32) + u8 (1) = 74 bytes
-    pub const LEN: usize = 8 + 32 + 33 + 1;
+    /// Such accounts must be upgraded with migrate_config before use
+    pub const LEGACY_LEN: usize = 8 + 32 + 33 + 1;
+
+    /// Maximum length of metadata_uri_template in bytes
+    pub const MAX_URI_TEMPLATE_LEN: usize = 200;
+
+    /// The month (YYYYMM) a claim made at `now` for a workout on
+    /// `workout_yyyymmdd` counts toward, according to month_source.
+    pub fn counted_yyyymm(&self, workout_yyyymmdd: u32, now: i64) -> u32 {
+        match self.month_source {
+            MonthSource::WorkoutDate => workout_yyyymmdd / 100,
+            MonthSource::ClaimTime => local_yyyymm(now, self.utc_offset_mins),
+        }
+    }
+}
+
+/// Config layout before merkle_tree and the later fields were added, read by
+/// migrate_config.
+#[derive(AnchorDeserialize)]
+pub struct LegacyConfig {
+    pub authority: Pubkey,
+    pub collection_mint: Option<Pubkey>,
+    pub bump: u8,
+}
+
+/// Which month a claim is counted toward.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MonthSource {
+    /// The month of the workout's date (yyyymmdd of the instance)
+    WorkoutDate,
+    /// The month of the claim's Clock timestamp in the gym's local time
+    ClaimTime,
+}
+
+/// How claim_nft mints attendance NFTs.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MintMode {
+    /// SPL Token mint with Metaplex metadata and master edition (transferable)
+    Classic,
+    /// Token-2022 mint with the NonTransferable extension; name, URI, trainer
+    /// and instance are stored on the mint via the metadata-pointer and
+    /// token-metadata extensions. Not verified into Config.collection_mint.
+    Soulbound,
 }
@@ -1,15 +1,19 @@
 pub mod admin;
 pub mod attendance;
+pub mod booking;
 pub mod config;
 pub mod monthly_counter;
 pub mod schedule;
+pub mod secret_chain;
 pub mod trainer;
 pub mod workout_instance;
 
 pub use admin::*;
 pub use attendance::*;
+pub use booking::*;
 pub use config::*;
 pub use monthly_counter::*;
 pub use schedule::*;
+pub use secret_chain::*;
 pub use trainer::*;
 pub use workout_instance::*;
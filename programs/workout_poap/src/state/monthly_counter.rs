@@ -8,6 +8,7 @@ use anchor_lang::prelude::*;
 /// - Tracks how many workouts a user attended in a given month
 /// - Enables leaderboard functionality (most active users per month)
 /// - Is automatically incremented when a user claims an NFT
+/// - Is decremented again when an admin revokes an attendance
 /// 
 /// Example: For October 2025, yyyymm = 202510
 #[account]
@@ -29,5 +30,37 @@ impl MonthlyCounter {
     /// Calculate the space needed for this account
     /// Discriminator (8) + Pubkey (32) + u32 (4) + u32 (4) + u8 (1) = 49 bytes
     pub const LEN: usize = 8 + 32 + 4 + 4 + 1;
+
+    /// How long after the end of its month a counter can be closed (1 year).
+    pub const RETENTION_SECS: i64 = 365 * 86_400;
 }
 
+impl MonthlyCounter {
+    /// Count one more attendance, initializing the counter on its first use.
+    ///
+    /// Returns the new count for the month.
+    pub fn increment(&mut self, user: Pubkey, yyyymm: u32, bump: u8) -> Result<u32> {
+        if self.count == 0 {
+            // First claim for this month
+            self.user = user;
+            self.yyyymm = yyyymm;
+            self.bump = bump;
+        }
+        self.count = self
+            .count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(self.count)
+    }
+
+    /// Take back one attendance after a revocation.
+    ///
+    /// Returns the new count for the month.
+    pub fn decrement(&mut self) -> Result<u32> {
+        self.count = self
+            .count
+            .checked_sub(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(self.count)
+    }
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 
 /// A single time slot in a weekly schedule.
-/// Represents a specific day of week + time when a workout occurs.
+/// Represents a specific day of week + time when a workout occurs, and who teaches it.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct DaySlot {
     /// Day of week: 0 = Sunday, 1 = Monday, ..., 6 = Saturday
@@ -12,11 +12,29 @@ pub struct DaySlot {
 
     /// Minute (0-59)
     pub minute: u8,
+
+    /// Wallet of the trainer assigned to this slot
+    pub trainer: Pubkey,
+
+    /// Length of the workout in minutes (1-1440)
+    pub duration_mins: u16,
 }
 
 impl DaySlot {
-    /// Size: u8 + u8 + u8 = 3 bytes
-    pub const LEN: usize = 3;
+    /// Size: u8 + u8 + u8 + Pubkey (32) + u16 (2) = 37 bytes
+    pub const LEN: usize = 3 + 32 + 2;
+
+    /// Longest allowed workout (a full day)
+    pub const MAX_DURATION_MINS: u16 = 24 * 60;
+
+    /// Whether day of week, time and duration are within range.
+    pub fn is_valid(&self) -> bool {
+        self.dow <= 6
+            && self.hour <= 23
+            && self.minute <= 59
+            && self.duration_mins >= 1
+            && self.duration_mins <= Self::MAX_DURATION_MINS
+    }
 }
 
 /// Schedule account representing a recurring workout schedule template.
@@ -24,7 +42,9 @@ impl DaySlot {
 /// Seeds: ["schedule", schedule_id (as bytes)]
 ///
 /// Schedules define recurring workout times (e.g., Mon-Fri at 7:00 AM and 6:00 PM).
-/// Admins use schedules as templates to create actual WorkoutInstance accounts.
+/// Admins use schedules as templates to create actual WorkoutInstance accounts,
+/// either by hand or through open_instance_from_schedule, which checks the
+/// requested date and time against the slots.
 #[account]
 pub struct Schedule {
     /// The admin who created this schedule
@@ -37,6 +57,35 @@ pub struct Schedule {
     /// Whether this schedule is currently active
     pub is_active: bool,
 
+    /// Default claim window of instances opened from this schedule, as offsets
+    /// in seconds from the slot's start time (may be negative)
+    pub window_start_offset_secs: i32,
+
+    /// End of the default claim window, in seconds from the end of the slot
+    /// (start time + duration_mins)
+    pub window_end_offset_secs: i32,
+
+    /// First date (YYYYMMDD) on which the schedule applies
+    pub start_yyyymmdd: u32,
+
+    /// Last date (YYYYMMDD) on which the schedule applies, None if open-ended
+    pub end_yyyymmdd: Option<u32>,
+
+    /// Offset of the schedule's local time from UTC, in minutes (e.g. -300 for EST)
+    pub utc_offset_mins: i16,
+
+    /// Dates (YYYYMMDD) on which no workout takes place, e.g. public holidays
+    pub blackout_dates: Vec<u32>,
+
+    /// Other admins allowed to edit this schedule (set by the creator)
+    pub delegates: Vec<Pubkey>,
+
+    /// The admin who last created or edited this schedule
+    pub last_modified_by: Pubkey,
+
+    /// Unix timestamp of the last creation or edit
+    pub last_modified_at: i64,
+
     /// PDA bump seed
     pub bump: u8,
 }
@@ -45,7 +94,63 @@ impl Schedule {
     /// Maximum number of slots allowed per schedule
     pub const MAX_SLOTS: usize = 20;
 
+    /// Maximum number of blackout dates per schedule
+    pub const MAX_BLACKOUT_DATES: usize = 30;
+
+    /// Maximum number of delegated editors per schedule
+    pub const MAX_DELEGATES: usize = 5;
+
     /// Calculate the space needed for this account
-    /// Discriminator (8) + Pubkey (32) + Vec<DaySlot> (4 + 20*3) + bool (1) + u8 (1) = 106 bytes
-    pub const LEN: usize = 8 + 32 + 4 + (Self::MAX_SLOTS * DaySlot::LEN) + 1 + 1;
+    /// Discriminator (8) + Pubkey (32) + Vec<DaySlot> (4 + 20*37) + bool (1) + i32 (4)
+    /// + i32 (4) + u32 (4) + Option<u32> (1 + 4) + i16 (2) + Vec<u32> (4 + 30*4)
+    /// + Vec<Pubkey> (4 + 5*32) + Pubkey (32) + i64 (8) + u8 (1) = 1133 bytes
+    pub const LEN: usize = 8 + 32 + 4 + (Self::MAX_SLOTS * DaySlot::LEN) + 1 + 4 + 4 + 4 + 5
+        + 2 + 4 + (Self::MAX_BLACKOUT_DATES * 4) + 4 + (Self::MAX_DELEGATES * 32) + 32 + 8 + 1;
+
+    /// Size of a Schedule created by the first program version, whose slots
+    /// had no trainer or duration. Such accounts must be upgraded with
+    /// migrate_schedule before use
+    /// Discriminator (8) + Pubkey (32) + Vec<LegacyDaySlot> (4 + 20*3) + bool (1) + u8 (1) = 106 bytes
+    pub const LEGACY_LEN: usize = 8 + 32 + 4 + (Self::MAX_SLOTS * 3) + 1 + 1;
+}
+
+impl Schedule {
+    /// Whether `admin` may edit this schedule: its creator or one of their delegates.
+    pub fn can_edit(&self, admin: &Pubkey) -> bool {
+        self.created_by == *admin || self.delegates.contains(admin)
+    }
+
+    /// The slot taught by `trainer` on day of week `dow` at `hour:minute`, if any.
+    pub fn find_slot(&self, dow: u8, hour: u8, minute: u8, trainer: &Pubkey) -> Option<&DaySlot> {
+        self.slots.iter().find(|slot| {
+            slot.dow == dow && slot.hour == hour && slot.minute == minute && slot.trainer == *trainer
+        })
+    }
+
+    /// Whether the schedule applies on `yyyymmdd`: within the effective date
+    /// range and not a blackout date.
+    pub fn is_effective_on(&self, yyyymmdd: u32) -> bool {
+        let before_end = match self.end_yyyymmdd {
+            Some(end) => yyyymmdd <= end,
+            None => true,
+        };
+        yyyymmdd >= self.start_yyyymmdd && before_end && !self.blackout_dates.contains(&yyyymmdd)
+    }
+}
+
+/// DaySlot layout of the first program version, read by migrate_schedule.
+#[derive(AnchorDeserialize)]
+pub struct LegacyDaySlot {
+    pub dow: u8,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+/// Schedule layout of the first program version, read by migrate_schedule.
+#[derive(AnchorDeserialize)]
+pub struct LegacySchedule {
+    pub created_by: Pubkey,
+    pub slots: Vec<LegacyDaySlot>,
+    pub is_active: bool,
+    pub bump: u8,
 }
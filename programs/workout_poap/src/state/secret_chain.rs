@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+use crate::errors::WorkoutError;
+
+/// SecretChain holds a trainer's pre-committed hash chain of QR secrets.
+///
+/// Seeds: ["secret_chain", trainer_pubkey]
+///
+/// The trainer picks a private seed and commits once to the tip h^N(seed).
+/// Each ClaimMethod::HashChain session reserves the next link down the chain
+/// when it is opened: the link at depth d is the value that hashes to the tip
+/// after d rounds. The first claim of the session reveals that link, which is
+/// checked against the deepest link revealed so far and binds it to the
+/// instance. Concurrent sessions of the same trainer therefore each have their
+/// own link, and no new commitment is needed until the chain runs out.
+///
+/// Revealing a link exposes every shallower one, so a trainer's sessions must
+/// be claimed in the order they were opened: a session whose link has been
+/// exposed by a later one can no longer be claimed.
+#[account]
+pub struct SecretChain {
+    /// The trainer who owns this chain
+    pub trainer: Pubkey,
+
+    /// The deepest link revealed so far (initially the committed tip)
+    pub last_value: [u8; 32],
+
+    /// Depth of last_value below the tip
+    pub revealed_depth: u32,
+
+    /// Depth of the link reserved by the most recently opened session
+    pub reserved_depth: u32,
+
+    /// Number of links in the chain, i.e. sessions it can serve
+    pub length: u32,
+
+    /// Incremented by each commit_secret_chain call; sessions reserved from an
+    /// earlier commitment can no longer be claimed
+    pub generation: u32,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SecretChain {
+    /// Calculate the space needed for this account
+    /// Discriminator (8) + Pubkey (32) + [u8;32] (32) + u32 (4) + u32 (4) + u32 (4)
+    /// + u32 (4) + u8 (1) = 89 bytes
+    pub const LEN: usize = 8 + 32 + 32 + 4 + 4 + 4 + 4 + 1;
+
+    /// Maximum number of reserved links not yet revealed. Revealing a link costs
+    /// one SHA-256 per depth level skipped, so this bounds compute.
+    pub const MAX_UNREVEALED_LINKS: u32 = 16;
+
+    /// Reserve the next link for a newly opened session and return its depth.
+    pub fn reserve_link(&mut self) -> Result<u32> {
+        require!(
+            self.reserved_depth < self.length,
+            WorkoutError::SecretChainExhausted
+        );
+        require!(
+            self.reserved_depth - self.revealed_depth < Self::MAX_UNREVEALED_LINKS,
+            WorkoutError::TooManyUnrevealedLinks
+        );
+        self.reserved_depth += 1;
+        Ok(self.reserved_depth)
+    }
+
+    /// Check that `secret` is the link at `depth` of commitment `generation`
+    /// and record it as the deepest revealed link.
+    pub fn reveal_link(&mut self, generation: u32, depth: u32, secret: &[u8]) -> Result<()> {
+        require!(generation == self.generation, WorkoutError::StaleChainLink);
+        require!(depth > self.revealed_depth, WorkoutError::ChainLinkExposed);
+        // Links are SHA-256 outputs, so a link is always 32 bytes
+        require!(secret.len() == 32, WorkoutError::HashMismatch);
+
+        let mut link = secret.to_vec();
+        for _ in self.revealed_depth..depth {
+            link = hash(&link).to_bytes().to_vec();
+        }
+        require!(link == self.last_value, WorkoutError::HashMismatch);
+
+        self.last_value.copy_from_slice(secret);
+        self.revealed_depth = depth;
+        Ok(())
+    }
+}
@@ -7,6 +7,14 @@ use anchor_lang::prelude::*;
 /// Trainers can:
 /// - Start and close workout instances they are assigned to
 /// - Generate QR codes for attendees to claim NFTs
+///
+/// The profile (bio, avatar, specialties, default artwork) is variable-sized:
+/// the account is reallocated by update_trainer_profile to fit its content.
+///
+/// Admins can suspend a trainer with set_trainer_active; inactive trainers
+/// cannot open, update, cancel or close workout instances, nor grant
+/// attendance. A suspended trainer can then be removed with remove_trainer,
+/// which closes this account.
 #[account]
 pub struct Trainer {
     /// The public key of this trainer
@@ -16,13 +24,88 @@ pub struct Trainer {
     /// Max length: 64 bytes for UTF-8 encoded string
     pub display_name: String,
 
+    /// Whether the trainer may open and close workout instances
+    pub is_active: bool,
+
+    /// URI of the trainer's bio (empty if none)
+    pub bio_uri: String,
+
+    /// URI of the trainer's avatar image (empty if none)
+    pub avatar_uri: String,
+
+    /// Specialty tags, e.g. "hiit" or "yoga"
+    pub specialties: Vec<String>,
+
+    /// Metadata URI of the trainer's default NFT artwork, used for claims on
+    /// instances without a metadata_uri_override (empty to use the config template)
+    pub default_artwork_uri: String,
+
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl Trainer {
-    /// Calculate the space needed for this account
-    /// Discriminator (8) + Pubkey (32) + String (4 + 64) + u8 (1) = 109 bytes
+    /// Calculate the space needed for this account with an empty profile
+    /// (see `space` for a filled-in one)
     /// String uses 4 bytes for length prefix + max 64 bytes for content
-    pub const LEN: usize = 8 + 32 + 4 + 64 + 1;
+    /// Discriminator (8) + Pubkey (32) + String (4 + 64) + bool (1) + String (4)
+    /// + String (4) + Vec<String> (4) + String (4) + u8 (1) = 126 bytes
+    pub const LEN: usize = 8 + 32 + 4 + Self::MAX_DISPLAY_NAME_LEN + 1 + 4 + 4 + 4 + 4 + 1;
+
+    /// Size of a Trainer created before activation and profiles were added
+    /// Discriminator (8) + Pubkey (32) + String (4 + 64) + u8 (1) = 109 bytes
+    /// Such accounts must be upgraded with migrate_trainer before use
+    pub const LEGACY_LEN: usize = 8 + 32 + 4 + Self::MAX_DISPLAY_NAME_LEN + 1;
+
+    /// Maximum length of display_name in bytes
+    pub const MAX_DISPLAY_NAME_LEN: usize = 64;
+
+    /// Maximum length of bio_uri, avatar_uri and default_artwork_uri in bytes
+    pub const MAX_PROFILE_URI_LEN: usize = 200;
+
+    /// Maximum number of specialty tags
+    pub const MAX_SPECIALTIES: usize = 8;
+
+    /// Maximum length of a specialty tag in bytes
+    pub const MAX_SPECIALTY_LEN: usize = 32;
+
+    /// Account size needed for a profile with the given content.
+    pub fn space(
+        bio_uri: &str,
+        avatar_uri: &str,
+        specialties: &[String],
+        default_artwork_uri: &str,
+    ) -> usize {
+        Self::LEN
+            + bio_uri.len()
+            + avatar_uri.len()
+            + specialties.iter().map(|tag| 4 + tag.len()).sum::<usize>()
+            + default_artwork_uri.len()
+    }
+
+    /// Account size after applying an update_trainer_profile call, where None
+    /// keeps the current value.
+    pub fn space_after_update(
+        &self,
+        bio_uri: &Option<String>,
+        avatar_uri: &Option<String>,
+        specialties: &Option<Vec<String>>,
+        default_artwork_uri: &Option<String>,
+    ) -> usize {
+        Self::space(
+            bio_uri.as_deref().unwrap_or(&self.bio_uri),
+            avatar_uri.as_deref().unwrap_or(&self.avatar_uri),
+            specialties.as_deref().unwrap_or(&self.specialties),
+            default_artwork_uri.as_deref().unwrap_or(&self.default_artwork_uri),
+        )
+    }
+}
+
+/// Trainer layout before activation and profiles were added, read by
+/// migrate_trainer.
+#[derive(AnchorDeserialize)]
+pub struct LegacyTrainer {
+    pub authority: Pubkey,
+    pub display_name: String,
+    pub bump: u8,
 }
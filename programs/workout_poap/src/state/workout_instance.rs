@@ -1,4 +1,10 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+use crate::allowlist::verify_allowlist_proof;
+use crate::errors::WorkoutError;
+use crate::state::{Booking, BookingStatus, SecretChain};
+use crate::voucher::verify_trainer_voucher;
 
 /// WorkoutInstance represents a specific occurrence of a workout session.
 ///
@@ -15,6 +21,14 @@ use anchor_lang::prelude::*;
 /// 2. After the workout, trainer displays a QR code with the reveal_secret
 /// 3. Attendees scan the QR and submit the reveal_secret to claim their NFT
 /// 4. The program verifies sha256(reveal_secret) matches the stored secret_hash
+///
+/// With ClaimMethod::TrainerVoucher, step 2-4 are replaced by a per-user voucher
+/// signed by the trainer's wallet (see `voucher::verify_trainer_voucher`).
+/// With ClaimMethod::RotatingSecret, secret_hash is the tip of a hash chain and
+/// the QR shows a different link of the chain every period (see `verify_rotating_code`).
+/// With ClaimMethod::HashChain, the instance reserves the next link of the
+/// trainer's SecretChain when opened, and secret_hash is taken from that link on
+/// the first claim instead of being passed to open_workout_instance.
 #[account]
 pub struct WorkoutInstance {
     /// The trainer leading this workout
@@ -33,6 +47,9 @@ pub struct WorkoutInstance {
 
     /// SHA-256 hash of the reveal_secret
     /// The reveal_secret is shown in the QR code after the workout
+    /// For ClaimMethod::RotatingSecret this is the seed commitment (hash chain tip)
+    /// For ClaimMethod::HashChain this is set from the trainer's SecretChain on the
+    /// first claim
     pub secret_hash: [u8; 32],
 
     /// Whether this instance has been closed (prevents further claims)
@@ -47,10 +64,63 @@ pub struct WorkoutInstance {
     /// Minute of the workout (0-59)
     pub minute: u8,
 
+    /// Unix timestamp of the stated workout time: yyyymmdd hour:minute in the
+    /// gym's time zone (Config.utc_offset_mins), or the schedule's for
+    /// instances opened from a schedule
+    pub workout_ts: i64,
+
     /// Optional: Override metadata URI for custom artwork
-    /// If empty, the program will use a default metadata template
+    /// If empty, the program expands Config.metadata_uri_template instead
     pub metadata_uri_override: String,
 
+    /// How attendees prove they were present when claiming
+    pub claim_method: ClaimMethod,
+
+    /// ClaimMethod::HashChain only: whether secret_hash has been bound to a link
+    /// of the trainer's SecretChain
+    pub chain_link_bound: bool,
+
+    /// ClaimMethod::HashChain only: SecretChain.generation of the chain the
+    /// link was reserved from
+    pub chain_generation: u32,
+
+    /// ClaimMethod::HashChain only: depth below the chain tip of the link
+    /// reserved for this session when it was opened
+    pub chain_depth: u32,
+
+    /// Optional: Merkle root of the users allowed to claim (members-only or
+    /// pre-booked classes). Claims must then include an inclusion proof for the
+    /// signer, built with `allowlist::AllowlistTree`
+    pub allowlist_root: Option<[u8; 32]>,
+
+    /// Optional: Maximum number of claims accepted for this workout
+    pub max_claims: Option<u32>,
+
+    /// Number of claims made so far
+    pub claim_count: u32,
+
+    /// Whether claims require a confirmed Booking (see book_spot)
+    pub booking_required: bool,
+
+    /// Number of confirmed bookings not yet consumed by a claim. Claims plus
+    /// these outstanding bookings never exceed max_claims
+    pub booked_count: u32,
+
+    /// Optional: two-phase mode. Attendees must check in near the start (see
+    /// check_in) and then claim with the regular secret near the end, at least
+    /// `min_duration_secs` later
+    pub check_in: Option<CheckInPhase>,
+
+    /// Set if the workout was cancelled before anyone claimed (see
+    /// cancel_workout_instance); cancelled instances are also closed
+    pub cancel_reason: Option<CancelReason>,
+
+    /// The Schedule this instance was opened from (see open_instance_from_schedule)
+    pub schedule: Option<Pubkey>,
+
+    /// Who paid the account's rent; receives it back when the instance is reclaimed
+    pub payer: Pubkey,
+
     /// PDA bump seed
     pub bump: u8,
 }
@@ -58,6 +128,338 @@ pub struct WorkoutInstance {
 impl WorkoutInstance {
     /// Calculate the space needed for this account
     /// Discriminator (8) + Pubkey (32) + i64 (8) + i64 (8) + i64 (8) + [u8;32] (32)
+    /// + bool (1) + u32 (4) + u8 (1) + u8 (1) + i64 (8) + String (4 + 200) + ClaimMethod (1 + 4)
+    /// + bool (1) + u32 (4) + u32 (4) + Option<[u8;32]> (1 + 32) + Option<u32> (1 + 4)
+    /// + u32 (4) + bool (1) + u32 (4) + Option<CheckInPhase> (1 + 52)
+    /// + Option<CancelReason> (1 + 1) + Option<Pubkey> (1 + 32) + Pubkey (32)
+    /// + u8 (1) = 497 bytes
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 32 + 1 + 4 + 1 + 1 + 8 + 4 + 200 + 5 + 1 + 4 + 4 + 33 + 5
+        + 4 + 1 + 4 + 1 + CheckInPhase::LEN + 2 + 33 + 32 + 1;
+
+    /// Size of a WorkoutInstance created by the first program version. Such
+    /// accounts must be upgraded with migrate_workout_instance before use
+    /// Discriminator (8) + Pubkey (32) + i64 (8) + i64 (8) + i64 (8) + [u8;32] (32)
     /// + bool (1) + u32 (4) + u8 (1) + u8 (1) + String (4 + 200) + u8 (1) = 308 bytes
-    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 32 + 1 + 4 + 1 + 1 + 4 + 200 + 1;
+    pub const LEGACY_LEN: usize = 8 + 32 + 8 + 8 + 8 + 32 + 1 + 4 + 1 + 1 + 4 + 200 + 1;
+
+    /// Maximum length of metadata_uri_override
+    pub const MAX_URI_LEN: usize = 200;
+
+    /// Maximum number of rotation periods in a RotatingSecret claim window.
+    /// Verifying a code costs one SHA-256 per elapsed period, so this bounds compute.
+    pub const MAX_ROTATION_STEPS: i64 = 240;
+
+    /// How far claim and check-in windows may lie from the stated workout time
+    /// (one day on either side).
+    pub const MAX_WINDOW_DRIFT_SECS: i64 = 24 * 3_600;
+}
+
+impl WorkoutInstance {
+    /// Check a claim window: it must end after it starts, lie within
+    /// MAX_WINDOW_DRIFT_SECS of the workout time and, for rotating codes, span
+    /// fewer than MAX_ROTATION_STEPS periods.
+    pub fn validate_claim_window(
+        window_start_ts: i64,
+        window_end_ts: i64,
+        claim_method: ClaimMethod,
+        workout_ts: i64,
+    ) -> Result<()> {
+        require!(
+            window_end_ts > window_start_ts,
+            WorkoutError::InvalidTimeWindow
+        );
+        Self::validate_window_plausible(window_start_ts, window_end_ts, workout_ts)?;
+
+        // Rotating codes must fit the window in a bounded number of periods
+        if let ClaimMethod::RotatingSecret { period_secs } = claim_method {
+            require!(period_secs > 0, WorkoutError::InvalidRotationPeriod);
+            let periods = (window_end_ts - window_start_ts) / i64::from(period_secs);
+            require!(
+                periods < Self::MAX_ROTATION_STEPS,
+                WorkoutError::InvalidRotationPeriod
+            );
+        }
+        Ok(())
+    }
+
+    /// Check that a claim or check-in window lies within MAX_WINDOW_DRIFT_SECS
+    /// of the workout time `workout_ts`.
+    pub fn validate_window_plausible(
+        window_start_ts: i64,
+        window_end_ts: i64,
+        workout_ts: i64,
+    ) -> Result<()> {
+        require!(
+            window_start_ts >= workout_ts - Self::MAX_WINDOW_DRIFT_SECS
+                && window_end_ts <= workout_ts + Self::MAX_WINDOW_DRIFT_SECS,
+            WorkoutError::ImplausibleClaimWindow
+        );
+        Ok(())
+    }
+
+    /// Check that the instance still accepts claims, check-ins and bookings.
+    pub fn require_open(&self) -> Result<()> {
+        require!(self.cancel_reason.is_none(), WorkoutError::InstanceCancelled);
+        require!(!self.is_closed, WorkoutError::InstanceClosed);
+        Ok(())
+    }
+
+    /// Unix timestamp after which neither claims nor check-ins are possible.
+    pub fn activity_end_ts(&self) -> i64 {
+        match &self.check_in {
+            Some(check_in) => self.window_end_ts.max(check_in.window_end_ts),
+            None => self.window_end_ts,
+        }
+    }
+
+    /// Whether a spot is left that is neither claimed nor held by an outstanding
+    /// confirmed booking. Always true for instances without max_claims.
+    pub fn has_free_spot(&self) -> bool {
+        match self.max_claims {
+            Some(max_claims) => {
+                u64::from(self.claim_count) + u64::from(self.booked_count)
+                    < u64::from(max_claims)
+            }
+            None => true,
+        }
+    }
+
+    /// Count a new claim against `max_claims` and return the claimant's ordinal
+    /// (1 for the first attendee).
+    ///
+    /// A claim with a confirmed booking takes the spot the booking held and
+    /// marks the booking consumed; any other claim needs a free spot, so
+    /// walk-ins cannot use up the spots of confirmed bookings.
+    pub fn record_claim(&mut self, booking: Option<&mut Booking>) -> Result<u32> {
+        match booking {
+            Some(booking) => {
+                require!(
+                    booking.status == BookingStatus::Confirmed,
+                    WorkoutError::BookingNotConfirmed
+                );
+                booking.status = BookingStatus::Consumed;
+                self.booked_count = self.booked_count.saturating_sub(1);
+            }
+            None => require!(self.has_free_spot(), WorkoutError::CapacityReached),
+        }
+        self.claim_count = self
+            .claim_count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(self.claim_count)
+    }
+
+    /// Check a check-in scan at `now`: the instance is open and two-phase, `now`
+    /// is inside the check-in window and SHA-256(secret) matches the check-in hash.
+    pub fn verify_check_in(&self, now: i64, secret: &[u8]) -> Result<()> {
+        self.require_open()?;
+        let check_in = self.check_in.as_ref().ok_or(WorkoutError::CheckInNotEnabled)?;
+        require!(
+            now >= check_in.window_start_ts && now <= check_in.window_end_ts,
+            WorkoutError::InvalidClaimWindow
+        );
+        require!(
+            hash(secret).to_bytes() == check_in.secret_hash,
+            WorkoutError::HashMismatch
+        );
+        Ok(())
+    }
+
+    /// For two-phase instances, check that the claimant checked in at least
+    /// `min_duration_secs` before `now`.
+    pub fn verify_check_out(&self, now: i64, checked_in_at: Option<i64>) -> Result<()> {
+        if let Some(check_in) = &self.check_in {
+            let checked_in_at = checked_in_at.ok_or(WorkoutError::NotCheckedIn)?;
+            require!(
+                now - checked_in_at >= i64::from(check_in.min_duration_secs),
+                WorkoutError::CheckOutTooEarly
+            );
+        }
+        Ok(())
+    }
+
+    /// Check the claimant's booking (None if they have none). Required if
+    /// `booking_required`; an existing booking must be confirmed either way,
+    /// since claiming consumes it (see `record_claim`).
+    pub fn verify_booking(&self, booking: Option<&Booking>) -> Result<()> {
+        match booking {
+            Some(booking) => require!(
+                booking.status == BookingStatus::Confirmed,
+                WorkoutError::BookingNotConfirmed
+            ),
+            None => require!(!self.booking_required, WorkoutError::BookingRequired),
+        }
+        Ok(())
+    }
+}
+
+/// WorkoutInstance layout of the first program version, read by
+/// migrate_workout_instance.
+#[derive(AnchorDeserialize)]
+pub struct LegacyWorkoutInstance {
+    pub trainer: Pubkey,
+    pub start_ts: i64,
+    pub window_start_ts: i64,
+    pub window_end_ts: i64,
+    pub secret_hash: [u8; 32],
+    pub is_closed: bool,
+    pub yyyymmdd: u32,
+    pub hour: u8,
+    pub minute: u8,
+    pub metadata_uri_override: String,
+    pub bump: u8,
+}
+
+/// Check-in phase of a two-phase workout instance.
+///
+/// The instance's own secret_hash and claim window act as the check-out phase.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CheckInPhase {
+    /// SHA-256 hash of the check-in secret shown near the start of the workout
+    pub secret_hash: [u8; 32],
+
+    /// Unix timestamp when check-in opens
+    pub window_start_ts: i64,
+
+    /// Unix timestamp when check-in closes
+    pub window_end_ts: i64,
+
+    /// Minimum time between a user's check-in and check-out, in seconds
+    pub min_duration_secs: u32,
+}
+
+impl CheckInPhase {
+    /// Size: [u8;32] (32) + i64 (8) + i64 (8) + u32 (4) = 52 bytes
+    pub const LEN: usize = 32 + 8 + 8 + 4;
+}
+
+/// How attendees of a workout instance prove attendance.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClaimMethod {
+    /// Everyone scans the same QR code; SHA-256(reveal_secret) must match secret_hash
+    RevealSecret,
+    /// Each attendee gets a voucher signed by the trainer's wallet for their own
+    /// pubkey, so a leaked voucher is useless to anyone else
+    TrainerVoucher,
+    /// The QR code changes every `period_secs`. secret_hash commits to the tip of
+    /// a SHA-256 chain; the code for period k (counted from window_start_ts) is
+    /// the link that hashes to secret_hash after k + 1 rounds. Codes for future
+    /// periods cannot be derived from the current one.
+    RotatingSecret { period_secs: u32 },
+    /// Recurring classes: each session reserves the next preimage of the trainer's
+    /// pre-committed SecretChain when opened, so no secret_hash is passed
+    HashChain,
+}
+
+/// Why a workout instance was cancelled.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CancelReason {
+    /// The trainer is sick or otherwise unavailable
+    TrainerUnavailable,
+    /// The room or facility cannot be used
+    FacilityUnavailable,
+    /// Weather conditions (outdoor classes)
+    Weather,
+    /// Too few bookings
+    LowAttendance,
+    /// Any other reason, documented off-chain
+    Other,
+}
+
+/// Proof of attendance submitted with a claim. Must match the instance's ClaimMethod.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum ClaimProof {
+    /// The secret shown in the QR code (the current code for RotatingSecret instances)
+    RevealSecret { secret: Vec<u8> },
+    /// A trainer-signed voucher over (instance, user, expiry_ts), verified through
+    /// an Ed25519 program instruction placed right before the claim
+    TrainerVoucher { expiry_ts: i64 },
+}
+
+impl WorkoutInstance {
+    /// Check that a claim by `user` at `now` with `proof` is valid: the instance
+    /// is open, `now` is inside the claim window, `user` is on the allowlist (if
+    /// any) and the proof matches the instance's claim method.
+    ///
+    /// `instructions_sysvar` is only needed for trainer vouchers and
+    /// `secret_chain` only for hash-chain instances. The first hash-chain claim
+    /// checks the revealed link against the depth reserved for this instance,
+    /// binds it to the instance and advances the chain.
+    pub fn verify_claim(
+        &mut self,
+        instance_key: &Pubkey,
+        user: &Pubkey,
+        now: i64,
+        proof: &ClaimProof,
+        allowlist_proof: &[[u8; 32]],
+        instructions_sysvar: Option<&AccountInfo>,
+        secret_chain: Option<&mut SecretChain>,
+    ) -> Result<()> {
+        self.require_open()?;
+        require!(
+            now >= self.window_start_ts && now <= self.window_end_ts,
+            WorkoutError::InvalidClaimWindow
+        );
+        if let Some(root) = &self.allowlist_root {
+            require!(
+                verify_allowlist_proof(root, user, allowlist_proof),
+                WorkoutError::NotOnAllowlist
+            );
+        }
+        match (self.claim_method, proof) {
+            (ClaimMethod::RevealSecret, ClaimProof::RevealSecret { secret }) => {
+                require!(
+                    hash(secret).to_bytes() == self.secret_hash,
+                    WorkoutError::HashMismatch
+                );
+            }
+            (ClaimMethod::RotatingSecret { period_secs }, ClaimProof::RevealSecret { secret }) => {
+                let step = (now - self.window_start_ts) / i64::from(period_secs);
+                require!(
+                    verify_rotating_code(secret, &self.secret_hash, step),
+                    WorkoutError::HashMismatch
+                );
+            }
+            (ClaimMethod::HashChain, ClaimProof::RevealSecret { secret }) => {
+                let secret_hash = hash(secret).to_bytes();
+                if !self.chain_link_bound {
+                    let secret_chain = secret_chain.ok_or(WorkoutError::MissingSecretChain)?;
+                    secret_chain.reveal_link(self.chain_generation, self.chain_depth, secret)?;
+                    self.secret_hash = secret_hash;
+                    self.chain_link_bound = true;
+                }
+                require!(secret_hash == self.secret_hash, WorkoutError::HashMismatch);
+            }
+            (ClaimMethod::TrainerVoucher, ClaimProof::TrainerVoucher { expiry_ts }) => {
+                let instructions_sysvar =
+                    instructions_sysvar.ok_or(WorkoutError::MissingInstructionsSysvar)?;
+                verify_trainer_voucher(
+                    instructions_sysvar,
+                    &self.trainer,
+                    instance_key,
+                    user,
+                    *expiry_ts,
+                    now,
+                )?;
+            }
+            _ => return err!(WorkoutError::WrongClaimMethod),
+        }
+        Ok(())
+    }
+}
+
+/// Check a rotating QR code against the seed commitment `tip` at period `step`.
+///
+/// The code for period k satisfies hash^(k + 1)(code) == tip. The code for the
+/// previous period (k - 1) is also accepted, so a QR scanned just before it
+/// refreshes still works.
+fn verify_rotating_code(code: &[u8], tip: &[u8; 32], step: i64) -> bool {
+    let mut link = code.to_vec();
+    for _ in 0..step {
+        link = hash(&link).to_bytes().to_vec();
+    }
+    if step > 0 && link == tip {
+        return true;
+    }
+    hash(&link).to_bytes() == *tip
 }
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+
+use crate::errors::WorkoutError;
+
+/// Length of a voucher message: instance (32) + user (32) + expiry_ts (8).
+pub const VOUCHER_MESSAGE_LEN: usize = 32 + 32 + 8;
+
+/// Ed25519 program instruction header: num_signatures (1) + padding (1).
+const ED25519_HEADER_LEN: usize = 2;
+
+/// Size of one Ed25519SignatureOffsets entry (seven u16 fields).
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Instruction index meaning "this same instruction" in Ed25519SignatureOffsets.
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Build the message a trainer signs to issue a claim voucher:
+/// `instance || user || expiry_ts (i64 little-endian)`.
+pub fn voucher_message(instance: &Pubkey, user: &Pubkey, expiry_ts: i64) -> [u8; VOUCHER_MESSAGE_LEN] {
+    let mut message = [0u8; VOUCHER_MESSAGE_LEN];
+    message[..32].copy_from_slice(instance.as_ref());
+    message[32..64].copy_from_slice(user.as_ref());
+    message[64..].copy_from_slice(&expiry_ts.to_le_bytes());
+    message
+}
+
+/// Verify that `trainer` signed a voucher for (`instance`, `user`, `expiry_ts`)
+/// and that it hasn't expired.
+///
+/// The signature itself is checked by the native Ed25519 program, whose
+/// instruction must immediately precede the current one in the transaction.
+/// Here we only read that instruction back from the instructions sysvar and
+/// check it carries exactly one signature, by `trainer`, over the expected
+/// message, with all data inline (so no other instruction can substitute it).
+pub fn verify_trainer_voucher(
+    instructions_sysvar: &AccountInfo,
+    trainer: &Pubkey,
+    instance: &Pubkey,
+    user: &Pubkey,
+    expiry_ts: i64,
+    now: i64,
+) -> Result<()> {
+    require!(now <= expiry_ts, WorkoutError::VoucherExpired);
+
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, WorkoutError::MissingVoucherSignature);
+    let ed25519_ix = load_instruction_at_checked(current_index as usize - 1, instructions_sysvar)?;
+    require_keys_eq!(
+        ed25519_ix.program_id,
+        ed25519_program::ID,
+        WorkoutError::MissingVoucherSignature
+    );
+
+    let data = &ed25519_ix.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        WorkoutError::InvalidVoucher
+    );
+    let offsets = &data[ED25519_HEADER_LEN..ED25519_HEADER_LEN + ED25519_OFFSETS_LEN];
+    let read_u16 = |i: usize| u16::from_le_bytes([offsets[2 * i], offsets[2 * i + 1]]);
+    let signature_ix_index = read_u16(1);
+    let public_key_offset = read_u16(2) as usize;
+    let public_key_ix_index = read_u16(3);
+    let message_offset = read_u16(4) as usize;
+    let message_size = read_u16(5) as usize;
+    let message_ix_index = read_u16(6);
+
+    require!(
+        signature_ix_index == ED25519_CURRENT_INSTRUCTION
+            && public_key_ix_index == ED25519_CURRENT_INSTRUCTION
+            && message_ix_index == ED25519_CURRENT_INSTRUCTION
+            && message_size == VOUCHER_MESSAGE_LEN,
+        WorkoutError::InvalidVoucher
+    );
+
+    let signer = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(WorkoutError::InvalidVoucher)?;
+    require!(signer == trainer.as_ref(), WorkoutError::VoucherSignerMismatch);
+
+    let message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(WorkoutError::InvalidVoucher)?;
+    require!(
+        message == voucher_message(instance, user, expiry_ts),
+        WorkoutError::InvalidVoucher
+    );
+
+    Ok(())
+}
@@ -1,10 +1,36 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, AnchorError } from "@coral-xyz/anchor";
 import { WorkoutPoap } from "../target/types/workout_poap";
-import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
-import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+    PublicKey,
+    Keypair,
+    SystemProgram,
+    ComputeBudgetProgram,
+    Ed25519Program,
+    SYSVAR_RENT_PUBKEY,
+    SYSVAR_INSTRUCTIONS_PUBKEY,
+} from "@solana/web3.js";
+import {
+    TOKEN_PROGRAM_ID,
+    TOKEN_2022_PROGRAM_ID,
+    ASSOCIATED_TOKEN_PROGRAM_ID,
+    ExtensionType,
+    getAccount,
+    getAssociatedTokenAddressSync,
+    getExtensionTypes,
+    getMint,
+    getTokenMetadata,
+    createTransferCheckedInstruction,
+} from "@solana/spl-token";
+import {
+    SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+    SPL_NOOP_PROGRAM_ID,
+    ConcurrentMerkleTreeAccount,
+    getConcurrentMerkleTreeAccountSize,
+} from "@solana/spl-account-compression";
 import { expect } from "chai";
 import * as crypto from "crypto";
+import * as fs from "fs";
 
 /**
  * Workout POAP Test Suite
@@ -32,6 +58,8 @@ describe("workout_poap", () => {
     const trainer = Keypair.generate();
     const user1 = Keypair.generate();
     const user2 = Keypair.generate();
+    const user3 = Keypair.generate();
+    const merkleTree = Keypair.generate();
 
     // PDAs
     let configPda: PublicKey;
@@ -45,10 +73,53 @@ describe("workout_poap", () => {
     // Test data
     const trainerName = "Coach Alex";
     const scheduleId = "morning-bootcamp";
-    const yyyymmdd = 20251028; // Oct 28, 2025
+    // Today (UTC), so claim windows around "now" are plausible for a 7:00 workout
+    const today = new Date();
+    const yyyymmdd =
+        today.getUTCFullYear() * 10000 + (today.getUTCMonth() + 1) * 100 + today.getUTCDate();
     const hour = 7;
     const minute = 0;
-    const yyyymm = Math.floor(yyyymmdd / 100); // 202510
+    const yyyymm = Math.floor(yyyymmdd / 100);
+    const uriTemplate = "https://poap.example.com/{yyyymmdd}/{hhmm}/{instance}/{user}.json";
+
+    // Metaplex Token Metadata program
+    const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+    // Metaplex metadata and master edition PDAs of an NFT mint
+    const metadataPdas = (mint: PublicKey) => ({
+        metadata: PublicKey.findProgramAddressSync(
+            [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+            TOKEN_METADATA_PROGRAM_ID
+        )[0],
+        masterEdition: PublicKey.findProgramAddressSync(
+            [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer(), Buffer.from("edition")],
+            TOKEN_METADATA_PROGRAM_ID
+        )[0],
+    });
+
+    // Booking PDA of a user for a workout instance; claims always pass it, whether
+    // or not the user booked
+    const bookingPda = (instance: PublicKey, user: PublicKey) =>
+        PublicKey.findProgramAddressSync(
+            [Buffer.from("booking"), instance.toBuffer(), user.toBuffer()],
+            program.programId
+        )[0];
+
+    // Expect a transaction to fail with the given Anchor error code
+    const expectError = async (promise: Promise<unknown>, code: string) => {
+        try {
+            await promise;
+            expect.fail(`Should have thrown ${code} error`);
+        } catch (err) {
+            expect(err).to.be.instanceOf(AnchorError);
+            expect((err as AnchorError).error.errorCode.code).to.equal(code);
+        }
+    };
+
+    // Admin permission bits (see Admin in state/admin.rs)
+    const REGISTER_TRAINERS = 1 << 0;
+    const MANAGE_INSTANCES = 1 << 3;
+    const ALL_PERMISSIONS = (1 << 6) - 1;
 
     // Secret for QR code simulation
     let revealSecret: Buffer;
@@ -70,6 +141,9 @@ describe("workout_poap", () => {
         await provider.connection.confirmTransaction(
             await provider.connection.requestAirdrop(user2.publicKey, airdropAmount)
         );
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(user3.publicKey, airdropAmount)
+        );
 
         // Generate secret and hash (simulating trainer's QR code generation)
         revealSecret = crypto.randomBytes(32);
@@ -102,6 +176,74 @@ describe("workout_poap", () => {
         expect(config.collectionMint).to.be.null;
     });
 
+    it("Only migrates a Config in the legacy layout", async () => {
+        // A freshly initialized Config already has the current size
+        await expectError(
+            program.methods
+                .migrateConfig()
+                .accounts({
+                    authority: authority.publicKey,
+                    config: configPda,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc(),
+            "AlreadyMigrated"
+        );
+
+        const info = await provider.connection.getAccountInfo(configPda);
+        expect(info.data.length).to.equal(348);
+    });
+
+    it("Sets the metadata URI template", async () => {
+        await program.methods
+            .setMetadataUriTemplate(uriTemplate)
+            .accounts({
+                authority: authority.publicKey,
+                config: configPda,
+            })
+            .rpc();
+
+        const config = await program.account.config.fetch(configPda);
+        expect(config.metadataUriTemplate).to.equal(uriTemplate);
+    });
+
+    it("Sets the gym timezone", async () => {
+        // An offset beyond UTC+14:00 is rejected
+        await expectError(
+            program.methods
+                .setTimezone(15 * 60, { workoutDate: {} })
+                .accounts({
+                    authority: authority.publicKey,
+                    config: configPda,
+                })
+                .rpc(),
+            "InvalidUtcOffset"
+        );
+
+        await program.methods
+            .setTimezone(-300, { claimTime: {} })
+            .accounts({
+                authority: authority.publicKey,
+                config: configPda,
+            })
+            .rpc();
+        let config = await program.account.config.fetch(configPda);
+        expect(config.utcOffsetMins).to.equal(-300);
+        expect(config.monthSource).to.deep.equal({ claimTime: {} });
+
+        // Back to UTC with months taken from the workout date for the tests below
+        await program.methods
+            .setTimezone(0, { workoutDate: {} })
+            .accounts({
+                authority: authority.publicKey,
+                config: configPda,
+            })
+            .rpc();
+        config = await program.account.config.fetch(configPda);
+        expect(config.utcOffsetMins).to.equal(0);
+        expect(config.monthSource).to.deep.equal({ workoutDate: {} });
+    });
+
     it("Adds a second admin", async () => {
         // Derive admin PDA
         [admin2Pda] = PublicKey.findProgramAddressSync(
@@ -110,7 +252,7 @@ describe("workout_poap", () => {
         );
 
         const tx = await program.methods
-            .addAdmin()
+            .addAdmin(ALL_PERMISSIONS)
             .accounts({
                 authority: authority.publicKey,
                 config: configPda,
@@ -125,6 +267,169 @@ describe("workout_poap", () => {
         // Verify admin state
         const admin = await program.account.admin.fetch(admin2Pda);
         expect(admin.authority.toBase58()).to.equal(admin2.publicKey.toBase58());
+        expect(admin.permissions).to.equal(ALL_PERMISSIONS);
+    });
+
+    it("Restricts admins to their permissions", async () => {
+        // Front-desk staff may open classes but not register trainers
+        const frontDesk = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(
+                frontDesk.publicKey,
+                anchor.web3.LAMPORTS_PER_SOL
+            )
+        );
+        const [frontDeskPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("admin"), frontDesk.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods
+            .addAdmin(MANAGE_INSTANCES)
+            .accounts({
+                authority: authority.publicKey,
+                config: configPda,
+                newAdmin: frontDesk.publicKey,
+                admin: frontDeskPda,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const someTrainer = Keypair.generate();
+        await expectError(
+            program.methods
+                .registerTrainer("Coach Nobody")
+                .accounts({
+                    adminAuthority: frontDesk.publicKey,
+                    admin: frontDeskPda,
+                    trainerPubkey: someTrainer.publicKey,
+                    trainer: PublicKey.findProgramAddressSync(
+                        [Buffer.from("trainer"), someTrainer.publicKey.toBuffer()],
+                        program.programId
+                    )[0],
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([frontDesk])
+                .rpc(),
+            "MissingPermission"
+        );
+
+        // Unknown bits are rejected
+        const updatePermissions = (permissions: number) =>
+            program.methods
+                .updateAdminPermissions(permissions)
+                .accounts({
+                    authority: authority.publicKey,
+                    config: configPda,
+                    admin: frontDeskPda,
+                })
+                .rpc();
+        await expectError(updatePermissions(1 << 15), "InvalidPermissions");
+
+        await updatePermissions(MANAGE_INSTANCES | REGISTER_TRAINERS);
+        const admin = await program.account.admin.fetch(frontDeskPda);
+        expect(admin.permissions).to.equal(MANAGE_INSTANCES | REGISTER_TRAINERS);
+    });
+
+    it("Migrates an admin from before permissions existed", async () => {
+        // Loaded at genesis from tests/fixtures (see Anchor.toml): a 41-byte
+        // Admin holding only the authority and bump
+        const legacyAdminPda = new PublicKey("6j6rcYH7R5pRNrafji3RrhSSJJkEijx5k9vRrVmgpns3");
+        const legacyInfo = await provider.connection.getAccountInfo(legacyAdminPda);
+        expect(legacyInfo.data.length).to.equal(41);
+        const legacyAuthority = new PublicKey(legacyInfo.data.subarray(8, 40));
+
+        const migrate = (signer?: Keypair) =>
+            program.methods
+                .migrateAdmin()
+                .accounts({
+                    authority: (signer ?? authority).publicKey,
+                    config: configPda,
+                    adminAuthority: legacyAuthority,
+                    admin: legacyAdminPda,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers(signer ? [signer] : [])
+                .rpc();
+
+        // Only the super-admin can migrate admins
+        await expectError(migrate(admin2), "NotConfigAuthority");
+
+        // The migrated admin keeps its authority and gets every permission
+        await migrate();
+        const info = await provider.connection.getAccountInfo(legacyAdminPda);
+        expect(info.data.length).to.equal(43);
+        const admin = await program.account.admin.fetch(legacyAdminPda);
+        expect(admin.authority.toBase58()).to.equal(legacyAuthority.toBase58());
+        expect(admin.permissions).to.equal(ALL_PERMISSIONS);
+
+        // A second migration, or one of an admin created by add_admin, is refused
+        await expectError(migrate(), "AlreadyMigrated");
+    });
+
+    it("Migrates a trainer and a schedule from the first program version", async () => {
+        // Loaded at genesis from tests/fixtures (see Anchor.toml): a 109-byte
+        // Trainer holding only the authority, display name and bump, and a
+        // 106-byte Schedule whose two slots have no trainer or duration
+        const legacyTrainerPda = new PublicKey("LEexMsDveJgTcUTUFAc5VvjR6KJ22Q3oKhW4PpwxWn1");
+        const legacySchedulePda = new PublicKey("CagkHRKkgBPN7LFJNoMsQmiadAovBh6BKW5oNx49NpXQ");
+        const legacyTrainerInfo = await provider.connection.getAccountInfo(legacyTrainerPda);
+        expect(legacyTrainerInfo.data.length).to.equal(109);
+        const legacyScheduleInfo = await provider.connection.getAccountInfo(legacySchedulePda);
+        expect(legacyScheduleInfo.data.length).to.equal(106);
+        const legacyAuthority = new PublicKey(legacyTrainerInfo.data.subarray(8, 40));
+        const legacyCreator = new PublicKey(legacyScheduleInfo.data.subarray(8, 40));
+
+        const migrateTrainer = (signer?: Keypair) =>
+            program.methods
+                .migrateTrainer()
+                .accounts({
+                    authority: (signer ?? authority).publicKey,
+                    config: configPda,
+                    trainerAuthority: legacyAuthority,
+                    trainer: legacyTrainerPda,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers(signer ? [signer] : [])
+                .rpc();
+        const migrateSchedule = (signer?: Keypair) =>
+            program.methods
+                .migrateSchedule("legacy-bootcamp")
+                .accounts({
+                    authority: (signer ?? authority).publicKey,
+                    config: configPda,
+                    schedule: legacySchedulePda,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers(signer ? [signer] : [])
+                .rpc();
+
+        // Only the super-admin can migrate
+        await expectError(migrateTrainer(admin2), "NotConfigAuthority");
+        await expectError(migrateSchedule(admin2), "NotConfigAuthority");
+
+        // The migrated trainer keeps its name, stays active and has an empty profile
+        await migrateTrainer();
+        const legacyTrainer = await program.account.trainer.fetch(legacyTrainerPda);
+        expect(legacyTrainer.authority.toBase58()).to.equal(legacyAuthority.toBase58());
+        expect(legacyTrainer.displayName).to.equal("Legacy Coach");
+        expect(legacyTrainer.isActive).to.be.true;
+        expect(legacyTrainer.bioUri).to.equal("");
+        expect(legacyTrainer.specialties).to.deep.equal([]);
+        await expectError(migrateTrainer(), "AlreadyMigrated");
+
+        // The migrated schedule keeps its creator and slot times, but stays
+        // inactive until trainers are assigned to its slots
+        await migrateSchedule();
+        const schedule = await program.account.schedule.fetch(legacySchedulePda);
+        expect(schedule.createdBy.toBase58()).to.equal(legacyCreator.toBase58());
+        expect(schedule.isActive).to.be.false;
+        expect(schedule.slots.map((slot) => [slot.dow, slot.hour, slot.minute])).to.deep.equal([
+            [1, 7, 0],
+            [3, 18, 30],
+        ]);
+        expect(schedule.slots[0].trainer.toBase58()).to.equal(PublicKey.default.toBase58());
+        expect(schedule.slots[0].durationMins).to.equal(60);
+        await expectError(migrateSchedule(), "AlreadyMigrated");
     });
 
     it("Registers a trainer", async () => {
@@ -153,6 +458,258 @@ describe("workout_poap", () => {
         expect(trainerAccount.displayName).to.equal(trainerName);
     });
 
+    it("Edits the trainer's extended profile", async () => {
+        const updateProfile = (artworkUri: string, byAdmin = false) =>
+            program.methods
+                .updateTrainerProfile(
+                    null,
+                    "https://poap.example.com/trainers/alex/bio.md",
+                    "https://poap.example.com/trainers/alex/avatar.png",
+                    ["hiit", "mobility"],
+                    artworkUri
+                )
+                .accounts({
+                    authority: byAdmin ? authority.publicKey : trainer.publicKey,
+                    admin: byAdmin ? admin2Pda : null,
+                    trainer: trainerPda,
+                    trainerAuthority: trainer.publicKey,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers(byAdmin ? [] : [trainer])
+                .rpc();
+
+        // The trainer edits their own profile; the account grows to fit it
+        const sizeBefore = (await provider.connection.getAccountInfo(trainerPda))!.data.length;
+        const artworkUri = "https://poap.example.com/trainers/alex/artwork.json";
+        await updateProfile(artworkUri);
+        let trainerAccount = await program.account.trainer.fetch(trainerPda);
+        expect(trainerAccount.displayName).to.equal(trainerName);
+        expect(trainerAccount.specialties).to.deep.equal(["hiit", "mobility"]);
+        expect(trainerAccount.defaultArtworkUri).to.equal(artworkUri);
+        const sizeWithArtwork = (await provider.connection.getAccountInfo(trainerPda))!.data
+            .length;
+        expect(sizeWithArtwork).to.be.greaterThan(sizeBefore);
+
+        // Clearing the default artwork (claims below use the config template) shrinks it again
+        await updateProfile("");
+        trainerAccount = await program.account.trainer.fetch(trainerPda);
+        expect(trainerAccount.defaultArtworkUri).to.equal("");
+        const sizeAfter = (await provider.connection.getAccountInfo(trainerPda))!.data.length;
+        expect(sizeAfter).to.equal(sizeWithArtwork - artworkUri.length);
+
+        // An admin pays to grow the profile, but the excess rent of shrinking
+        // it always goes back to the trainer
+        await updateProfile(artworkUri, true);
+        const rentWithArtwork = (await provider.connection.getAccountInfo(trainerPda))!.lamports;
+        const trainerBalanceBefore = await provider.connection.getBalance(trainer.publicKey);
+        await updateProfile("", true);
+        const rentAfter = (await provider.connection.getAccountInfo(trainerPda))!.lamports;
+        const trainerBalanceAfter = await provider.connection.getBalance(trainer.publicKey);
+        expect(trainerBalanceAfter - trainerBalanceBefore).to.equal(rentWithArtwork - rentAfter);
+        expect(rentAfter).to.be.lessThan(rentWithArtwork);
+
+        // Profile URIs are capped at 200 bytes
+        await expectError(
+            updateProfile("https://poap.example.com/" + "a".repeat(200)),
+            "ProfileUriTooLong"
+        );
+    });
+
+    it("Updates, deactivates and removes a trainer", async () => {
+        const trainer2 = Keypair.generate();
+        const [trainer2Pda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("trainer"), trainer2.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods
+            .registerTrainer("Coach Bobb")
+            .accounts({
+                adminAuthority: authority.publicKey,
+                admin: admin2Pda,
+                trainerPubkey: trainer2.publicKey,
+                trainer: trainer2Pda,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        // The trainer fixes the typo in their name
+        await program.methods
+            .updateTrainerProfile("Coach Bob", null, null, null, null)
+            .accounts({
+                authority: trainer2.publicKey,
+                admin: null,
+                trainer: trainer2Pda,
+                trainerAuthority: trainer2.publicKey,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([trainer2])
+            .rpc();
+        let trainer2Account = await program.account.trainer.fetch(trainer2Pda);
+        expect(trainer2Account.displayName).to.equal("Coach Bob");
+        expect(trainer2Account.isActive).to.be.true;
+
+        // Someone else cannot change it
+        await expectError(
+            program.methods
+                .updateTrainerProfile("Not Bob", null, null, null, null)
+                .accounts({
+                    authority: user1.publicKey,
+                    admin: null,
+                    trainer: trainer2Pda,
+                    trainerAuthority: trainer2.publicKey,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([user1])
+                .rpc(),
+            "NotAssignedTrainer"
+        );
+
+        // The trainer commits a secret chain for recurring classes
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(
+                trainer2.publicKey,
+                anchor.web3.LAMPORTS_PER_SOL
+            )
+        );
+        const [trainer2ChainPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("secret_chain"), trainer2.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods
+            .commitSecretChain(Array.from(crypto.randomBytes(32)), 10)
+            .accounts({
+                trainerAuthority: trainer2.publicKey,
+                trainer: trainer2Pda,
+                secretChain: trainer2ChainPda,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([trainer2])
+            .rpc();
+
+        // An active trainer cannot be removed
+        const removeTrainer2 = () =>
+            program.methods
+                .removeTrainer()
+                .accounts({
+                    adminAuthority: authority.publicKey,
+                    admin: admin2Pda,
+                    trainer: trainer2Pda,
+                    trainerAuthority: trainer2.publicKey,
+                    secretChain: trainer2ChainPda,
+                })
+                .rpc();
+        await expectError(removeTrainer2(), "TrainerStillActive");
+
+        // The trainer has a workout open when they leave
+        const trainer2InstancePda = (min: number) => {
+            const yyyymmddBuf = Buffer.alloc(4);
+            yyyymmddBuf.writeUInt32LE(yyyymmdd);
+            return PublicKey.findProgramAddressSync(
+                [
+                    Buffer.from("instance"),
+                    trainer2.publicKey.toBuffer(),
+                    yyyymmddBuf,
+                    Buffer.from([hour]),
+                    Buffer.from([min]),
+                ],
+                program.programId
+            )[0];
+        };
+        const now = Math.floor(Date.now() / 1000);
+        const openTrainer2Instance = (min: number) =>
+            program.methods
+                .openWorkoutInstance(
+                    yyyymmdd,
+                    hour,
+                    min,
+                    new anchor.BN(now - 60),
+                    new anchor.BN(now + 600),
+                    Array.from(secretHash),
+                    null,
+                    { revealSecret: {} },
+                    null,
+                    null,
+                    false,
+                    null
+                )
+                .accounts({
+                    authority: authority.publicKey,
+                    admin: admin2Pda,
+                    config: configPda,
+                    trainer: trainer2Pda,
+                    instance: trainer2InstancePda(min),
+                    secretChain: null,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+        await openTrainer2Instance(minute);
+        const leftoverInstancePda = trainer2InstancePda(minute);
+
+        // Once deactivated, no workouts can be opened for the trainer
+        await program.methods
+            .setTrainerActive(false)
+            .accounts({
+                adminAuthority: authority.publicKey,
+                admin: admin2Pda,
+                trainer: trainer2Pda,
+            })
+            .rpc();
+        trainer2Account = await program.account.trainer.fetch(trainer2Pda);
+        expect(trainer2Account.isActive).to.be.false;
+
+        await expectError(openTrainer2Instance(minute + 1), "TrainerInactive");
+
+        // ...nor can the trainer still manage their open workout
+        await expectError(
+            program.methods
+                .updateWorkoutInstance(null, null, null, "https://poap.example.com/late.json")
+                .accounts({
+                    authority: trainer2.publicKey,
+                    admin: null,
+                    trainer: trainer2Pda,
+                    instance: leftoverInstancePda,
+                })
+                .signers([trainer2])
+                .rpc(),
+            "TrainerInactive"
+        );
+
+        // ...and can then be removed
+        const chainRent = await provider.connection.getBalance(trainer2ChainPda);
+        const trainer2Balance = await provider.connection.getBalance(trainer2.publicKey);
+        await removeTrainer2();
+        const info = await provider.connection.getAccountInfo(trainer2Pda);
+        expect(info).to.be.null;
+
+        // The trainer's secret chain is closed and its rent returned to them
+        expect(await provider.connection.getAccountInfo(trainer2ChainPda)).to.be.null;
+        expect(await provider.connection.getBalance(trainer2.publicKey)).to.equal(
+            trainer2Balance + chainRent
+        );
+
+        // Admins still manage the removed trainer's workout without the Trainer account
+        await program.methods
+            .updateWorkoutInstance(null, null, null, "https://poap.example.com/covered.json")
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                trainer: null,
+                instance: leftoverInstancePda,
+            })
+            .rpc();
+        await program.methods
+            .closeWorkoutInstance()
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                trainer: null,
+                instance: leftoverInstancePda,
+            })
+            .rpc();
+        const leftover = await program.account.workoutInstance.fetch(leftoverInstancePda);
+        expect(leftover.isClosed).to.be.true;
+    });
+
     it("Creates a schedule", async () => {
         // Derive schedule PDA
         [schedulePda] = PublicKey.findProgramAddressSync(
@@ -160,17 +717,34 @@ describe("workout_poap", () => {
             program.programId
         );
 
-        // Define weekly slots: Mon-Fri at 7:00 AM
-        const slots = [
-            { dow: 1, hour: 7, minute: 0 }, // Monday
-            { dow: 2, hour: 7, minute: 0 }, // Tuesday
-            { dow: 3, hour: 7, minute: 0 }, // Wednesday
-            { dow: 4, hour: 7, minute: 0 }, // Thursday
-            { dow: 5, hour: 7, minute: 0 }, // Friday
-        ];
+        // Define weekly slots: Mon-Fri at 7:00 AM, one hour each
+        const slot = (dow: number) => ({
+            dow,
+            hour: 7,
+            minute: 0,
+            trainer: trainer.publicKey,
+            durationMins: 60,
+        });
+        const slots = [1, 2, 3, 4, 5].map(slot); // Monday to Friday
+
+        // Two identical slots are rejected
+        await expectError(
+            program.methods
+                .setSchedule(scheduleId, [slot(1), slot(1)], true, 0, 15 * 60, 20311001, null, 0, [])
+                .accounts({
+                    adminAuthority: authority.publicKey,
+                    admin: admin2Pda,
+                    schedule: schedulePda,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc(),
+            "DuplicateSlot"
+        );
 
         const tx = await program.methods
-            .setSchedule(scheduleId, slots, true)
+            // Claims open at the slot time and close 15 minutes after the slot
+            // ends; no workout on Oct 30, 2031
+            .setSchedule(scheduleId, slots, true, 0, 15 * 60, 20311001, null, 0, [20311030])
             .accounts({
                 adminAuthority: authority.publicKey,
                 admin: admin2Pda,
@@ -185,6 +759,209 @@ describe("workout_poap", () => {
         const schedule = await program.account.schedule.fetch(schedulePda);
         expect(schedule.slots.length).to.equal(5);
         expect(schedule.isActive).to.be.true;
+        expect(schedule.windowEndOffsetSecs).to.equal(15 * 60);
+        expect(schedule.slots[0].durationMins).to.equal(60);
+        expect(schedule.startYyyymmdd).to.equal(20311001);
+        expect(schedule.endYyyymmdd).to.be.null;
+        expect(schedule.blackoutDates).to.deep.equal([20311030]);
+        expect(schedule.createdBy.toBase58()).to.equal(authority.publicKey.toBase58());
+        expect(schedule.lastModifiedBy.toBase58()).to.equal(authority.publicKey.toBase58());
+    });
+
+    it("Restricts schedule edits to the creator and delegates", async () => {
+        const schedule = await program.account.schedule.fetch(schedulePda);
+        const editAsAdmin2 = () =>
+            program.methods
+                .setSchedule(
+                    scheduleId,
+                    schedule.slots,
+                    true,
+                    0,
+                    15 * 60,
+                    20311001,
+                    null,
+                    0,
+                    [20311030]
+                )
+                .accounts({
+                    adminAuthority: admin2.publicKey,
+                    admin: admin2Pda,
+                    schedule: schedulePda,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([admin2])
+                .rpc();
+
+        // Another admin cannot overwrite the schedule
+        await expectError(editAsAdmin2(), "NotScheduleEditor");
+
+        // The creator delegates editing to admin2, who can then edit
+        await program.methods
+            .setScheduleDelegates([admin2.publicKey])
+            .accounts({
+                adminAuthority: authority.publicKey,
+                admin: admin2Pda,
+                schedule: schedulePda,
+            })
+            .rpc();
+        await editAsAdmin2();
+
+        const updated = await program.account.schedule.fetch(schedulePda);
+        expect(updated.createdBy.toBase58()).to.equal(authority.publicKey.toBase58());
+        expect(updated.lastModifiedBy.toBase58()).to.equal(admin2.publicKey.toBase58());
+        expect(updated.delegates.map((d) => d.toBase58())).to.deep.equal([
+            admin2.publicKey.toBase58(),
+        ]);
+    });
+
+    it("Opens a workout instance from a schedule slot", async () => {
+        const instanceFor = (date: number) => {
+            const dateBuf = Buffer.alloc(4);
+            dateBuf.writeUInt32LE(date);
+            return PublicKey.findProgramAddressSync(
+                [
+                    Buffer.from("instance"),
+                    trainer.publicKey.toBuffer(),
+                    dateBuf,
+                    Buffer.from([hour]),
+                    Buffer.from([minute]),
+                ],
+                program.programId
+            )[0];
+        };
+        const openFromSchedule = (date: number) =>
+            program.methods
+                .openInstanceFromSchedule(
+                    date,
+                    hour,
+                    minute,
+                    Array.from(secretHash),
+                    null,
+                    { revealSecret: {} },
+                    null,
+                    null,
+                    false,
+                    null
+                )
+                .accounts({
+                    authority: trainer.publicKey,
+                    admin: null,
+                    schedule: schedulePda,
+                    trainer: trainerPda,
+                    instance: instanceFor(date),
+                    secretChain: null,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([trainer])
+                .rpc();
+
+        // Nov 1, 2031 is a Saturday, which has no slot
+        await expectError(openFromSchedule(20311101), "NotAScheduledSlot");
+
+        // Oct 30, 2031 is a Thursday, but a blackout date
+        await expectError(openFromSchedule(20311030), "DateNotInSchedule");
+
+        // Oct 29, 2031 is a Wednesday
+        await openFromSchedule(20311029);
+        const instance = await program.account.workoutInstance.fetch(instanceFor(20311029));
+        const slotTs = Date.UTC(2031, 9, 29, hour, minute) / 1000;
+        expect(instance.windowStartTs.toNumber()).to.equal(slotTs);
+        expect(instance.windowEndTs.toNumber()).to.equal(slotTs + 75 * 60);
+        expect(instance.schedule.toBase58()).to.equal(schedulePda.toBase58());
+    });
+
+    it("Deletes a schedule", async () => {
+        const [tempSchedulePda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("schedule"), Buffer.from("temp-schedule")],
+            program.programId
+        );
+        await program.methods
+            .setSchedule(
+                "temp-schedule",
+                [{ dow: 6, hour: 9, minute: 30, trainer: trainer.publicKey, durationMins: 45 }],
+                true,
+                -5 * 60,
+                0,
+                20311001,
+                20311231,
+                60,
+                []
+            )
+            .accounts({
+                adminAuthority: authority.publicKey,
+                admin: admin2Pda,
+                schedule: tempSchedulePda,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        await program.methods
+            .deleteSchedule()
+            .accounts({
+                adminAuthority: authority.publicKey,
+                admin: admin2Pda,
+                schedule: tempSchedulePda,
+            })
+            .rpc();
+
+        const info = await provider.connection.getAccountInfo(tempSchedulePda);
+        expect(info).to.be.null;
+    });
+
+    it("Rejects invalid workout dates, times and windows", async () => {
+        const now = Math.floor(Date.now() / 1000);
+        const open = (date: number, h: number, windowStart: number, windowEnd: number) => {
+            const dateBuf = Buffer.alloc(4);
+            dateBuf.writeUInt32LE(date);
+            const [pda] = PublicKey.findProgramAddressSync(
+                [
+                    Buffer.from("instance"),
+                    trainer.publicKey.toBuffer(),
+                    dateBuf,
+                    Buffer.from([h]),
+                    Buffer.from([minute]),
+                ],
+                program.programId
+            );
+            return program.methods
+                .openWorkoutInstance(
+                    date,
+                    h,
+                    minute,
+                    new anchor.BN(windowStart),
+                    new anchor.BN(windowEnd),
+                    Array.from(secretHash),
+                    null,
+                    { revealSecret: {} },
+                    null,
+                    null,
+                    false,
+                    null
+                )
+                .accounts({
+                    authority: trainer.publicKey,
+                    admin: null,
+                    config: configPda,
+                    trainer: trainerPda,
+                    instance: pda,
+                    secretChain: null,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([trainer])
+                .rpc();
+        };
+        // Month 13 and February 30th do not exist
+        await expectError(open(20251399, hour, now - 60, now + 600), "InvalidDate");
+        await expectError(open(20250230, hour, now - 60, now + 600), "InvalidDate");
+        // 25:00 is not a time
+        await expectError(open(yyyymmdd, 25, now - 60, now + 600), "InvalidTime");
+        // A window three days after the workout is implausible
+        await expectError(
+            open(yyyymmdd, hour, now + 3 * 86400, now + 3 * 86400 + 600),
+            "ImplausibleClaimWindow"
+        );
+        // A window that has already ended cannot be opened (e.g. to re-open a reclaimed instance)
+        await expectError(open(yyyymmdd, hour, now - 600, now - 60), "InvalidClaimWindow");
     });
 
     it("Opens a workout instance", async () => {
@@ -216,13 +993,20 @@ describe("workout_poap", () => {
                 windowStartTs,
                 windowEndTs,
                 Array.from(secretHash), // Convert Buffer to number[]
-                null // No metadata URI override
+                null, // No metadata URI override
+                { revealSecret: {} },
+                null, // No allowlist
+                null, // No capacity limit
+                false, // No booking required
+                null // No check-in phase
             )
             .accounts({
                 authority: authority.publicKey,
                 admin: admin2Pda,
+                config: configPda,
                 trainer: trainerPda,
                 instance: instancePda,
+                secretChain: null,
                 systemProgram: SystemProgram.programId,
             })
             .rpc();
@@ -258,26 +1042,8 @@ describe("workout_poap", () => {
         const nftMint = Keypair.generate();
 
         // Derive metadata and master edition PDAs (Metaplex standard)
-        const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
-
-        const [nftMetadata] = PublicKey.findProgramAddressSync(
-            [
-                Buffer.from("metadata"),
-                TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-                nftMint.publicKey.toBuffer(),
-            ],
-            TOKEN_METADATA_PROGRAM_ID
-        );
 
-        const [nftMasterEdition] = PublicKey.findProgramAddressSync(
-            [
-                Buffer.from("metadata"),
-                TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-                nftMint.publicKey.toBuffer(),
-                Buffer.from("edition"),
-            ],
-            TOKEN_METADATA_PROGRAM_ID
-        );
+        const { metadata: nftMetadata, masterEdition: nftMasterEdition } = metadataPdas(nftMint.publicKey);
 
         // Derive user's ATA for the NFT
         const [nftTokenAccount] = PublicKey.findProgramAddressSync(
@@ -289,12 +1055,16 @@ describe("workout_poap", () => {
             ASSOCIATED_TOKEN_PROGRAM_ID
         );
 
-        const SYSVAR_INSTRUCTIONS_PUBKEY = new PublicKey("Sysvar1nstructions1111111111111111111111111");
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
 
         const tx = await program.methods
-            .claimNft(Array.from(revealSecret))
+            .claimNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
             .accounts({
                 user: user1.publicKey,
+                config: configPda,
                 instance: instancePda,
                 trainer: trainerPda,
                 attendance: attendance1Pda,
@@ -303,17 +1073,32 @@ describe("workout_poap", () => {
                 nftTokenAccount: nftTokenAccount,
                 nftMetadata: nftMetadata,
                 nftMasterEdition: nftMasterEdition,
-                sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+                nftAuthority: nftAuthority,
                 tokenProgram: TOKEN_PROGRAM_ID,
                 associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                booking: bookingPda(instancePda, user1.publicKey),
+                secretChain: null,
+                instructionsSysvar: null,
                 systemProgram: SystemProgram.programId,
                 tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                rent: SYSVAR_RENT_PUBKEY,
             })
+            .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
             .signers([user1, nftMint])
             .rpc();
 
         console.log("NFT claimed:", tx);
 
+        // Verify the user holds exactly one token of the new mint
+        const balance = await provider.connection.getTokenAccountBalance(nftTokenAccount);
+        expect(balance.value.amount).to.equal("1");
+
+        // Verify metadata and master edition were created by the token metadata program
+        const metadataInfo = await provider.connection.getAccountInfo(nftMetadata);
+        expect(metadataInfo.owner.toBase58()).to.equal(TOKEN_METADATA_PROGRAM_ID.toBase58());
+        const editionInfo = await provider.connection.getAccountInfo(nftMasterEdition);
+        expect(editionInfo.owner.toBase58()).to.equal(TOKEN_METADATA_PROGRAM_ID.toBase58());
+
         // Verify attendance
         const attendance = await program.account.attendance.fetch(attendance1Pda);
         expect(attendance.claimed).to.be.true;
@@ -329,26 +1114,8 @@ describe("workout_poap", () => {
     it("Prevents double claim", async () => {
         // Try to claim again with the same user
         const nftMint2 = Keypair.generate();
-        const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
-
-        const [nftMetadata] = PublicKey.findProgramAddressSync(
-            [
-                Buffer.from("metadata"),
-                TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-                nftMint2.publicKey.toBuffer(),
-            ],
-            TOKEN_METADATA_PROGRAM_ID
-        );
 
-        const [nftMasterEdition] = PublicKey.findProgramAddressSync(
-            [
-                Buffer.from("metadata"),
-                TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-                nftMint2.publicKey.toBuffer(),
-                Buffer.from("edition"),
-            ],
-            TOKEN_METADATA_PROGRAM_ID
-        );
+        const { metadata: nftMetadata, masterEdition: nftMasterEdition } = metadataPdas(nftMint2.publicKey);
 
         const [nftTokenAccount] = PublicKey.findProgramAddressSync(
             [
@@ -359,13 +1126,17 @@ describe("workout_poap", () => {
             ASSOCIATED_TOKEN_PROGRAM_ID
         );
 
-        const SYSVAR_INSTRUCTIONS_PUBKEY = new PublicKey("Sysvar1nstructions1111111111111111111111111");
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
 
         try {
             await program.methods
-                .claimNft(Array.from(revealSecret))
+                .claimNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
                 .accounts({
                     user: user1.publicKey,
+                    config: configPda,
                     instance: instancePda,
                     trainer: trainerPda,
                     attendance: attendance1Pda,
@@ -374,11 +1145,15 @@ describe("workout_poap", () => {
                     nftTokenAccount: nftTokenAccount,
                     nftMetadata: nftMetadata,
                     nftMasterEdition: nftMasterEdition,
-                    sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+                    nftAuthority: nftAuthority,
                     tokenProgram: TOKEN_PROGRAM_ID,
                     associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                    booking: bookingPda(instancePda, user1.publicKey),
+                    secretChain: null,
+                instructionsSysvar: null,
                     systemProgram: SystemProgram.programId,
                     tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    rent: SYSVAR_RENT_PUBKEY,
                 })
                 .signers([user1, nftMint2])
                 .rpc();
@@ -408,65 +1183,2000 @@ describe("workout_poap", () => {
         );
 
         const nftMint = Keypair.generate();
-        const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
 
-        const [nftMetadata] = PublicKey.findProgramAddressSync(
+        const { metadata: nftMetadata, masterEdition: nftMasterEdition } = metadataPdas(nftMint.publicKey);
+
+        const [nftTokenAccount] = PublicKey.findProgramAddressSync(
             [
-                Buffer.from("metadata"),
-                TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+                user2.publicKey.toBuffer(),
+                TOKEN_PROGRAM_ID.toBuffer(),
                 nftMint.publicKey.toBuffer(),
             ],
-            TOKEN_METADATA_PROGRAM_ID
+            ASSOCIATED_TOKEN_PROGRAM_ID
         );
 
-        const [nftMasterEdition] = PublicKey.findProgramAddressSync(
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+
+        try {
+            await program.methods
+                .claimNft({ revealSecret: { secret: Array.from(wrongSecret) } }, [])
+                .accounts({
+                    user: user2.publicKey,
+                    config: configPda,
+                    instance: instancePda,
+                    trainer: trainerPda,
+                    attendance: attendance2Pda,
+                    monthlyCounter: monthlyCounter2Pda,
+                    nftMint: nftMint.publicKey,
+                    nftTokenAccount: nftTokenAccount,
+                    nftMetadata: nftMetadata,
+                    nftMasterEdition: nftMasterEdition,
+                    nftAuthority: nftAuthority,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                    booking: bookingPda(instancePda, user2.publicKey),
+                    secretChain: null,
+                instructionsSysvar: null,
+                    systemProgram: SystemProgram.programId,
+                    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    rent: SYSVAR_RENT_PUBKEY,
+                })
+                .signers([user2, nftMint])
+                .rpc();
+
+            expect.fail("Should have thrown HashMismatch error");
+        } catch (err) {
+            expect(err).to.be.instanceOf(AnchorError);
+            expect((err as AnchorError).error.errorMessage).to.include("Hash mismatch");
+        }
+    });
+
+    it("Creates the Merkle tree for compressed claims", async () => {
+        const BUBBLEGUM_PROGRAM_ID = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+        const maxDepth = 14;
+        const maxBufferSize = 64;
+
+        // Allocate the tree account, owned by the account-compression program
+        const space = getConcurrentMerkleTreeAccountSize(maxDepth, maxBufferSize);
+        const lamports = await provider.connection.getMinimumBalanceForRentExemption(space);
+        const allocTree = SystemProgram.createAccount({
+            fromPubkey: authority.publicKey,
+            newAccountPubkey: merkleTree.publicKey,
+            lamports,
+            space,
+            programId: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+        });
+
+        const [treeConfig] = PublicKey.findProgramAddressSync(
+            [merkleTree.publicKey.toBuffer()],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+
+        const tx = await program.methods
+            .createMerkleTree(maxDepth, maxBufferSize)
+            .accounts({
+                authority: authority.publicKey,
+                config: configPda,
+                merkleTree: merkleTree.publicKey,
+                treeConfig,
+                nftAuthority,
+                bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+                logWrapper: SPL_NOOP_PROGRAM_ID,
+                compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .preInstructions([allocTree])
+            .signers([merkleTree])
+            .rpc();
+
+        console.log("Merkle tree created:", tx);
+
+        const config = await program.account.config.fetch(configPda);
+        expect(config.merkleTree.toBase58()).to.equal(merkleTree.publicKey.toBase58());
+    });
+
+    it("User claims compressed NFT successfully", async () => {
+        const BUBBLEGUM_PROGRAM_ID = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+
+        const [attendance2Pda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("attendance"), instancePda.toBuffer(), user2.publicKey.toBuffer()],
+            program.programId
+        );
+        const [monthlyCounter2Pda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("monthly"), user2.publicKey.toBuffer(), yyyymmBuf],
+            program.programId
+        );
+        const [treeConfig] = PublicKey.findProgramAddressSync(
+            [merkleTree.publicKey.toBuffer()],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+        const [bubblegumSigner] = PublicKey.findProgramAddressSync(
+            [Buffer.from("collection_cpi")],
+            BUBBLEGUM_PROGRAM_ID
+        );
+
+        const tx = await program.methods
+            .claimCompressedNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
+            .accounts({
+                user: user2.publicKey,
+                config: configPda,
+                instance: instancePda,
+                trainer: trainerPda,
+                attendance: attendance2Pda,
+                monthlyCounter: monthlyCounter2Pda,
+                merkleTree: merkleTree.publicKey,
+                treeConfig,
+                nftAuthority,
+                bubblegumSigner,
+                collectionMint: null,
+                collectionMetadata: null,
+                collectionMasterEdition: null,
+                collectionAuthorityRecord: null,
+                bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+                logWrapper: SPL_NOOP_PROGRAM_ID,
+                compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                booking: bookingPda(instancePda, user2.publicKey),
+                secretChain: null,
+                instructionsSysvar: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([user2])
+            .rpc();
+
+        console.log("Compressed NFT claimed:", tx);
+
+        // The first leaf in a fresh tree has index 0; its asset id is derived from (tree, index)
+        const leafIndexBuf = Buffer.alloc(8);
+        const [assetId] = PublicKey.findProgramAddressSync(
+            [Buffer.from("asset"), merkleTree.publicKey.toBuffer(), leafIndexBuf],
+            BUBBLEGUM_PROGRAM_ID
+        );
+
+        const attendance = await program.account.attendance.fetch(attendance2Pda);
+        expect(attendance.claimed).to.be.true;
+        expect(attendance.leafIndex.toNumber()).to.equal(0);
+        expect(attendance.nftMint.toBase58()).to.equal(assetId.toBase58());
+
+        const counter = await program.account.monthlyCounter.fetch(monthlyCounter2Pda);
+        expect(counter.count).to.equal(1);
+    });
+
+    it("User claims soulbound Token-2022 NFT successfully", async () => {
+        await program.methods
+            .setMintMode({ soulbound: {} })
+            .accounts({
+                authority: authority.publicKey,
+                config: configPda,
+            })
+            .rpc();
+
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+
+        const nftMint = Keypair.generate();
+        const [attendance3Pda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("attendance"), instancePda.toBuffer(), user3.publicKey.toBuffer()],
+            program.programId
+        );
+        const [monthlyCounter3Pda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("monthly"), user3.publicKey.toBuffer(), yyyymmBuf],
+            program.programId
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+        const nftTokenAccount = getAssociatedTokenAddressSync(
+            nftMint.publicKey,
+            user3.publicKey,
+            false,
+            TOKEN_2022_PROGRAM_ID
+        );
+
+        const tx = await program.methods
+            .claimNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
+            .accounts({
+                user: user3.publicKey,
+                config: configPda,
+                instance: instancePda,
+                trainer: trainerPda,
+                attendance: attendance3Pda,
+                monthlyCounter: monthlyCounter3Pda,
+                nftMint: nftMint.publicKey,
+                nftTokenAccount,
+                nftMetadata: null,
+                nftMasterEdition: null,
+                nftAuthority,
+                collectionMint: null,
+                collectionMetadata: null,
+                collectionMasterEdition: null,
+                collectionAuthorityRecord: null,
+                tokenProgram: TOKEN_2022_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                tokenMetadataProgram: null,
+                booking: bookingPda(instancePda, user3.publicKey),
+                secretChain: null,
+                instructionsSysvar: null,
+                systemProgram: SystemProgram.programId,
+                rent: SYSVAR_RENT_PUBKEY,
+            })
+            .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+            .signers([user3, nftMint])
+            .rpc();
+
+        console.log("Soulbound NFT claimed:", tx);
+
+        // Non-transferable mint with on-chain metadata and no remaining mint authority
+        const mint = await getMint(provider.connection, nftMint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+        expect(mint.supply.toString()).to.equal("1");
+        expect(mint.mintAuthority).to.be.null;
+        expect(getExtensionTypes(mint.tlvData)).to.include(ExtensionType.NonTransferable);
+
+        const metadata = await getTokenMetadata(provider.connection, nftMint.publicKey);
+        const ordinal = metadata.additionalMetadata.find(([key]) => key === "ordinal");
+        expect(Number(ordinal[1])).to.be.greaterThan(0);
+        // Token-2022 metadata has no 32-byte name limit, so the trainer name and
+        // the attendee's ordinal are kept whole
+        const isoDate = `${String(yyyymmdd).slice(0, 4)}-${String(yyyymmdd).slice(4, 6)}-${String(yyyymmdd).slice(6)}`;
+        expect(metadata.name).to.equal(`${isoDate} 07:00 • ${trainerName} #${ordinal[1]}`);
+        expect(metadata.uri).to.equal(
+            `https://poap.example.com/${yyyymmdd}/0700/${instancePda.toBase58()}/${user3.publicKey.toBase58()}.json`
+        );
+        expect(metadata.additionalMetadata).to.deep.include(["trainer", trainerName]);
+        expect(metadata.additionalMetadata).to.deep.include(["instance", instancePda.toBase58()]);
+
+        // Transfers are rejected by the token program
+        const otherAta = getAssociatedTokenAddressSync(
+            nftMint.publicKey,
+            user1.publicKey,
+            false,
+            TOKEN_2022_PROGRAM_ID
+        );
+        try {
+            await provider.sendAndConfirm(
+                new anchor.web3.Transaction().add(
+                    createTransferCheckedInstruction(
+                        nftTokenAccount,
+                        nftMint.publicKey,
+                        otherAta,
+                        user3.publicKey,
+                        1,
+                        0,
+                        [],
+                        TOKEN_2022_PROGRAM_ID
+                    )
+                ),
+                [user3]
+            );
+            expect.fail("Transfer should have failed");
+        } catch (err) {
+            expect(err.message).to.not.contain("Transfer should have failed");
+        }
+
+        const attendance = await program.account.attendance.fetch(attendance3Pda);
+        expect(attendance.claimed).to.be.true;
+        expect(attendance.nftMint.toBase58()).to.equal(nftMint.publicKey.toBase58());
+
+        // Restore classic mode for the remaining tests
+        await program.methods
+            .setMintMode({ classic: {} })
+            .accounts({
+                authority: authority.publicKey,
+                config: configPda,
+            })
+            .rpc();
+        const config = await program.account.config.fetch(configPda);
+        expect(config.mintMode).to.deep.equal({ classic: {} });
+    });
+
+    it("User claims NFT with a trainer-signed voucher", async () => {
+        const voucherMinute = 30;
+
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+
+        const [voucherInstancePda] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("instance"),
+                trainer.publicKey.toBuffer(),
+                yyyymmddBuf,
+                Buffer.from([hour]),
+                Buffer.from([voucherMinute]),
+            ],
+            program.programId
+        );
+
+        // The trainer opens their own instance; no shared secret is used
+        const now = Math.floor(Date.now() / 1000);
+        await program.methods
+            .openWorkoutInstance(
+                yyyymmdd,
+                hour,
+                voucherMinute,
+                new anchor.BN(now - 60),
+                new anchor.BN(now + 600),
+                Array.from(Buffer.alloc(32)),
+                null,
+                { trainerVoucher: {} },
+                null, // No allowlist
+                null, // No capacity limit
+                false, // No booking required
+                null // No check-in phase
+            )
+            .accounts({
+                authority: trainer.publicKey,
+                admin: null,
+                config: configPda,
+                trainer: trainerPda,
+                instance: voucherInstancePda,
+                secretChain: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([trainer])
+            .rpc();
+
+        // Voucher message: instance || user || expiry_ts (i64 LE), signed by the trainer's wallet
+        const expiryTs = new anchor.BN(now + 300);
+        const voucherFor = (user: PublicKey) =>
+            Buffer.concat([
+                voucherInstancePda.toBuffer(),
+                user.toBuffer(),
+                expiryTs.toArrayLike(Buffer, "le", 8),
+            ]);
+        const voucherIx = Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: trainer.secretKey,
+            message: voucherFor(user1.publicKey),
+        });
+
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+        const claimWithVoucher = (user: Keypair) => {
+            const nftMint = Keypair.generate();
+            const { metadata: nftMetadata, masterEdition: nftMasterEdition } = metadataPdas(nftMint.publicKey);
+            const [attendancePda] = PublicKey.findProgramAddressSync(
+                [Buffer.from("attendance"), voucherInstancePda.toBuffer(), user.publicKey.toBuffer()],
+                program.programId
+            );
+            const [monthlyCounterPda] = PublicKey.findProgramAddressSync(
+                [Buffer.from("monthly"), user.publicKey.toBuffer(), yyyymmBuf],
+                program.programId
+            );
+            return program.methods
+                .claimNft({ trainerVoucher: { expiryTs } }, [])
+                .accounts({
+                    user: user.publicKey,
+                    config: configPda,
+                    instance: voucherInstancePda,
+                    trainer: trainerPda,
+                    attendance: attendancePda,
+                    monthlyCounter: monthlyCounterPda,
+                    nftMint: nftMint.publicKey,
+                    nftTokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, user.publicKey),
+                    nftMetadata,
+                    nftMasterEdition,
+                    nftAuthority,
+                    collectionMint: null,
+                    collectionMetadata: null,
+                    collectionMasterEdition: null,
+                    collectionAuthorityRecord: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    booking: bookingPda(voucherInstancePda, user.publicKey),
+                    secretChain: null,
+                    instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+                    systemProgram: SystemProgram.programId,
+                    rent: SYSVAR_RENT_PUBKEY,
+                })
+                .preInstructions([
+                    ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+                    voucherIx,
+                ])
+                .signers([user, nftMint])
+                .rpc();
+        };
+
+        // A voucher issued to user1 is useless to user2
+        await expectError(claimWithVoucher(user2), "InvalidVoucher");
+
+        await claimWithVoucher(user1);
+
+        const [attendancePda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("attendance"), voucherInstancePda.toBuffer(), user1.publicKey.toBuffer()],
+            program.programId
+        );
+        const attendance = await program.account.attendance.fetch(attendancePda);
+        expect(attendance.claimed).to.be.true;
+    });
+
+    it("User claims compressed NFT with a rotating QR code", async () => {
+        const BUBBLEGUM_PROGRAM_ID = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+        const rotatingMinute = 45;
+        const periodSecs = 30;
+
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+
+        const [rotatingInstancePda] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("instance"),
+                trainer.publicKey.toBuffer(),
+                yyyymmddBuf,
+                Buffer.from([hour]),
+                Buffer.from([rotatingMinute]),
+            ],
+            program.programId
+        );
+
+        // The trainer's device derives a hash chain from a private seed and commits to its tip
+        const now = Math.floor(Date.now() / 1000);
+        const windowStart = now - 60;
+        const windowEnd = now + 600;
+        const chainLength = Math.floor((windowEnd - windowStart) / periodSecs) + 1;
+        const chain = [crypto.randomBytes(32)];
+        for (let i = 0; i < chainLength; i++) {
+            chain.push(crypto.createHash("sha256").update(chain[i]).digest());
+        }
+        const tip = chain[chainLength];
+        // The code for period k hashes to the tip after k + 1 rounds
+        const codeForPeriod = (k: number) => chain[chainLength - k - 1];
+
+        await program.methods
+            .openWorkoutInstance(
+                yyyymmdd,
+                hour,
+                rotatingMinute,
+                new anchor.BN(windowStart),
+                new anchor.BN(windowEnd),
+                Array.from(tip),
+                null,
+                { rotatingSecret: { periodSecs } },
+                null, // No allowlist
+                null, // No capacity limit
+                false, // No booking required
+                null // No check-in phase
+            )
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                config: configPda,
+                trainer: trainerPda,
+                instance: rotatingInstancePda,
+                secretChain: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        // Codes are counted from the window start, which therefore cannot move
+        await expectError(
+            program.methods
+                .updateWorkoutInstance(new anchor.BN(windowStart - 60), null, null, null)
+                .accounts({
+                    authority: authority.publicKey,
+                    admin: admin2Pda,
+                    trainer: trainerPda,
+                    instance: rotatingInstancePda,
+                })
+                .rpc(),
+            "RotationStartFixed"
+        );
+
+        const [attendancePda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("attendance"), rotatingInstancePda.toBuffer(), user2.publicKey.toBuffer()],
+            program.programId
+        );
+        const [monthlyCounterPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("monthly"), user2.publicKey.toBuffer(), yyyymmBuf],
+            program.programId
+        );
+        const [treeConfig] = PublicKey.findProgramAddressSync(
+            [merkleTree.publicKey.toBuffer()],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+        const [bubblegumSigner] = PublicKey.findProgramAddressSync(
+            [Buffer.from("collection_cpi")],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const claimWithCode = (code: Buffer) =>
+            program.methods
+                .claimCompressedNft({ revealSecret: { secret: Array.from(code) } }, [])
+                .accounts({
+                    user: user2.publicKey,
+                    config: configPda,
+                    instance: rotatingInstancePda,
+                    trainer: trainerPda,
+                    attendance: attendancePda,
+                    monthlyCounter: monthlyCounterPda,
+                    merkleTree: merkleTree.publicKey,
+                    treeConfig,
+                    nftAuthority,
+                    bubblegumSigner,
+                    collectionMint: null,
+                    collectionMetadata: null,
+                    collectionMasterEdition: null,
+                    collectionAuthorityRecord: null,
+                    bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+                    logWrapper: SPL_NOOP_PROGRAM_ID,
+                    compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    booking: bookingPda(rotatingInstancePda, user2.publicKey),
+                    secretChain: null,
+                instructionsSysvar: null,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([user2])
+                .rpc();
+
+        const period = Math.floor((Math.floor(Date.now() / 1000) - windowStart) / periodSecs);
+
+        // A code from several periods ago has expired
+        await expectError(claimWithCode(codeForPeriod(period - 2)), "HashMismatch");
+
+        await claimWithCode(codeForPeriod(period));
+
+        const attendance = await program.account.attendance.fetch(attendancePda);
+        expect(attendance.claimed).to.be.true;
+    });
+
+    it("Recurring sessions reveal successive links of a trainer's hash chain", async () => {
+        const BUBBLEGUM_PROGRAM_ID = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+        const sha256 = (data: Buffer) => crypto.createHash("sha256").update(data).digest();
+
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+
+        // The trainer commits once to h^3(seed); sessions reserve h^2(seed), h^1(seed), seed
+        const chainLength = 3;
+        const chain = [crypto.randomBytes(32)];
+        for (let i = 0; i < chainLength; i++) {
+            chain.push(sha256(chain[i]));
+        }
+        const [secretChainPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("secret_chain"), trainer.publicKey.toBuffer()],
+            program.programId
+        );
+
+        // A chain must serve at least one session
+        await expectError(
+            program.methods
+                .commitSecretChain(Array.from(chain[0]), 0)
+                .accounts({
+                    trainerAuthority: trainer.publicKey,
+                    trainer: trainerPda,
+                    secretChain: secretChainPda,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([trainer])
+                .rpc(),
+            "InvalidChainLength"
+        );
+
+        await program.methods
+            .commitSecretChain(Array.from(chain[chainLength]), chainLength)
+            .accounts({
+                trainerAuthority: trainer.publicKey,
+                trainer: trainerPda,
+                secretChain: secretChainPda,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([trainer])
+            .rpc();
+
+        const now = Math.floor(Date.now() / 1000);
+        const openSession = async (sessionMinute: number) => {
+            const [sessionPda] = PublicKey.findProgramAddressSync(
+                [
+                    Buffer.from("instance"),
+                    trainer.publicKey.toBuffer(),
+                    yyyymmddBuf,
+                    Buffer.from([hour]),
+                    Buffer.from([sessionMinute]),
+                ],
+                program.programId
+            );
+            await program.methods
+                .openWorkoutInstance(
+                    yyyymmdd,
+                    hour,
+                    sessionMinute,
+                    new anchor.BN(now - 60),
+                    new anchor.BN(now + 600),
+                    Array.from(Buffer.alloc(32)), // Ignored for hash-chain sessions
+                    null,
+                    { hashChain: {} },
+                    null, // No allowlist
+                    null, // No capacity limit
+                    false, // No booking required
+                    null // No check-in phase
+                )
+                .accounts({
+                    authority: trainer.publicKey,
+                    admin: null,
+                    config: configPda,
+                    trainer: trainerPda,
+                    instance: sessionPda,
+                    secretChain: secretChainPda,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([trainer])
+                .rpc();
+            return sessionPda;
+        };
+
+        const [treeConfig] = PublicKey.findProgramAddressSync(
+            [merkleTree.publicKey.toBuffer()],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+        const [bubblegumSigner] = PublicKey.findProgramAddressSync(
+            [Buffer.from("collection_cpi")],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const claim = (sessionPda: PublicKey, user: Keypair, secret: Buffer) =>
+            program.methods
+                .claimCompressedNft({ revealSecret: { secret: Array.from(secret) } }, [])
+                .accounts({
+                    user: user.publicKey,
+                    config: configPda,
+                    instance: sessionPda,
+                    trainer: trainerPda,
+                    attendance: PublicKey.findProgramAddressSync(
+                        [Buffer.from("attendance"), sessionPda.toBuffer(), user.publicKey.toBuffer()],
+                        program.programId
+                    )[0],
+                    monthlyCounter: PublicKey.findProgramAddressSync(
+                        [Buffer.from("monthly"), user.publicKey.toBuffer(), yyyymmBuf],
+                        program.programId
+                    )[0],
+                    merkleTree: merkleTree.publicKey,
+                    treeConfig,
+                    nftAuthority,
+                    bubblegumSigner,
+                    collectionMint: null,
+                    collectionMetadata: null,
+                    collectionMasterEdition: null,
+                    collectionAuthorityRecord: null,
+                    bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+                    logWrapper: SPL_NOOP_PROGRAM_ID,
+                    compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    booking: bookingPda(sessionPda, user.publicKey),
+                    secretChain: secretChainPda,
+                    instructionsSysvar: null,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([user])
+                .rpc();
+
+        // Two sessions open at the same time each reserve their own link
+        const session1 = await openSession(10);
+        const session2 = await openSession(20);
+        expect((await program.account.workoutInstance.fetch(session1)).chainDepth).to.equal(1);
+        expect((await program.account.workoutInstance.fetch(session2)).chainDepth).to.equal(2);
+
+        // The first session's link cannot be used to claim the second one
+        await expectError(claim(session2, user1, chain[chainLength - 1]), "HashMismatch");
+
+        // First session: both attendees use its link, the first claim advances the chain
+        await claim(session1, user1, chain[chainLength - 1]);
+        await claim(session1, user3, chain[chainLength - 1]);
+
+        const secretChain = await program.account.secretChain.fetch(secretChainPda);
+        expect(secretChain.revealedDepth).to.equal(1);
+        expect(secretChain.reservedDepth).to.equal(2);
+        expect(Buffer.from(secretChain.lastValue)).to.deep.equal(chain[chainLength - 1]);
+
+        // Second session: the previous link can't be replayed, its own one works
+        await expectError(claim(session2, user1, chain[chainLength - 1]), "HashMismatch");
+        await claim(session2, user1, chain[chainLength - 2]);
+
+        // A third session takes the last link; the chain is then exhausted
+        await openSession(11);
+        await expectError(openSession(12), "SecretChainExhausted");
+    });
+
+    it("Only allowlisted users can claim a members-only workout", async () => {
+        const BUBBLEGUM_PROGRAM_ID = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+        const membersMinute = 50;
+        const sha256 = (...parts: Buffer[]) => crypto.createHash("sha256").update(Buffer.concat(parts)).digest();
+
+        // Same leaf/node format as allowlist::AllowlistTree: leaf = H(0x00 || user),
+        // node = H(0x01 || min || max)
+        const leaf = (user: PublicKey) => sha256(Buffer.from([0]), user.toBuffer());
+        const parent = (a: Buffer, b: Buffer) =>
+            Buffer.compare(a, b) <= 0
+                ? sha256(Buffer.from([1]), a, b)
+                : sha256(Buffer.from([1]), b, a);
+        const root = parent(leaf(user1.publicKey), leaf(user3.publicKey));
+
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+
+        const [membersInstancePda] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("instance"),
+                trainer.publicKey.toBuffer(),
+                yyyymmddBuf,
+                Buffer.from([hour]),
+                Buffer.from([membersMinute]),
+            ],
+            program.programId
+        );
+        const now = Math.floor(Date.now() / 1000);
+        await program.methods
+            .openWorkoutInstance(
+                yyyymmdd,
+                hour,
+                membersMinute,
+                new anchor.BN(now - 60),
+                new anchor.BN(now + 600),
+                Array.from(secretHash),
+                null,
+                { revealSecret: {} },
+                Array.from(root),
+                null,
+                false,
+                null
+            )
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                config: configPda,
+                trainer: trainerPda,
+                instance: membersInstancePda,
+                secretChain: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const [treeConfig] = PublicKey.findProgramAddressSync(
+            [merkleTree.publicKey.toBuffer()],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+        const [bubblegumSigner] = PublicKey.findProgramAddressSync(
+            [Buffer.from("collection_cpi")],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const claim = (user: Keypair, proof: Buffer[]) =>
+            program.methods
+                .claimCompressedNft(
+                    { revealSecret: { secret: Array.from(revealSecret) } },
+                    proof.map((node) => Array.from(node))
+                )
+                .accounts({
+                    user: user.publicKey,
+                    config: configPda,
+                    instance: membersInstancePda,
+                    trainer: trainerPda,
+                    attendance: PublicKey.findProgramAddressSync(
+                        [Buffer.from("attendance"), membersInstancePda.toBuffer(), user.publicKey.toBuffer()],
+                        program.programId
+                    )[0],
+                    monthlyCounter: PublicKey.findProgramAddressSync(
+                        [Buffer.from("monthly"), user.publicKey.toBuffer(), yyyymmBuf],
+                        program.programId
+                    )[0],
+                    merkleTree: merkleTree.publicKey,
+                    treeConfig,
+                    nftAuthority,
+                    bubblegumSigner,
+                    collectionMint: null,
+                    collectionMetadata: null,
+                    collectionMasterEdition: null,
+                    collectionAuthorityRecord: null,
+                    bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+                    logWrapper: SPL_NOOP_PROGRAM_ID,
+                    compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    booking: bookingPda(membersInstancePda, user.publicKey),
+                    secretChain: null,
+                    instructionsSysvar: null,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([user])
+                .rpc();
+
+        // A walk-in holding the secret is refused, even with a member's proof
+        await expectError(claim(user2, [leaf(user3.publicKey)]), "NotOnAllowlist");
+
+        await claim(user1, [leaf(user3.publicKey)]);
+    });
+
+    it("Rejects claims beyond the instance's capacity", async () => {
+        const BUBBLEGUM_PROGRAM_ID = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+        const smallClassMinute = 55;
+
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+
+        const [smallClassPda] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("instance"),
+                trainer.publicKey.toBuffer(),
+                yyyymmddBuf,
+                Buffer.from([hour]),
+                Buffer.from([smallClassMinute]),
+            ],
+            program.programId
+        );
+        const now = Math.floor(Date.now() / 1000);
+        await program.methods
+            .openWorkoutInstance(
+                yyyymmdd,
+                hour,
+                smallClassMinute,
+                new anchor.BN(now - 60),
+                new anchor.BN(now + 600),
+                Array.from(secretHash),
+                null,
+                { revealSecret: {} },
+                null,
+                1, // A single spot
+                false,
+                null
+            )
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                config: configPda,
+                trainer: trainerPda,
+                instance: smallClassPda,
+                secretChain: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const [treeConfig] = PublicKey.findProgramAddressSync(
+            [merkleTree.publicKey.toBuffer()],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+        const [bubblegumSigner] = PublicKey.findProgramAddressSync(
+            [Buffer.from("collection_cpi")],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const claim = (user: Keypair) =>
+            program.methods
+                .claimCompressedNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
+                .accounts({
+                    user: user.publicKey,
+                    config: configPda,
+                    instance: smallClassPda,
+                    trainer: trainerPda,
+                    attendance: PublicKey.findProgramAddressSync(
+                        [Buffer.from("attendance"), smallClassPda.toBuffer(), user.publicKey.toBuffer()],
+                        program.programId
+                    )[0],
+                    monthlyCounter: PublicKey.findProgramAddressSync(
+                        [Buffer.from("monthly"), user.publicKey.toBuffer(), yyyymmBuf],
+                        program.programId
+                    )[0],
+                    merkleTree: merkleTree.publicKey,
+                    treeConfig,
+                    nftAuthority,
+                    bubblegumSigner,
+                    collectionMint: null,
+                    collectionMetadata: null,
+                    collectionMasterEdition: null,
+                    collectionAuthorityRecord: null,
+                    bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+                    logWrapper: SPL_NOOP_PROGRAM_ID,
+                    compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    booking: bookingPda(smallClassPda, user.publicKey),
+                    secretChain: null,
+                    instructionsSysvar: null,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([user])
+                .rpc();
+
+        await claim(user1);
+
+        await expectError(claim(user2), "CapacityReached");
+
+        const instance = await program.account.workoutInstance.fetch(smallClassPda);
+        expect(instance.claimCount).to.equal(1);
+        expect(instance.maxClaims).to.equal(1);
+    });
+
+    it("Books spots with a waitlist and requires a booking to claim", async () => {
+        const BUBBLEGUM_PROGRAM_ID = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+        const bookedMinute = 5;
+
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+
+        const [bookedInstancePda] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("instance"),
+                trainer.publicKey.toBuffer(),
+                yyyymmddBuf,
+                Buffer.from([hour]),
+                Buffer.from([bookedMinute]),
+            ],
+            program.programId
+        );
+        const [waitlistPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("waitlist"), bookedInstancePda.toBuffer()],
+            program.programId
+        );
+        const bookingOf = (user: PublicKey) =>
+            PublicKey.findProgramAddressSync(
+                [Buffer.from("booking"), bookedInstancePda.toBuffer(), user.toBuffer()],
+                program.programId
+            )[0];
+
+        const now = Math.floor(Date.now() / 1000);
+        await program.methods
+            .openWorkoutInstance(
+                yyyymmdd,
+                hour,
+                bookedMinute,
+                new anchor.BN(now - 60),
+                new anchor.BN(now + 600),
+                Array.from(secretHash),
+                null,
+                { revealSecret: {} },
+                null,
+                1, // A single spot
+                true, // Bookings required
+                null
+            )
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                config: configPda,
+                trainer: trainerPda,
+                instance: bookedInstancePda,
+                secretChain: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const attendanceOf = (user: PublicKey) =>
+            PublicKey.findProgramAddressSync(
+                [Buffer.from("attendance"), bookedInstancePda.toBuffer(), user.toBuffer()],
+                program.programId
+            )[0];
+        const book = (user: Keypair) =>
+            program.methods
+                .bookSpot()
+                .accounts({
+                    user: user.publicKey,
+                    instance: bookedInstancePda,
+                    booking: bookingOf(user.publicKey),
+                    waitlist: waitlistPda,
+                    attendance: attendanceOf(user.publicKey),
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([user])
+                .rpc();
+
+        // user1 takes the only spot, user2 is waitlisted
+        await book(user1);
+        await book(user2);
+        expect((await program.account.booking.fetch(bookingOf(user1.publicKey))).status).to.deep.equal({ confirmed: {} });
+        expect((await program.account.booking.fetch(bookingOf(user2.publicKey))).status).to.deep.equal({ waitlisted: {} });
+
+        const [treeConfig] = PublicKey.findProgramAddressSync(
+            [merkleTree.publicKey.toBuffer()],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+        const [bubblegumSigner] = PublicKey.findProgramAddressSync(
+            [Buffer.from("collection_cpi")],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const claim = (user: Keypair) =>
+            program.methods
+                .claimCompressedNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
+                .accounts({
+                    user: user.publicKey,
+                    config: configPda,
+                    instance: bookedInstancePda,
+                    trainer: trainerPda,
+                    attendance: attendanceOf(user.publicKey),
+                    monthlyCounter: PublicKey.findProgramAddressSync(
+                        [Buffer.from("monthly"), user.publicKey.toBuffer(), yyyymmBuf],
+                        program.programId
+                    )[0],
+                    merkleTree: merkleTree.publicKey,
+                    treeConfig,
+                    nftAuthority,
+                    bubblegumSigner,
+                    collectionMint: null,
+                    collectionMetadata: null,
+                    collectionMasterEdition: null,
+                    collectionAuthorityRecord: null,
+                    bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+                    logWrapper: SPL_NOOP_PROGRAM_ID,
+                    compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    booking: bookingOf(user.publicKey),
+                    secretChain: null,
+                    instructionsSysvar: null,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([user])
+                .rpc();
+
+        // Walk-ins without a booking are refused
+        await expectError(claim(user3), "BookingRequired");
+
+        const cancel = (user: Keypair, promotedBooking: PublicKey | null) =>
+            program.methods
+                .cancelBooking()
+                .accounts({
+                    user: user.publicKey,
+                    instance: bookedInstancePda,
+                    booking: bookingOf(user.publicKey),
+                    waitlist: waitlistPda,
+                    attendance: attendanceOf(user.publicKey),
+                    promotedBooking,
+                })
+                .signers([user])
+                .rpc();
+
+        // user1 cancels; user2 is promoted from the waitlist
+        await cancel(user1, bookingOf(user2.publicKey));
+        expect((await program.account.booking.fetch(bookingOf(user2.publicKey))).status).to.deep.equal({ confirmed: {} });
+        expect((await program.account.waitlist.fetch(waitlistPda)).users).to.be.empty;
+
+        // The claim consumes the booking, which can then no longer be cancelled
+        await claim(user2);
+        expect((await program.account.booking.fetch(bookingOf(user2.publicKey))).status).to.deep.equal({ consumed: {} });
+        const instance = await program.account.workoutInstance.fetch(bookedInstancePda);
+        expect(instance.claimCount).to.equal(1);
+        expect(instance.bookedCount).to.equal(0);
+        await expectError(cancel(user2, null), "BookingConsumed");
+    });
+
+    it("Walk-ins cannot take spots held by confirmed bookings, which claims and grants consume", async () => {
+        const BUBBLEGUM_PROGRAM_ID = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+        const openClassMinute = 40;
+
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+
+        const [openClassPda] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("instance"),
+                trainer.publicKey.toBuffer(),
+                yyyymmddBuf,
+                Buffer.from([hour]),
+                Buffer.from([openClassMinute]),
+            ],
+            program.programId
+        );
+        const [waitlistPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("waitlist"), openClassPda.toBuffer()],
+            program.programId
+        );
+        const bookingOf = (user: PublicKey) =>
+            PublicKey.findProgramAddressSync(
+                [Buffer.from("booking"), openClassPda.toBuffer(), user.toBuffer()],
+                program.programId
+            )[0];
+        const attendanceOf = (user: PublicKey) =>
+            PublicKey.findProgramAddressSync(
+                [Buffer.from("attendance"), openClassPda.toBuffer(), user.toBuffer()],
+                program.programId
+            )[0];
+
+        const now = Math.floor(Date.now() / 1000);
+        await program.methods
+            .openWorkoutInstance(
+                yyyymmdd,
+                hour,
+                openClassMinute,
+                new anchor.BN(now - 60),
+                new anchor.BN(now + 600),
+                Array.from(secretHash),
+                null,
+                { revealSecret: {} },
+                null,
+                3, // Three spots
+                false, // Walk-ins welcome
+                null
+            )
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                config: configPda,
+                trainer: trainerPda,
+                instance: openClassPda,
+                secretChain: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const [treeConfig] = PublicKey.findProgramAddressSync(
+            [merkleTree.publicKey.toBuffer()],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+        const [bubblegumSigner] = PublicKey.findProgramAddressSync(
+            [Buffer.from("collection_cpi")],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const claim = (user: Keypair) =>
+            program.methods
+                .claimCompressedNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
+                .accounts({
+                    user: user.publicKey,
+                    config: configPda,
+                    instance: openClassPda,
+                    trainer: trainerPda,
+                    attendance: attendanceOf(user.publicKey),
+                    monthlyCounter: PublicKey.findProgramAddressSync(
+                        [Buffer.from("monthly"), user.publicKey.toBuffer(), yyyymmBuf],
+                        program.programId
+                    )[0],
+                    merkleTree: merkleTree.publicKey,
+                    treeConfig,
+                    nftAuthority,
+                    bubblegumSigner,
+                    collectionMint: null,
+                    collectionMetadata: null,
+                    collectionMasterEdition: null,
+                    collectionAuthorityRecord: null,
+                    bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+                    logWrapper: SPL_NOOP_PROGRAM_ID,
+                    compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    booking: bookingOf(user.publicKey),
+                    secretChain: null,
+                    instructionsSysvar: null,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([user])
+                .rpc();
+
+        // Granting attendance to a user who cannot claim themselves, in classic mode
+        const grant = (user: PublicKey) => {
+            const nftMint = Keypair.generate();
+            const { metadata: nftMetadata, masterEdition: nftMasterEdition } = metadataPdas(nftMint.publicKey);
+            return program.methods
+                .grantAttendance({ deviceFailure: {} })
+                .accounts({
+                    authority: trainer.publicKey,
+                    admin: null,
+                    user,
+                    config: configPda,
+                    instance: openClassPda,
+                    trainer: trainerPda,
+                    attendance: attendanceOf(user),
+                    booking: bookingOf(user),
+                    monthlyCounter: PublicKey.findProgramAddressSync(
+                        [Buffer.from("monthly"), user.toBuffer(), yyyymmBuf],
+                        program.programId
+                    )[0],
+                    nftMint: nftMint.publicKey,
+                    nftTokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, user),
+                    nftMetadata,
+                    nftMasterEdition,
+                    nftAuthority,
+                    collectionMint: null,
+                    collectionMetadata: null,
+                    collectionMasterEdition: null,
+                    collectionAuthorityRecord: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                    rent: SYSVAR_RENT_PUBKEY,
+                })
+                .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+                .signers([trainer, nftMint])
+                .rpc();
+        };
+
+        // user1 and user2 book two of the three spots
+        for (const user of [user1, user2]) {
+            await program.methods
+                .bookSpot()
+                .accounts({
+                    user: user.publicKey,
+                    instance: openClassPda,
+                    booking: bookingOf(user.publicKey),
+                    waitlist: waitlistPda,
+                    attendance: attendanceOf(user.publicKey),
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([user])
+                .rpc();
+        }
+
+        // A walk-in takes the free spot; the others are still held by the bookings,
+        // also against a manual grant for someone without a booking
+        await claim(user3);
+        await expectError(grant(Keypair.generate().publicKey), "CapacityReached");
+
+        // A grant for user2 takes their booked spot and consumes the booking
+        await grant(user2.publicKey);
+        expect((await program.account.booking.fetch(bookingOf(user2.publicKey))).status).to.deep.equal({ consumed: {} });
+
+        // user1's claim always passes the booking PDA, so it takes the booked spot
+        await claim(user1);
+        expect((await program.account.booking.fetch(bookingOf(user1.publicKey))).status).to.deep.equal({ consumed: {} });
+
+        // Having claimed, user1 cannot cancel the booking to free another spot
+        await expectError(
+            program.methods
+                .cancelBooking()
+                .accounts({
+                    user: user1.publicKey,
+                    instance: openClassPda,
+                    booking: bookingOf(user1.publicKey),
+                    waitlist: waitlistPda,
+                    attendance: attendanceOf(user1.publicKey),
+                    promotedBooking: null,
+                })
+                .signers([user1])
+                .rpc(),
+            "BookingConsumed"
+        );
+
+        const instance = await program.account.workoutInstance.fetch(openClassPda);
+        expect(instance.claimCount).to.equal(3);
+        expect(instance.bookedCount).to.equal(0);
+    });
+
+    it("Two-phase workouts issue the NFT only after check-in and check-out", async () => {
+        const BUBBLEGUM_PROGRAM_ID = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+        const twoPhaseMinute = 35;
+        const minDurationSecs = 5;
+
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+
+        const [twoPhasePda] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("instance"),
+                trainer.publicKey.toBuffer(),
+                yyyymmddBuf,
+                Buffer.from([hour]),
+                Buffer.from([twoPhaseMinute]),
+            ],
+            program.programId
+        );
+        const attendanceOf = (user: PublicKey) =>
+            PublicKey.findProgramAddressSync(
+                [Buffer.from("attendance"), twoPhasePda.toBuffer(), user.toBuffer()],
+                program.programId
+            )[0];
+
+        const checkInSecret = crypto.randomBytes(32);
+        const now = Math.floor(Date.now() / 1000);
+        await program.methods
+            .openWorkoutInstance(
+                yyyymmdd,
+                hour,
+                twoPhaseMinute,
+                new anchor.BN(now - 60),
+                new anchor.BN(now + 600),
+                Array.from(secretHash),
+                null,
+                { revealSecret: {} },
+                null,
+                null,
+                false,
+                {
+                    secretHash: Array.from(crypto.createHash("sha256").update(checkInSecret).digest()),
+                    windowStartTs: new anchor.BN(now - 60),
+                    windowEndTs: new anchor.BN(now + 600),
+                    minDurationSecs,
+                }
+            )
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                config: configPda,
+                trainer: trainerPda,
+                instance: twoPhasePda,
+                secretChain: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        await program.methods
+            .checkIn(Array.from(checkInSecret))
+            .accounts({
+                user: user1.publicKey,
+                instance: twoPhasePda,
+                attendance: attendanceOf(user1.publicKey),
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([user1])
+            .rpc();
+
+        let attendance = await program.account.attendance.fetch(attendanceOf(user1.publicKey));
+        expect(attendance.checkedInAt).to.not.be.null;
+        expect(attendance.claimed).to.be.false;
+
+        const [treeConfig] = PublicKey.findProgramAddressSync(
+            [merkleTree.publicKey.toBuffer()],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+        const [bubblegumSigner] = PublicKey.findProgramAddressSync(
+            [Buffer.from("collection_cpi")],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const checkOut = (user: Keypair) =>
+            program.methods
+                .claimCompressedNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
+                .accounts({
+                    user: user.publicKey,
+                    config: configPda,
+                    instance: twoPhasePda,
+                    trainer: trainerPda,
+                    attendance: attendanceOf(user.publicKey),
+                    monthlyCounter: PublicKey.findProgramAddressSync(
+                        [Buffer.from("monthly"), user.publicKey.toBuffer(), yyyymmBuf],
+                        program.programId
+                    )[0],
+                    merkleTree: merkleTree.publicKey,
+                    treeConfig,
+                    nftAuthority,
+                    bubblegumSigner,
+                    collectionMint: null,
+                    collectionMetadata: null,
+                    collectionMasterEdition: null,
+                    collectionAuthorityRecord: null,
+                    bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+                    logWrapper: SPL_NOOP_PROGRAM_ID,
+                    compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    booking: bookingPda(twoPhasePda, user.publicKey),
+                    secretChain: null,
+                    instructionsSysvar: null,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([user])
+                .rpc();
+
+        // Only the check-out secret, without a check-in, is not enough
+        await expectError(checkOut(user2), "NotCheckedIn");
+        // Checking out straight after checking in is too early
+        await expectError(checkOut(user1), "CheckOutTooEarly");
+
+        await new Promise((resolve) => setTimeout(resolve, (minDurationSecs + 2) * 1000));
+        await checkOut(user1);
+
+        attendance = await program.account.attendance.fetch(attendanceOf(user1.publicKey));
+        expect(attendance.claimed).to.be.true;
+    });
+
+    it("Trainer grants attendance to a user without a phone", async () => {
+        // The attendee never signs, so their wallet doesn't need any SOL
+        const phoneless = Keypair.generate();
+
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+        const [attendancePda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("attendance"), instancePda.toBuffer(), phoneless.publicKey.toBuffer()],
+            program.programId
+        );
+        const [monthlyCounterPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("monthly"), phoneless.publicKey.toBuffer(), yyyymmBuf],
+            program.programId
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+
+        const grant = (signer: Keypair) => {
+            const nftMint = Keypair.generate();
+            const { metadata: nftMetadata, masterEdition: nftMasterEdition } = metadataPdas(nftMint.publicKey);
+            const nftTokenAccount = getAssociatedTokenAddressSync(nftMint.publicKey, phoneless.publicKey);
+            return {
+                nftMint,
+                nftTokenAccount,
+                tx: program.methods
+                    .grantAttendance({ noPhone: {} })
+                    .accounts({
+                        authority: signer.publicKey,
+                        admin: null,
+                        user: phoneless.publicKey,
+                        config: configPda,
+                        instance: instancePda,
+                        trainer: trainerPda,
+                        attendance: attendancePda,
+                        booking: bookingPda(instancePda, phoneless.publicKey),
+                        monthlyCounter: monthlyCounterPda,
+                        nftMint: nftMint.publicKey,
+                        nftTokenAccount,
+                        nftMetadata,
+                        nftMasterEdition,
+                        nftAuthority,
+                        collectionMint: null,
+                        collectionMetadata: null,
+                        collectionMasterEdition: null,
+                        collectionAuthorityRecord: null,
+                        tokenProgram: TOKEN_PROGRAM_ID,
+                        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                        systemProgram: SystemProgram.programId,
+                        rent: SYSVAR_RENT_PUBKEY,
+                    })
+                    .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+                    .signers([signer, nftMint])
+                    .rpc(),
+            };
+        };
+
+        // Someone who is neither an admin nor the trainer cannot grant attendance
+        await expectError(grant(user1).tx, "NotAssignedTrainer");
+
+        const { nftMint, nftTokenAccount, tx } = grant(trainer);
+        await tx;
+
+        const balance = await provider.connection.getTokenAccountBalance(nftTokenAccount);
+        expect(balance.value.amount).to.equal("1");
+
+        const attendance = await program.account.attendance.fetch(attendancePda);
+        expect(attendance.claimed).to.be.true;
+        expect(attendance.user.toBase58()).to.equal(phoneless.publicKey.toBase58());
+        expect(attendance.nftMint.toBase58()).to.equal(nftMint.publicKey.toBase58());
+
+        const counter = await program.account.monthlyCounter.fetch(monthlyCounterPda);
+        expect(counter.count).to.equal(1);
+
+        // Granting twice is rejected like a double claim
+        await expectError(grant(trainer).tx, "AlreadyClaimed");
+    });
+
+    it("Admin revokes an attendance, freezing the soulbound NFT", async () => {
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+        const [attendance3Pda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("attendance"), instancePda.toBuffer(), user3.publicKey.toBuffer()],
+            program.programId
+        );
+        const [monthlyCounter3Pda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("monthly"), user3.publicKey.toBuffer(), yyyymmBuf],
+            program.programId
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+
+        const before = await program.account.attendance.fetch(attendance3Pda);
+        const countBefore = (await program.account.monthlyCounter.fetch(monthlyCounter3Pda)).count;
+        const nftTokenAccount = getAssociatedTokenAddressSync(
+            before.nftMint,
+            user3.publicKey,
+            false,
+            TOKEN_2022_PROGRAM_ID
+        );
+
+        await program.methods
+            .revokeAttendance({ leakedSecret: {} }, null)
+            .accounts({
+                adminAuthority: admin2.publicKey,
+                admin: admin2Pda,
+                instance: instancePda,
+                attendance: attendance3Pda,
+                monthlyCounter: monthlyCounter3Pda,
+                nftMint: before.nftMint,
+                nftTokenAccount,
+                nftMetadata: null,
+                nftMasterEdition: null,
+                nftAuthority,
+                tokenProgram: TOKEN_2022_PROGRAM_ID,
+                tokenMetadataProgram: null,
+                leafOwner: null,
+                merkleTree: null,
+                treeConfig: null,
+                bubblegumProgram: null,
+                logWrapper: null,
+                compressionProgram: null,
+                systemProgram: null,
+            })
+            .signers([admin2])
+            .rpc();
+
+        const attendance = await program.account.attendance.fetch(attendance3Pda);
+        expect(attendance.revoked).to.be.true;
+        const counter = await program.account.monthlyCounter.fetch(monthlyCounter3Pda);
+        expect(counter.count).to.equal(countBefore - 1);
+        const tokenAccount = await getAccount(
+            provider.connection,
+            nftTokenAccount,
+            "confirmed",
+            TOKEN_2022_PROGRAM_ID
+        );
+        expect(tokenAccount.isFrozen).to.be.true;
+
+        // The revoked user cannot claim the same instance again
+        const nftMint = Keypair.generate();
+        await expectError(
+            program.methods
+                .claimNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
+                .accounts({
+                    user: user3.publicKey,
+                    config: configPda,
+                    instance: instancePda,
+                    trainer: trainerPda,
+                    attendance: attendance3Pda,
+                    monthlyCounter: monthlyCounter3Pda,
+                    nftMint: nftMint.publicKey,
+                    nftTokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, user3.publicKey),
+                    nftMetadata: null,
+                    nftMasterEdition: null,
+                    nftAuthority,
+                    collectionMint: null,
+                    collectionMetadata: null,
+                    collectionMasterEdition: null,
+                    collectionAuthorityRecord: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                    tokenMetadataProgram: null,
+                    booking: bookingPda(instancePda, user3.publicKey),
+                    secretChain: null,
+                    instructionsSysvar: null,
+                    systemProgram: SystemProgram.programId,
+                    rent: SYSVAR_RENT_PUBKEY,
+                })
+                .signers([user3, nftMint])
+                .rpc(),
+            "AttendanceRevoked"
+        );
+    });
+
+    it("Admin revokes a classic NFT, freezing the delegated token account", async () => {
+        const classicMinute = 1;
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+        const [classicPda] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("instance"),
+                trainer.publicKey.toBuffer(),
+                yyyymmddBuf,
+                Buffer.from([hour]),
+                Buffer.from([classicMinute]),
+            ],
+            program.programId
+        );
+        const [attendancePda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("attendance"), classicPda.toBuffer(), user2.publicKey.toBuffer()],
+            program.programId
+        );
+        const [monthlyCounterPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("monthly"), user2.publicKey.toBuffer(), yyyymmBuf],
+            program.programId
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+
+        const now = Math.floor(Date.now() / 1000);
+        await program.methods
+            .openWorkoutInstance(
+                yyyymmdd,
+                hour,
+                classicMinute,
+                new anchor.BN(now - 60),
+                new anchor.BN(now + 600),
+                Array.from(secretHash),
+                null,
+                { revealSecret: {} },
+                null,
+                null,
+                false,
+                null
+            )
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                config: configPda,
+                trainer: trainerPda,
+                instance: classicPda,
+                secretChain: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const nftMint = Keypair.generate();
+        const { metadata: nftMetadata, masterEdition: nftMasterEdition } = metadataPdas(nftMint.publicKey);
+        const nftTokenAccount = getAssociatedTokenAddressSync(nftMint.publicKey, user2.publicKey);
+        await program.methods
+            .claimNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
+            .accounts({
+                user: user2.publicKey,
+                config: configPda,
+                instance: classicPda,
+                trainer: trainerPda,
+                attendance: attendancePda,
+                monthlyCounter: monthlyCounterPda,
+                nftMint: nftMint.publicKey,
+                nftTokenAccount,
+                nftMetadata,
+                nftMasterEdition,
+                nftAuthority,
+                collectionMint: null,
+                collectionMetadata: null,
+                collectionMasterEdition: null,
+                collectionAuthorityRecord: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                booking: bookingPda(classicPda, user2.publicKey),
+                secretChain: null,
+                instructionsSysvar: null,
+                systemProgram: SystemProgram.programId,
+                rent: SYSVAR_RENT_PUBKEY,
+            })
+            .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+            .signers([user2, nftMint])
+            .rpc();
+
+        // The claim delegates the token to the program
+        const claimed = await getAccount(provider.connection, nftTokenAccount);
+        expect(claimed.delegate.toBase58()).to.equal(nftAuthority.toBase58());
+
+        await program.methods
+            .revokeAttendance({ leakedSecret: {} }, null)
+            .accounts({
+                adminAuthority: admin2.publicKey,
+                admin: admin2Pda,
+                instance: classicPda,
+                attendance: attendancePda,
+                monthlyCounter: monthlyCounterPda,
+                nftMint: nftMint.publicKey,
+                nftTokenAccount,
+                nftMetadata,
+                nftMasterEdition,
+                nftAuthority,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                leafOwner: null,
+                merkleTree: null,
+                treeConfig: null,
+                bubblegumProgram: null,
+                logWrapper: null,
+                compressionProgram: null,
+                systemProgram: null,
+            })
+            .signers([admin2])
+            .rpc();
+
+        expect((await program.account.attendance.fetch(attendancePda)).revoked).to.be.true;
+        const revoked = await getAccount(provider.connection, nftTokenAccount);
+        expect(revoked.isFrozen).to.be.true;
+        expect(revoked.amount.toString()).to.equal("1");
+        // The metadata is marked too: the name starts with "REVOKED"
+        const metadataInfo = await provider.connection.getAccountInfo(nftMetadata);
+        const nameLength = metadataInfo.data.readUInt32LE(65);
+        expect(metadataInfo.data.subarray(69, 69 + nameLength).toString()).to.match(/^REVOKED /);
+    });
+
+    it("Admin revokes a compressed NFT, burning the leaf", async () => {
+        const BUBBLEGUM_PROGRAM_ID = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+        const compressedMinute = 2;
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const yyyymmBuf = Buffer.alloc(4);
+        yyyymmBuf.writeUInt32LE(yyyymm);
+        const [compressedPda] = PublicKey.findProgramAddressSync(
             [
-                Buffer.from("metadata"),
-                TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-                nftMint.publicKey.toBuffer(),
-                Buffer.from("edition"),
+                Buffer.from("instance"),
+                trainer.publicKey.toBuffer(),
+                yyyymmddBuf,
+                Buffer.from([hour]),
+                Buffer.from([compressedMinute]),
             ],
-            TOKEN_METADATA_PROGRAM_ID
+            program.programId
+        );
+        const [attendancePda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("attendance"), compressedPda.toBuffer(), user2.publicKey.toBuffer()],
+            program.programId
+        );
+        const [monthlyCounterPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("monthly"), user2.publicKey.toBuffer(), yyyymmBuf],
+            program.programId
+        );
+        const [treeConfig] = PublicKey.findProgramAddressSync(
+            [merkleTree.publicKey.toBuffer()],
+            BUBBLEGUM_PROGRAM_ID
+        );
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
+        const [bubblegumSigner] = PublicKey.findProgramAddressSync(
+            [Buffer.from("collection_cpi")],
+            BUBBLEGUM_PROGRAM_ID
         );
 
-        const [nftTokenAccount] = PublicKey.findProgramAddressSync(
+        const now = Math.floor(Date.now() / 1000);
+        await program.methods
+            .openWorkoutInstance(
+                yyyymmdd,
+                hour,
+                compressedMinute,
+                new anchor.BN(now - 60),
+                new anchor.BN(now + 600),
+                Array.from(secretHash),
+                null,
+                { revealSecret: {} },
+                null,
+                null,
+                false,
+                null
+            )
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                config: configPda,
+                trainer: trainerPda,
+                instance: compressedPda,
+                secretChain: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        await program.methods
+            .claimCompressedNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
+            .accounts({
+                user: user2.publicKey,
+                config: configPda,
+                instance: compressedPda,
+                trainer: trainerPda,
+                attendance: attendancePda,
+                monthlyCounter: monthlyCounterPda,
+                merkleTree: merkleTree.publicKey,
+                treeConfig,
+                nftAuthority,
+                bubblegumSigner,
+                collectionMint: null,
+                collectionMetadata: null,
+                collectionMasterEdition: null,
+                collectionAuthorityRecord: null,
+                bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+                logWrapper: SPL_NOOP_PROGRAM_ID,
+                compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                booking: bookingPda(compressedPda, user2.publicKey),
+                secretChain: null,
+                instructionsSysvar: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([user2])
+            .rpc();
+
+        // The new leaf is the tree's rightmost one, whose proof the tree keeps on-chain
+        const tree = await ConcurrentMerkleTreeAccount.fromAccountAddress(
+            provider.connection,
+            merkleTree.publicKey
+        );
+        const root = tree.getCurrentRoot();
+        const proof = tree.tree.rightMostPath.proof;
+
+        await program.methods
+            .revokeAttendance({ leakedSecret: {} }, Array.from(root))
+            .accounts({
+                adminAuthority: admin2.publicKey,
+                admin: admin2Pda,
+                instance: compressedPda,
+                attendance: attendancePda,
+                monthlyCounter: monthlyCounterPda,
+                nftMint: null,
+                nftTokenAccount: null,
+                nftMetadata: null,
+                nftMasterEdition: null,
+                nftAuthority,
+                tokenProgram: null,
+                tokenMetadataProgram: null,
+                leafOwner: user2.publicKey,
+                merkleTree: merkleTree.publicKey,
+                treeConfig,
+                bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+                logWrapper: SPL_NOOP_PROGRAM_ID,
+                compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .remainingAccounts(proof.map((node) => ({ pubkey: node, isSigner: false, isWritable: false })))
+            .signers([admin2])
+            .rpc();
+
+        expect((await program.account.attendance.fetch(attendancePda)).revoked).to.be.true;
+        // Burning replaced the leaf with an empty one, changing the root
+        const after = await ConcurrentMerkleTreeAccount.fromAccountAddress(
+            provider.connection,
+            merkleTree.publicKey
+        );
+        expect(Buffer.from(after.getCurrentRoot()).equals(Buffer.from(root))).to.be.false;
+    });
+
+    it("Cancels a workout instance only before any claim", async () => {
+        const cancelledMinute = 25;
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const [cancelledPda] = PublicKey.findProgramAddressSync(
             [
-                user2.publicKey.toBuffer(),
-                TOKEN_PROGRAM_ID.toBuffer(),
-                nftMint.publicKey.toBuffer(),
+                Buffer.from("instance"),
+                trainer.publicKey.toBuffer(),
+                yyyymmddBuf,
+                Buffer.from([hour]),
+                Buffer.from([cancelledMinute]),
             ],
-            ASSOCIATED_TOKEN_PROGRAM_ID
+            program.programId
         );
+        const now = Math.floor(Date.now() / 1000);
+        await program.methods
+            .openWorkoutInstance(
+                yyyymmdd,
+                hour,
+                cancelledMinute,
+                new anchor.BN(now - 60),
+                new anchor.BN(now + 600),
+                Array.from(secretHash),
+                null,
+                { revealSecret: {} },
+                null,
+                null,
+                false,
+                null
+            )
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                config: configPda,
+                trainer: trainerPda,
+                instance: cancelledPda,
+                secretChain: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
 
-        const SYSVAR_INSTRUCTIONS_PUBKEY = new PublicKey("Sysvar1nstructions1111111111111111111111111");
-
-        try {
-            await program.methods
-                .claimNft(Array.from(wrongSecret))
+        const cancel = (instance: PublicKey) =>
+            program.methods
+                .cancelWorkoutInstance({ trainerUnavailable: {} })
                 .accounts({
-                    user: user2.publicKey,
-                    instance: instancePda,
+                    authority: trainer.publicKey,
+                    admin: null,
                     trainer: trainerPda,
-                    attendance: attendance2Pda,
-                    monthlyCounter: monthlyCounter2Pda,
-                    nftMint: nftMint.publicKey,
-                    nftTokenAccount: nftTokenAccount,
-                    nftMetadata: nftMetadata,
-                    nftMasterEdition: nftMasterEdition,
-                    sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
-                    tokenProgram: TOKEN_PROGRAM_ID,
-                    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                    instance,
+                })
+                .signers([trainer])
+                .rpc();
+
+        // The main instance already has claims
+        await expectError(cancel(instancePda), "ClaimsAlreadyMade");
+
+        await cancel(cancelledPda);
+        const instance = await program.account.workoutInstance.fetch(cancelledPda);
+        expect(instance.isClosed).to.be.true;
+        expect(instance.cancelReason).to.deep.equal({ trainerUnavailable: {} });
+
+        // Nobody can book a cancelled workout
+        await expectError(
+            program.methods
+                .bookSpot()
+                .accounts({
+                    user: user1.publicKey,
+                    instance: cancelledPda,
+                    booking: PublicKey.findProgramAddressSync(
+                        [Buffer.from("booking"), cancelledPda.toBuffer(), user1.publicKey.toBuffer()],
+                        program.programId
+                    )[0],
+                    waitlist: PublicKey.findProgramAddressSync(
+                        [Buffer.from("waitlist"), cancelledPda.toBuffer()],
+                        program.programId
+                    )[0],
+                    attendance: PublicKey.findProgramAddressSync(
+                        [Buffer.from("attendance"), cancelledPda.toBuffer(), user1.publicKey.toBuffer()],
+                        program.programId
+                    )[0],
                     systemProgram: SystemProgram.programId,
-                    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
                 })
-                .signers([user2, nftMint])
+                .signers([user1])
+                .rpc(),
+            "InstanceCancelled"
+        );
+    });
+
+    it("Trainer updates an open workout instance", async () => {
+        const update = (
+            windowStartTs: anchor.BN | null,
+            windowEndTs: anchor.BN | null,
+            metadataUriOverride: string | null
+        ) =>
+            program.methods
+                .updateWorkoutInstance(windowStartTs, windowEndTs, null, metadataUriOverride)
+                .accounts({
+                    authority: trainer.publicKey,
+                    admin: null,
+                    trainer: trainerPda,
+                    instance: instancePda,
+                })
+                .signers([trainer])
                 .rpc();
 
-            expect.fail("Should have thrown HashMismatch error");
-        } catch (err) {
-            expect(err).to.be.instanceOf(AnchorError);
-            expect((err as AnchorError).error.errorMessage).to.include("Hash mismatch");
-        }
+        const before = await program.account.workoutInstance.fetch(instancePda);
+
+        // The window must still end after it starts
+        await expectError(update(null, before.windowStartTs.subn(1), null), "InvalidTimeWindow");
+        // ... and must not have ended already
+        await expectError(update(null, before.windowStartTs.addn(1), null), "InvalidClaimWindow");
+
+        const newEnd = before.windowEndTs.addn(3600);
+        await update(null, newEnd, "https://poap.example.com/fixed.json");
+
+        let instance = await program.account.workoutInstance.fetch(instancePda);
+        expect(instance.windowStartTs.toString()).to.equal(before.windowStartTs.toString());
+        expect(instance.windowEndTs.toString()).to.equal(newEnd.toString());
+        expect(instance.metadataUriOverride).to.equal("https://poap.example.com/fixed.json");
+        expect(Buffer.from(instance.secretHash)).to.deep.equal(secretHash);
+
+        // An empty URI removes the override again
+        await update(null, null, "");
+        instance = await program.account.workoutInstance.fetch(instancePda);
+        expect(instance.metadataUriOverride).to.equal("");
     });
 
     it("Closes workout instance", async () => {
@@ -503,26 +3213,8 @@ describe("workout_poap", () => {
         );
 
         const nftMint = Keypair.generate();
-        const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
-
-        const [nftMetadata] = PublicKey.findProgramAddressSync(
-            [
-                Buffer.from("metadata"),
-                TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-                nftMint.publicKey.toBuffer(),
-            ],
-            TOKEN_METADATA_PROGRAM_ID
-        );
 
-        const [nftMasterEdition] = PublicKey.findProgramAddressSync(
-            [
-                Buffer.from("metadata"),
-                TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-                nftMint.publicKey.toBuffer(),
-                Buffer.from("edition"),
-            ],
-            TOKEN_METADATA_PROGRAM_ID
-        );
+        const { metadata: nftMetadata, masterEdition: nftMasterEdition } = metadataPdas(nftMint.publicKey);
 
         const [nftTokenAccount] = PublicKey.findProgramAddressSync(
             [
@@ -533,13 +3225,17 @@ describe("workout_poap", () => {
             ASSOCIATED_TOKEN_PROGRAM_ID
         );
 
-        const SYSVAR_INSTRUCTIONS_PUBKEY = new PublicKey("Sysvar1nstructions1111111111111111111111111");
+        const [nftAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_authority")],
+            program.programId
+        );
 
         try {
             await program.methods
-                .claimNft(Array.from(revealSecret))
+                .claimNft({ revealSecret: { secret: Array.from(revealSecret) } }, [])
                 .accounts({
                     user: user2.publicKey,
+                    config: configPda,
                     instance: instancePda,
                     trainer: trainerPda,
                     attendance: attendance2Pda,
@@ -548,11 +3244,15 @@ describe("workout_poap", () => {
                     nftTokenAccount: nftTokenAccount,
                     nftMetadata: nftMetadata,
                     nftMasterEdition: nftMasterEdition,
-                    sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+                    nftAuthority: nftAuthority,
                     tokenProgram: TOKEN_PROGRAM_ID,
                     associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                    booking: bookingPda(instancePda, user2.publicKey),
+                    secretChain: null,
+                instructionsSysvar: null,
                     systemProgram: SystemProgram.programId,
                     tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                    rent: SYSVAR_RENT_PUBKEY,
                 })
                 .signers([user2, nftMint])
                 .rpc();
@@ -564,6 +3264,284 @@ describe("workout_poap", () => {
         }
     });
 
+    it("Reclaims rent of ended workout instances", async () => {
+        const shortMinute = 15;
+        const yyyymmddBuf = Buffer.alloc(4);
+        yyyymmddBuf.writeUInt32LE(yyyymmdd);
+        const [shortPda] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("instance"),
+                trainer.publicKey.toBuffer(),
+                yyyymmddBuf,
+                Buffer.from([hour]),
+                Buffer.from([shortMinute]),
+            ],
+            program.programId
+        );
+        const now = Math.floor(Date.now() / 1000);
+        await program.methods
+            .openWorkoutInstance(
+                yyyymmdd,
+                hour,
+                shortMinute,
+                new anchor.BN(now - 60),
+                new anchor.BN(now + 4),
+                Array.from(secretHash),
+                null,
+                { revealSecret: {} },
+                null,
+                null,
+                false,
+                null
+            )
+            .accounts({
+                authority: authority.publicKey,
+                admin: admin2Pda,
+                config: configPda,
+                trainer: trainerPda,
+                instance: shortPda,
+                secretChain: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        // user1 books the short workout, creating its waitlist
+        const [shortWaitlistPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("waitlist"), shortPda.toBuffer()],
+            program.programId
+        );
+        const [shortBookingPda] = PublicKey.findProgramAddressSync(
+            [Buffer.from("booking"), shortPda.toBuffer(), user1.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods
+            .bookSpot()
+            .accounts({
+                user: user1.publicKey,
+                instance: shortPda,
+                booking: shortBookingPda,
+                waitlist: shortWaitlistPda,
+                attendance: PublicKey.findProgramAddressSync(
+                    [Buffer.from("attendance"), shortPda.toBuffer(), user1.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([user1])
+            .rpc();
+        const closeBooking = () =>
+            program.methods
+                .closeBooking()
+                .accounts({
+                    user: user1.publicKey,
+                    booking: shortBookingPda,
+                    instance: shortPda,
+                })
+                .signers([user1])
+                .rpc();
+
+        const reclaim = (instance: PublicKey, waitlist: PublicKey | null = null) =>
+            program.methods
+                .reclaimWorkoutInstance()
+                .accounts({
+                    caller: user1.publicKey,
+                    instance,
+                    payer: authority.publicKey,
+                    waitlist,
+                    waitlistPayer: waitlist ? user1.publicKey : null,
+                })
+                .signers([user1])
+                .rpc();
+
+        // The main instance's claim window is still running
+        await expectError(reclaim(instancePda), "ClaimWindowNotEnded");
+
+        // Attendance can't be closed while its instance still exists
+        await expectError(
+            program.methods
+                .closeAttendance()
+                .accounts({
+                    user: user1.publicKey,
+                    attendance: attendance1Pda,
+                    instance: instancePda,
+                })
+                .signers([user1])
+                .rpc(),
+            "InstanceNotReclaimed"
+        );
+
+        // Neither can the booking
+        await expectError(closeBooking(), "InstanceNotReclaimed");
+
+        await new Promise((resolve) => setTimeout(resolve, 6000));
+        const rent = (await provider.connection.getAccountInfo(shortPda)).lamports;
+        const balanceBefore = await provider.connection.getBalance(authority.publicKey);
+
+        const waitlistRent = (await provider.connection.getAccountInfo(shortWaitlistPda)).lamports;
+        const user1Before = await provider.connection.getBalance(user1.publicKey);
+
+        // Anyone can crank it; the rent goes back to the payer, the waitlist's
+        // to the user who created it
+        await reclaim(shortPda, shortWaitlistPda);
+
+        expect(await provider.connection.getAccountInfo(shortPda)).to.be.null;
+        expect(await provider.connection.getAccountInfo(shortWaitlistPda)).to.be.null;
+        // The provider wallet pays the fee for both signatures
+        const balanceAfter = await provider.connection.getBalance(authority.publicKey);
+        expect(balanceAfter - balanceBefore).to.equal(rent - 2 * 5000);
+        const user1After = await provider.connection.getBalance(user1.publicKey);
+        expect(user1After - user1Before).to.equal(waitlistRent);
+
+        // The leftover booking can now be closed
+        await closeBooking();
+        expect(await provider.connection.getAccountInfo(shortBookingPda)).to.be.null;
+    });
+
+    it("Closes expired attendance records and monthly counters", async () => {
+        // Loaded at genesis from tests/fixtures (see Anchor.toml): a former
+        // member's attendance at a January 2024 workout whose instance is long
+        // gone, and their monthly counter for that month
+        const fixture = (name: string) =>
+            JSON.parse(fs.readFileSync(`tests/fixtures/${name}.json`, "utf8"));
+        const formerMember = Keypair.fromSecretKey(Uint8Array.from(fixture("former_member")));
+        const attendancePda = new PublicKey(fixture("expired_attendance").pubkey);
+        const counterPda = new PublicKey(fixture("expired_monthly_counter").pubkey);
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(formerMember.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+        );
+
+        // This month's counter is still within its retention period
+        await expectError(
+            program.methods
+                .closeMonthlyCounter()
+                .accounts({
+                    user: user1.publicKey,
+                    monthlyCounter: monthlyCounter1Pda,
+                })
+                .signers([user1])
+                .rpc(),
+            "RetentionPeriodActive"
+        );
+
+        const attendance = await program.account.attendance.fetch(attendancePda);
+        expect(attendance.user.toBase58()).to.equal(formerMember.publicKey.toBase58());
+        const attendanceRent = (await provider.connection.getAccountInfo(attendancePda)).lamports;
+        let balanceBefore = await provider.connection.getBalance(formerMember.publicKey);
+        await program.methods
+            .closeAttendance()
+            .accounts({
+                user: formerMember.publicKey,
+                attendance: attendancePda,
+                instance: attendance.instance,
+            })
+            .signers([formerMember])
+            .rpc();
+        expect(await provider.connection.getAccountInfo(attendancePda)).to.be.null;
+        let balanceAfter = await provider.connection.getBalance(formerMember.publicKey);
+        expect(balanceAfter - balanceBefore).to.equal(attendanceRent);
+
+        const counter = await program.account.monthlyCounter.fetch(counterPda);
+        expect(counter.yyyymm).to.equal(202401);
+        const counterRent = (await provider.connection.getAccountInfo(counterPda)).lamports;
+        balanceBefore = balanceAfter;
+        await program.methods
+            .closeMonthlyCounter()
+            .accounts({
+                user: formerMember.publicKey,
+                monthlyCounter: counterPda,
+            })
+            .signers([formerMember])
+            .rpc();
+        expect(await provider.connection.getAccountInfo(counterPda)).to.be.null;
+        balanceAfter = await provider.connection.getBalance(formerMember.publicKey);
+        expect(balanceAfter - balanceBefore).to.equal(counterRent);
+    });
+
+    it("Migrates a workout instance and attendance from the first program version", async () => {
+        // Loaded at genesis from tests/fixtures (see Anchor.toml): a 308-byte
+        // WorkoutInstance of a January 2024 workout and the former member's
+        // 106-byte Attendance for it
+        const formerMember = Keypair.fromSecretKey(
+            Uint8Array.from(JSON.parse(fs.readFileSync("tests/fixtures/former_member.json", "utf8")))
+        );
+        const legacyInstancePda = new PublicKey("6H68ets4J1RHRopxqEY3iE6pzzPKXrb8JhGDCWmA8vEA");
+        const legacyAttendancePda = new PublicKey("98RdAJzbuEgaKMvwEQWmVZzJgEh9r9DdmuBBsU5EUTGy");
+        const legacyInfo = await provider.connection.getAccountInfo(legacyInstancePda);
+        expect(legacyInfo.data.length).to.equal(308);
+        expect((await provider.connection.getAccountInfo(legacyAttendancePda)).data.length).to.equal(106);
+        const legacyTrainer = new PublicKey(legacyInfo.data.subarray(8, 40));
+
+        const migrateInstance = (signer?: Keypair) =>
+            program.methods
+                .migrateWorkoutInstance(legacyTrainer, 20240102, 9, 30)
+                .accounts({
+                    authority: (signer ?? authority).publicKey,
+                    config: configPda,
+                    instance: legacyInstancePda,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers(signer ? [signer] : [])
+                .rpc();
+        const migrateAttendance = () =>
+            program.methods
+                .migrateAttendance(legacyTrainer, 20240102, 9, 30)
+                .accounts({
+                    user: formerMember.publicKey,
+                    config: configPda,
+                    instance: legacyInstancePda,
+                    attendance: legacyAttendancePda,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([formerMember])
+                .rpc();
+
+        // Only the super-admin can migrate instances
+        await expectError(migrateInstance(user1), "NotConfigAuthority");
+
+        // The migrated instance keeps its window and becomes the caller's to reclaim
+        await migrateInstance();
+        const instance = await program.account.workoutInstance.fetch(legacyInstancePda);
+        expect(instance.trainer.toBase58()).to.equal(legacyTrainer.toBase58());
+        expect(instance.yyyymmdd).to.equal(20240102);
+        expect(instance.claimMethod).to.deep.equal({ revealSecret: {} });
+        expect(instance.payer.toBase58()).to.equal(authority.publicKey.toBase58());
+        await expectError(migrateInstance(), "AlreadyMigrated");
+
+        await program.methods
+            .reclaimWorkoutInstance()
+            .accounts({
+                caller: authority.publicKey,
+                instance: legacyInstancePda,
+                payer: authority.publicKey,
+                waitlist: null,
+                waitlistPayer: null,
+            })
+            .rpc();
+        expect(await provider.connection.getAccountInfo(legacyInstancePda)).to.be.null;
+
+        // The attendance is counted in the workout's month, claimed at its time
+        await migrateAttendance();
+        const attendance = await program.account.attendance.fetch(legacyAttendancePda);
+        expect(attendance.user.toBase58()).to.equal(formerMember.publicKey.toBase58());
+        expect(attendance.instance.toBase58()).to.equal(legacyInstancePda.toBase58());
+        expect(attendance.claimed).to.be.true;
+        expect(attendance.revoked).to.be.false;
+        expect(attendance.countedYyyymm).to.equal(202401);
+        await expectError(migrateAttendance(), "AlreadyMigrated");
+
+        // Its retention period is long over, so the user can close it
+        await program.methods
+            .closeAttendance()
+            .accounts({
+                user: formerMember.publicKey,
+                attendance: legacyAttendancePda,
+                instance: legacyInstancePda,
+            })
+            .signers([formerMember])
+            .rpc();
+        expect(await provider.connection.getAccountInfo(legacyAttendancePda)).to.be.null;
+    });
+
     it("Removes an admin", async () => {
         const tx = await program.methods
             .removeAdmin()
@@ -584,5 +3562,55 @@ describe("workout_poap", () => {
             expect(err.message).to.include("Account does not exist");
         }
     });
+
+    it("Transfers the super-admin role in two steps", async () => {
+        const newAuthority = Keypair.generate();
+        const propose = (from: Keypair | null, to: PublicKey) => {
+            const builder = program.methods.proposeAuthorityTransfer(to).accounts({
+                authority: from ? from.publicKey : authority.publicKey,
+                config: configPda,
+            });
+            return from ? builder.signers([from]).rpc() : builder.rpc();
+        };
+        const accept = (signer: Keypair | null) => {
+            const builder = program.methods.acceptAuthorityTransfer().accounts({
+                newAuthority: signer ? signer.publicKey : authority.publicKey,
+                config: configPda,
+            });
+            return signer ? builder.signers([signer]).rpc() : builder.rpc();
+        };
+        // A proposal changes nothing until the new key accepts
+        await propose(null, newAuthority.publicKey);
+        let config = await program.account.config.fetch(configPda);
+        expect(config.authority.toBase58()).to.equal(authority.publicKey.toBase58());
+        expect(config.pendingAuthority.toBase58()).to.equal(newAuthority.publicKey.toBase58());
+        await expectError(accept(user1), "NotPendingAuthority");
+
+        // Cancelling clears the proposal
+        await program.methods
+            .cancelAuthorityTransfer()
+            .accounts({
+                authority: authority.publicKey,
+                config: configPda,
+            })
+            .rpc();
+        config = await program.account.config.fetch(configPda);
+        expect(config.pendingAuthority).to.be.null;
+        await expectError(accept(newAuthority), "NoPendingAuthorityTransfer");
+
+        // Propose again and accept with the new key
+        await propose(null, newAuthority.publicKey);
+        await accept(newAuthority);
+        config = await program.account.config.fetch(configPda);
+        expect(config.authority.toBase58()).to.equal(newAuthority.publicKey.toBase58());
+        expect(config.pendingAuthority).to.be.null;
+
+        // The old super-admin has lost control; hand the role back
+        await expectError(propose(null, authority.publicKey), "NotConfigAuthority");
+        await propose(newAuthority, authority.publicKey);
+        await accept(null);
+        config = await program.account.config.fetch(configPda);
+        expect(config.authority.toBase58()).to.equal(authority.publicKey.toBase58());
+    });
 });
 